@@ -0,0 +1,185 @@
+use crate::notacao::ErroNotacao;
+use crate::pdn::JogoPdn;
+use crate::Partida;
+
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::sync::OnceLock;
+
+// Catálogo de aberturas com nome. Cada abertura é guardada pela chave da posição
+// em que termina, então uma partida que chega lá por outra ordem de lances
+// recebe o mesmo nome. A classificação fica com a abertura mais longa entre as
+// posições por onde a partida passou.
+
+// Código, nome e lances em notação algébrica, separados por tabulação. Os
+// códigos começam com a letra do primeiro lance das brancas.
+const CATALOGO: &str = "\
+A00\tAbertura c3-d4\tc3-d4
+A10\tPartida da Cidade\tc3-d4 d6-c5 b2-c3 c7-d6
+A11\tPartida da Cidade Recusada\tc3-d4 d6-c5 b2-c3 f6-g5
+A20\tCruz\tc3-d4 f6-e5 d4:f6 g7:e5
+A21\tCruz, tomada com e7\tc3-d4 f6-e5 d4:f6 e7:g5
+A30\tToque\tc3-d4 b6-c5 d4:b6 a7:c5
+A40\tEstaca\tc3-d4 b6-a5
+A50\tAtaque Clássico\tc3-d4 f6-g5 g3-f4
+A60\tPartida Dupla\tc3-d4 f6-g5 b2-c3 g7-f6
+B00\tAbertura c3-b4\tc3-b4
+C00\tBodiansky Invertida\ta3-b4
+D00\tAbertura e3-f4\te3-f4
+E00\tAbertura e3-d4\te3-d4
+F00\tBodiansky\tg3-h4
+G00\tAbertura g3-f4\tg3-f4
+";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErroCatalogo {
+    /// Linha do catálogo, a partir de 1, sem os três campos.
+    Linha(usize),
+    /// Lance que não pode ser jogado na linha dada.
+    Lance(usize, ErroNotacao),
+}
+
+impl Display for ErroCatalogo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErroCatalogo::Linha(linha) => write!(f, "linha {linha}: use código, nome e lances separados por tabulação"),
+            ErroCatalogo::Lance(linha, erro) => write!(f, "linha {linha}: {erro}"),
+        }
+    }
+}
+
+impl std::error::Error for ErroCatalogo {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Abertura {
+    pub codigo: String,
+    pub nome: String,
+    /// Lances a partir da posição inicial, como foram escritos no catálogo.
+    pub lances: Vec<String>,
+}
+
+impl Display for Abertura {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.codigo, self.nome)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Catalogo {
+    aberturas: Vec<Abertura>,
+    por_posicao: HashMap<u64, usize>,
+}
+
+impl Catalogo {
+    /// O catálogo embutido no crate.
+    pub fn padrao() -> &'static Catalogo {
+        static PADRAO: OnceLock<Catalogo> = OnceLock::new();
+        PADRAO.get_or_init(|| Catalogo::ler(CATALOGO).unwrap())
+    }
+
+    /// Lê um catálogo no formato do embutido: código, nome e lances separados
+    /// por tabulação, uma abertura por linha. Linhas com # são comentários.
+    pub fn ler(texto: &str) -> Result<Catalogo, ErroCatalogo> {
+        let mut catalogo = Catalogo::default();
+        for (i, linha) in texto.lines().enumerate() {
+            if linha.trim().is_empty() || linha.starts_with('#') {
+                continue;
+            }
+            let campos: Vec<&str> = linha.split('\t').collect();
+            let [codigo, nome, lances] = campos[..] else { return Err(ErroCatalogo::Linha(i + 1)) };
+            let abertura = Abertura {
+                codigo: codigo.trim().to_string(),
+                nome: nome.trim().to_string(),
+                lances: lances.split_whitespace().map(str::to_string).collect(),
+            };
+            catalogo.acrescentar(abertura).map_err(|erro| ErroCatalogo::Lance(i + 1, erro))?;
+        }
+        Ok(catalogo)
+    }
+
+    /// Inclui uma abertura. Se outra já termina na mesma posição, fica a primeira.
+    pub fn acrescentar(&mut self, abertura: Abertura) -> Result<(), ErroNotacao> {
+        let mut partida = Partida::default();
+        for lance in &abertura.lances {
+            partida.jogar(partida.encontrar_jogada(lance)?);
+        }
+        self.por_posicao.entry(partida.chave()).or_insert(self.aberturas.len());
+        self.aberturas.push(abertura);
+        Ok(())
+    }
+
+    pub fn aberturas(&self) -> &[Abertura] {
+        &self.aberturas
+    }
+
+    /// A abertura que termina exatamente nesta posição.
+    pub fn na_posicao(&self, partida: &Partida) -> Option<&Abertura> {
+        self.por_posicao.get(&partida.chave()).map(|&i| &self.aberturas[i])
+    }
+
+    /// A abertura mais longa por onde a partida passou, reproduzindo o
+    /// histórico. Partidas que não começam na posição inicial não têm abertura.
+    pub fn classificar(&self, partida: &Partida) -> Option<&Abertura> {
+        let mut posicao = partida.posicao_inicial();
+        if posicao.chave() != Partida::default().chave() {
+            return None;
+        }
+        let mut melhor = None;
+        let mut lances = partida.historico().iter();
+        loop {
+            if let Some(abertura) = self.na_posicao(&posicao) {
+                if melhor.is_none_or(|melhor: &Abertura| abertura.lances.len() > melhor.lances.len()) {
+                    melhor = Some(abertura);
+                }
+            }
+            let Some(sequencia) = lances.next() else { break };
+            let Some(indice) = posicao.todas_jogadas_possiveis().iter().position(|possivel| possivel == sequencia) else {
+                break;
+            };
+            posicao.jogar(indice);
+        }
+        melhor
+    }
+
+    /// Põe as tags Opening e OpeningCode no jogo, se a partida tiver abertura.
+    pub fn marcar(&self, jogo: &mut JogoPdn, partida: &Partida) {
+        if let Some(abertura) = self.classificar(partida) {
+            jogo.set_tag("Opening", &abertura.nome);
+            jogo.set_tag("OpeningCode", &abertura.codigo);
+        }
+    }
+}
+
+#[test]
+fn testar_aberturas() {
+    let catalogo = Catalogo::padrao();
+    assert_eq!(catalogo.aberturas().len(), CATALOGO.lines().count());
+
+    let jogar = |lances: &[&str]| {
+        let mut partida = Partida::default();
+        for lance in lances {
+            partida.jogar(partida.encontrar_jogada(lance).unwrap());
+        }
+        partida
+    };
+    // 22-18 é c3-d4; a partida segue além da abertura
+    let cidade = jogar(&["22-18", "d6-c5", "b2-c3", "c7-d6", "c3-b4"]);
+    assert_eq!(catalogo.classificar(&cidade).unwrap().nome, "Partida da Cidade");
+    let cruz = jogar(&["c3-d4", "f6-e5", "d4:f6", "g7:e5"]);
+    assert_eq!(catalogo.classificar(&cruz).unwrap().codigo, "A20");
+    assert_eq!(catalogo.classificar(&jogar(&["c3-d4", "h6-g5"])).unwrap().codigo, "A00");
+    assert_eq!(catalogo.classificar(&Partida::default()), None);
+
+    // Transposição: a mesma posição com os lances das brancas trocados
+    let mut proprio = Catalogo::ler("# comentário\nX1\tTeste\tc3-d4 h6-g5 g3-h4").unwrap();
+    assert_eq!(proprio.classificar(&jogar(&["g3-h4", "h6-g5", "c3-d4", "g7-h6"])).unwrap().codigo, "X1");
+    proprio
+        .acrescentar(Abertura { codigo: "X2".to_string(), nome: "Outra".to_string(), lances: vec!["a3-b4".to_string()] })
+        .unwrap();
+    assert_eq!(proprio.classificar(&jogar(&["a3-b4", "f6-g5"])).unwrap().codigo, "X2");
+    assert!(matches!(Catalogo::ler("X\tRuim\tc3-e5"), Err(ErroCatalogo::Lance(1, _))));
+
+    let mut jogo = JogoPdn::de_partida(&cruz);
+    catalogo.marcar(&mut jogo, &cruz);
+    assert_eq!(jogo.tag("Opening"), Some("Cruz"));
+}
@@ -0,0 +1,499 @@
+use crate::notacao::ErroNotacao;
+use crate::pdn::{self, ErroPdn, JogoPdn};
+use crate::{Material, Partida};
+
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// Acervo de partidas com índices por posição e por material. Cada lance é
+// guardado como um byte, o índice da sequência em `todas_jogadas_possiveis`;
+// os índices são refeitos ao abrir o arquivo, reproduzindo as partidas.
+//
+// Arquivo: "DACV", versão (1 byte) e as partidas, cada uma com as tags
+// (quantidade e pares de textos), o resultado, o FEN inicial (vazio para a
+// posição padrão) e os lances. Textos têm o tamanho em 2 bytes na frente.
+
+const MAGICO: &[u8; 4] = b"DACV";
+const VERSAO: u8 = 1;
+// Quantidades e tamanhos de texto vão em 2 bytes no arquivo, e o lance de um
+// achado vai de 0 ao total de lances
+const LIMITE: usize = u16::MAX as usize;
+
+#[derive(Debug)]
+pub enum ErroAcervo {
+    Io(io::Error),
+    /// O arquivo não é um acervo ou está corrompido.
+    Formato(String),
+    Pdn(ErroPdn),
+}
+
+impl Display for ErroAcervo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErroAcervo::Io(erro) => write!(f, "{erro}"),
+            ErroAcervo::Formato(motivo) => write!(f, "acervo inválido: {motivo}"),
+            ErroAcervo::Pdn(erro) => write!(f, "{erro}"),
+        }
+    }
+}
+
+impl std::error::Error for ErroAcervo {}
+
+impl From<io::Error> for ErroAcervo {
+    fn from(erro: io::Error) -> Self {
+        ErroAcervo::Io(erro)
+    }
+}
+
+/// Uma partida do acervo.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Jogo {
+    pub tags: Vec<(String, String)>,
+    pub resultado: String,
+    fen: Option<String>,
+    lances: Vec<u8>,
+}
+
+impl Jogo {
+    pub fn tag(&self, nome: &str) -> Option<&str> {
+        self.tags.iter().find(|(chave, _)| chave == nome).map(|(_, valor)| valor.as_str())
+    }
+
+    /// Número de lances (meios-lances) da partida.
+    pub fn lances(&self) -> usize {
+        self.lances.len()
+    }
+
+    /// Índice em `todas_jogadas_possiveis` da jogada feita depois de `lance` lances.
+    pub fn jogada(&self, lance: usize) -> Option<usize> {
+        self.lances.get(lance).map(|&indice| indice as usize)
+    }
+
+    pub fn posicao_inicial(&self) -> Partida {
+        match &self.fen {
+            // O FEN foi validado na importação
+            Some(fen) => Partida::from_fen(fen).unwrap(),
+            None => Partida::default(),
+        }
+    }
+
+    /// A partida depois dos primeiros `lance` lances.
+    pub fn partida_ate(&self, lance: usize) -> Partida {
+        let mut partida = self.posicao_inicial();
+        for &indice in self.lances.iter().take(lance) {
+            partida.jogar(indice as usize);
+        }
+        partida
+    }
+
+    pub fn partida(&self) -> Partida {
+        self.partida_ate(self.lances.len())
+    }
+
+    pub fn pdn(&self) -> JogoPdn {
+        let partida = self.partida();
+        let mut jogo = JogoPdn::de_partida(&partida);
+        for (chave, valor) in &self.tags {
+            jogo.set_tag(chave, valor);
+        }
+        // Desistências e acordos não aparecem nos lances
+        if !partida.encerrada() {
+            jogo.resultado = self.resultado.clone();
+        }
+        jogo
+    }
+
+    fn de_pdn(pdn: &JogoPdn) -> Result<Jogo, ErroPdn> {
+        // O que não cabe no arquivo é recusado aqui, e não cortado ao salvar
+        if pdn.lances.len() >= LIMITE {
+            return Err(ErroPdn::Malformado(format!("{} lances; o acervo guarda menos de {LIMITE}", pdn.lances.len())));
+        }
+        if pdn.tags.len() > LIMITE {
+            return Err(ErroPdn::Malformado(format!("{} tags; o acervo guarda até {LIMITE}", pdn.tags.len())));
+        }
+        let mut textos = pdn.tags.iter().flat_map(|(chave, valor)| [chave, valor]).chain([&pdn.resultado]);
+        if let Some(texto) = textos.find(|texto| texto.len() > LIMITE) {
+            let inicio: String = texto.chars().take(20).collect();
+            return Err(ErroPdn::Malformado(format!("texto \"{inicio}...\" com mais de {LIMITE} bytes")));
+        }
+        let mut partida = pdn.posicao_inicial()?;
+        let mut lances = Vec::with_capacity(pdn.lances.len());
+        for (i, lance) in pdn.lances.iter().enumerate() {
+            let indice = partida.encontrar_jogada(lance).map_err(|erro| ErroPdn::Jogada { lance: i + 1, erro })?;
+            let indice = u8::try_from(indice)
+                .map_err(|_| ErroPdn::Jogada { lance: i + 1, erro: ErroNotacao::Ilegal(lance.clone()) })?;
+            lances.push(indice);
+            partida.jogar(indice as usize);
+        }
+        let fen = pdn.tag("FEN").map(|_| pdn.posicao_inicial().unwrap().fen());
+        let tags = pdn.tags.iter().filter(|(chave, _)| chave != "FEN").cloned().collect();
+        Ok(Jogo { tags, resultado: pdn.resultado.clone(), fen, lances })
+    }
+}
+
+/// Uma partida encontrada e o lance em que a busca casou.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Achado {
+    pub jogo: usize,
+    pub lance: usize,
+}
+
+/// Filtros de uma busca; os que forem dados precisam valer todos ao mesmo tempo.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Consulta {
+    /// Nome nas tags White ou Black.
+    pub jogador: Option<String>,
+    pub branco: Option<String>,
+    pub preto: Option<String>,
+    /// Limites da tag Date, no formato do PDN (AAAA.MM.DD), inclusivos.
+    pub desde: Option<String>,
+    pub ate: Option<String>,
+    /// Resultado em qualquer formato; 1-0 e 2-0 são o mesmo.
+    pub resultado: Option<String>,
+    pub posicao: Option<u64>,
+    pub material: Option<Material>,
+    /// Primeiros lances em notação, a partir da posição inicial padrão.
+    pub prefixo: Vec<String>,
+}
+
+impl Consulta {
+    pub fn jogador(mut self, nome: &str) -> Self {
+        self.jogador = Some(nome.to_string());
+        self
+    }
+
+    pub fn branco(mut self, nome: &str) -> Self {
+        self.branco = Some(nome.to_string());
+        self
+    }
+
+    pub fn preto(mut self, nome: &str) -> Self {
+        self.preto = Some(nome.to_string());
+        self
+    }
+
+    pub fn periodo(mut self, desde: Option<&str>, ate: Option<&str>) -> Self {
+        self.desde = desde.map(str::to_string);
+        self.ate = ate.map(str::to_string);
+        self
+    }
+
+    pub fn resultado(mut self, resultado: &str) -> Self {
+        self.resultado = Some(resultado.to_string());
+        self
+    }
+
+    pub fn posicao(mut self, partida: &Partida) -> Self {
+        self.posicao = Some(partida.chave());
+        self
+    }
+
+    pub fn material(mut self, material: Material) -> Self {
+        self.material = Some(material);
+        self
+    }
+
+    pub fn prefixo(mut self, lances: &[&str]) -> Self {
+        self.prefixo = lances.iter().map(|lance| lance.to_string()).collect();
+        self
+    }
+}
+
+fn resultado_normalizado(resultado: &str) -> &str {
+    match pdn::vencedor(resultado) {
+        Some(vencedor) => pdn::resultado(vencedor),
+        None => resultado,
+    }
+}
+
+// Primeiro lance em que cada partida aparece numa entrada do índice
+fn primeiros(entradas: Option<&Vec<(u32, u16)>>) -> Vec<Achado> {
+    let mut primeiros: Vec<Achado> = vec![];
+    for &(jogo, lance) in entradas.into_iter().flatten() {
+        if primeiros.last().is_none_or(|achado| achado.jogo != jogo as usize) {
+            primeiros.push(Achado { jogo: jogo as usize, lance: lance as usize });
+        }
+    }
+    primeiros
+}
+
+/// Quantas partidas entraram numa importação e os erros das que ficaram de fora,
+/// pela posição do jogo no PDN a partir de 1.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Importacao {
+    pub importados: usize,
+    pub erros: Vec<(usize, ErroPdn)>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Acervo {
+    jogos: Vec<Jogo>,
+    posicoes: HashMap<u64, Vec<(u32, u16)>>,
+    materiais: HashMap<Material, Vec<(u32, u16)>>,
+}
+
+impl Acervo {
+    /// Lê o arquivo; se ele não existe, começa um acervo vazio.
+    pub fn abrir(caminho: impl AsRef<Path>) -> Result<Acervo, ErroAcervo> {
+        match fs::read(caminho) {
+            Ok(bytes) => Acervo::de_bytes(&bytes),
+            Err(erro) if erro.kind() == io::ErrorKind::NotFound => Ok(Acervo::default()),
+            Err(erro) => Err(erro.into()),
+        }
+    }
+
+    pub fn salvar(&self, caminho: impl AsRef<Path>) -> Result<(), ErroAcervo> {
+        fs::write(caminho, self.bytes())?;
+        Ok(())
+    }
+
+    pub fn jogos(&self) -> &[Jogo] {
+        &self.jogos
+    }
+
+    pub fn jogo(&self, indice: usize) -> Option<&Jogo> {
+        self.jogos.get(indice)
+    }
+
+    pub fn len(&self) -> usize {
+        self.jogos.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jogos.is_empty()
+    }
+
+    /// Importa todas as partidas de um texto PDN. Partidas com lances ilegais
+    /// ficam de fora e aparecem em `Importacao::erros`.
+    pub fn importar(&mut self, texto: &str) -> Result<Importacao, ErroAcervo> {
+        let mut importacao = Importacao::default();
+        for (i, pdn) in pdn::ler(texto).map_err(ErroAcervo::Pdn)?.iter().enumerate() {
+            match Jogo::de_pdn(pdn) {
+                Ok(jogo) => {
+                    self.adicionar(jogo);
+                    importacao.importados += 1;
+                }
+                Err(erro) => importacao.erros.push((i + 1, erro)),
+            }
+        }
+        Ok(importacao)
+    }
+
+    fn adicionar(&mut self, jogo: Jogo) {
+        let id = self.jogos.len() as u32;
+        let mut partida = jogo.posicao_inicial();
+        for lance in 0..=jogo.lances.len() {
+            // `de_pdn` e `de_bytes` garantem menos de LIMITE lances
+            let lance16 = u16::try_from(lance).expect("jogo com lances demais para o acervo");
+            self.posicoes.entry(partida.chave()).or_default().push((id, lance16));
+            self.materiais.entry(partida.material()).or_default().push((id, lance16));
+            if let Some(&indice) = jogo.lances.get(lance) {
+                partida.jogar(indice as usize);
+            }
+        }
+        self.jogos.push(jogo);
+    }
+
+    /// Partidas que passam pela posição, com o primeiro lance em que ela aparece.
+    pub fn ocorrencias(&self, partida: &Partida) -> Vec<Achado> {
+        primeiros(self.posicoes.get(&partida.chave()))
+    }
+
+    /// Partidas que passam por todos os filtros, na ordem do acervo. O lance de
+    /// cada achado é a primeira vez que a posição ou o material aparecem, ou o
+    /// fim do prefixo.
+    pub fn buscar(&self, consulta: &Consulta) -> Vec<Achado> {
+        let mut achados = match (consulta.posicao, consulta.material) {
+            (Some(chave), _) => primeiros(self.posicoes.get(&chave)),
+            (None, Some(material)) => primeiros(self.materiais.get(&material)),
+            (None, None) => (0..self.jogos.len()).map(|jogo| Achado { jogo, lance: 0 }).collect(),
+        };
+        if let (Some(_), Some(material)) = (consulta.posicao, consulta.material) {
+            achados.retain(|achado| self.jogos[achado.jogo].partida_ate(achado.lance).material() == material);
+        }
+
+        let prefixo = if consulta.prefixo.is_empty() {
+            None
+        } else {
+            let mut partida = Partida::default();
+            let mut indices = vec![];
+            for lance in &consulta.prefixo {
+                let Ok(indice) = partida.encontrar_jogada(lance) else { return vec![] };
+                indices.push(indice as u8);
+                partida.jogar(indice);
+            }
+            Some(indices)
+        };
+
+        let contem = |valor: Option<&str>, procurado: &Option<String>| match procurado {
+            Some(procurado) => valor.is_some_and(|valor| valor.to_lowercase().contains(&procurado.to_lowercase())),
+            None => true,
+        };
+        achados.retain_mut(|achado| {
+            let jogo = &self.jogos[achado.jogo];
+            let (branco, preto, data) = (jogo.tag("White"), jogo.tag("Black"), jogo.tag("Date"));
+            if !contem(branco, &consulta.branco) || !contem(preto, &consulta.preto) {
+                return false;
+            }
+            if consulta.jogador.is_some() && !contem(branco, &consulta.jogador) && !contem(preto, &consulta.jogador) {
+                return false;
+            }
+            if consulta.desde.as_ref().is_some_and(|desde| data.is_none_or(|data| data < desde.as_str()))
+                || consulta.ate.as_ref().is_some_and(|ate| data.is_none_or(|data| data > ate.as_str()))
+            {
+                return false;
+            }
+            if consulta
+                .resultado
+                .as_ref()
+                .is_some_and(|resultado| resultado_normalizado(resultado) != resultado_normalizado(&jogo.resultado))
+            {
+                return false;
+            }
+            if let Some(prefixo) = &prefixo {
+                if jogo.fen.is_some() || !jogo.lances.starts_with(prefixo) {
+                    return false;
+                }
+                if consulta.posicao.is_none() && consulta.material.is_none() {
+                    achado.lance = prefixo.len();
+                }
+            }
+            true
+        });
+        achados
+    }
+
+    fn bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGICO.to_vec();
+        bytes.push(VERSAO);
+        // Os tamanhos foram conferidos na importação
+        let tamanho = |n: usize| u16::try_from(n).expect("validado na importação").to_le_bytes();
+        let texto = |bytes: &mut Vec<u8>, texto: &str| {
+            bytes.extend_from_slice(&tamanho(texto.len()));
+            bytes.extend_from_slice(texto.as_bytes());
+        };
+        bytes.extend_from_slice(&(self.jogos.len() as u32).to_le_bytes());
+        for jogo in &self.jogos {
+            bytes.extend_from_slice(&tamanho(jogo.tags.len()));
+            for (chave, valor) in &jogo.tags {
+                texto(&mut bytes, chave);
+                texto(&mut bytes, valor);
+            }
+            texto(&mut bytes, &jogo.resultado);
+            texto(&mut bytes, jogo.fen.as_deref().unwrap_or(""));
+            bytes.extend_from_slice(&tamanho(jogo.lances.len()));
+            bytes.extend_from_slice(&jogo.lances);
+        }
+        bytes
+    }
+
+    fn de_bytes(bytes: &[u8]) -> Result<Acervo, ErroAcervo> {
+        if bytes.len() < 5 || &bytes[..4] != MAGICO {
+            return Err(ErroAcervo::Formato("cabeçalho desconhecido".to_string()));
+        }
+        if bytes[4] != VERSAO {
+            return Err(ErroAcervo::Formato(format!("versão {} não suportada", bytes[4])));
+        }
+        let mut leitor = Leitor { bytes, posicao: 5 };
+        let total = u32::from_le_bytes(leitor.ler(4)?.try_into().unwrap());
+        let mut acervo = Acervo::default();
+        for _ in 0..total {
+            let tags = (0..leitor.u16()?).map(|_| Ok((leitor.texto()?, leitor.texto()?))).collect::<Result<_, ErroAcervo>>()?;
+            let resultado = leitor.texto()?;
+            let fen = Some(leitor.texto()?).filter(|fen| !fen.is_empty());
+            if let Some(fen) = &fen {
+                Partida::from_fen(fen).map_err(|erro| ErroAcervo::Formato(erro.to_string()))?;
+            }
+            let n = leitor.u16()? as usize;
+            if n >= LIMITE {
+                return Err(ErroAcervo::Formato(format!("lances demais no jogo {}", acervo.len() + 1)));
+            }
+            let lances = leitor.ler(n)?.to_vec();
+            let jogo = Jogo { tags, resultado, fen, lances };
+            // Confere os lances antes de indexar
+            let mut partida = jogo.posicao_inicial();
+            for &indice in &jogo.lances {
+                if indice as usize >= partida.todas_jogadas_possiveis().len() {
+                    return Err(ErroAcervo::Formato(format!("lance inválido no jogo {}", acervo.len() + 1)));
+                }
+                partida.jogar(indice as usize);
+            }
+            acervo.adicionar(jogo);
+        }
+        Ok(acervo)
+    }
+}
+
+struct Leitor<'a> {
+    bytes: &'a [u8],
+    posicao: usize,
+}
+
+impl<'a> Leitor<'a> {
+    fn ler(&mut self, n: usize) -> Result<&'a [u8], ErroAcervo> {
+        let fim = self.posicao + n;
+        let pedaco = self.bytes.get(self.posicao..fim).ok_or(ErroAcervo::Formato("arquivo truncado".to_string()))?;
+        self.posicao = fim;
+        Ok(pedaco)
+    }
+
+    fn u16(&mut self) -> Result<u16, ErroAcervo> {
+        Ok(u16::from_le_bytes(self.ler(2)?.try_into().unwrap()))
+    }
+
+    fn texto(&mut self) -> Result<String, ErroAcervo> {
+        let n = self.u16()? as usize;
+        String::from_utf8(self.ler(n)?.to_vec()).map_err(|_| ErroAcervo::Formato("texto inválido".to_string()))
+    }
+}
+
+#[test]
+fn testar_acervo() {
+    let pdn = r#"
+[White "Ana"] [Black "Bia"] [Date "2024.03.10"]
+1. 21-17 12-16 2. 22-18 16-20 2-0
+[White "Bia"] [Black "Caio"] [Date "2024.05.02"]
+1. 22-18 12-16 2. 21-17 9-13 1-1
+[White "Caio"] [Black "Ana"] [FEN "W:WK1,K2,K3:BK32"]
+1. 1-5 0-2
+[White "Davi"] [Black "Eva"]
+1. 22-19 1-0
+"#;
+    let mut acervo = Acervo::default();
+    let importacao = acervo.importar(pdn).unwrap();
+    assert_eq!(importacao.importados, 3);
+    assert_eq!(importacao.erros.len(), 1);
+    assert_eq!(importacao.erros[0].0, 4);
+
+    // Transposição: os dois primeiros jogos passam pela mesma posição
+    let mut posicao = Partida::default();
+    for lance in ["21-17", "12-16", "22-18"] {
+        posicao.jogar(posicao.encontrar_jogada(lance).unwrap());
+    }
+    let achados = acervo.buscar(&Consulta::default().posicao(&posicao));
+    assert_eq!(achados, [Achado { jogo: 0, lance: 3 }, Achado { jogo: 1, lance: 3 }]);
+    assert_eq!(acervo.jogo(1).unwrap().partida_ate(3).fen(), posicao.fen());
+
+    let material = "3D x 1D".parse().unwrap();
+    assert_eq!(acervo.buscar(&Consulta::default().material(material)), [Achado { jogo: 2, lance: 0 }]);
+    assert_eq!(acervo.buscar(&Consulta::default().prefixo(&["22-18"])), [Achado { jogo: 1, lance: 1 }]);
+    assert_eq!(acervo.buscar(&Consulta::default().jogador("ana")).len(), 2);
+    assert_eq!(acervo.buscar(&Consulta::default().jogador("Ana").resultado("1-0")).len(), 1);
+    assert_eq!(acervo.buscar(&Consulta::default().periodo(Some("2024.04.01"), None)).len(), 1);
+    assert_eq!(acervo.buscar(&Consulta::default().branco("Bia").posicao(&posicao)).len(), 1);
+
+    let relido = Acervo::de_bytes(&acervo.bytes()).unwrap();
+    assert_eq!(relido.jogos(), acervo.jogos());
+    assert_eq!(relido.buscar(&Consulta::default().posicao(&posicao)).len(), 2);
+    assert_eq!(relido.jogo(2).unwrap().pdn().tag("FEN"), Some("W:WK1,K2,K3:BK32"));
+    assert!(Acervo::de_bytes(b"DACV\x01\x05").is_err());
+
+    // Um texto que não cabe nos 2 bytes de tamanho é recusado na importação
+    let longo = format!("[Event \"{}\"]\n1. 22-18 *\n", "ã".repeat(40_000));
+    let importacao = acervo.importar(&longo).unwrap();
+    assert_eq!((importacao.importados, importacao.erros.len()), (0, 1));
+    assert_eq!(Acervo::de_bytes(&acervo.bytes()).unwrap().len(), 3);
+}
@@ -0,0 +1,255 @@
+use std::fmt::Write;
+
+use crate::diagrama::Diagrama;
+use crate::notacao::notacao;
+use crate::{Casa, Coord, Cor, Jogada, Partida};
+
+// Replay animado de uma partida a partir do seu histórico: SVG com animações
+// SMIL ou, com a feature `gif`, um GIF quadro a quadro. Cada salto de uma
+// captura em cadeia é animado separadamente.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Animacao {
+    pub diagrama: Diagrama,
+    /// Duração de cada salto, em milissegundos.
+    pub salto: u32,
+    /// Pausa depois de cada lance completo, em milissegundos.
+    pub pausa: u32,
+    /// Mostra o número do lance e a notação abaixo do tabuleiro.
+    pub legenda: bool,
+}
+
+impl Default for Animacao {
+    fn default() -> Self {
+        Animacao {
+            diagrama: Diagrama { setas: vec![], ..Diagrama::default() },
+            salto: 400,
+            pausa: 800,
+            legenda: true,
+        }
+    }
+}
+
+/// Um lance do histórico com as posições antes e depois dele.
+struct Lance {
+    legenda: String,
+    sequencia: Vec<Jogada>,
+    antes: [[Casa; 8]; 8],
+    depois: [[Casa; 8]; 8],
+}
+
+fn lances(partida: &Partida) -> Vec<Lance> {
+    let mut atual = partida.posicao_inicial();
+    let deslocamento = if *atual.get_vez() == Cor::Preto { 1 } else { 0 };
+    let mut lances = vec![];
+    for (i, sequencia) in partida.historico().iter().enumerate() {
+        let numero = (i + deslocamento) / 2 + 1;
+        let pontos = if *atual.get_vez() == Cor::Branco { "." } else { "..." };
+        let antes = *atual.get_tabuleiro();
+        let indice = atual
+            .todas_jogadas_possiveis()
+            .iter()
+            .position(|possivel| possivel == sequencia)
+            .expect("o histórico só contém jogadas possíveis");
+        atual.jogar(indice);
+        lances.push(Lance {
+            legenda: format!("{numero}{pontos} {}", notacao(sequencia)),
+            sequencia: sequencia.clone(),
+            antes,
+            depois: *atual.get_tabuleiro(),
+        });
+    }
+    lances
+}
+
+impl Animacao {
+    pub fn new() -> Self {
+        Animacao::default()
+    }
+
+    fn altura_legenda(&self) -> f32 {
+        if self.legenda { self.diagrama.tamanho as f32 / 12.0 } else { 0.0 }
+    }
+
+    fn dimensoes(&self) -> (f32, f32) {
+        let (largura, altura) = self.diagrama.dimensoes();
+        (largura, altura + self.altura_legenda())
+    }
+
+    fn cabecalho(&self) -> String {
+        let (largura, altura) = self.dimensoes();
+        let mut svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{largura}" height="{altura}" viewBox="0 0 {largura} {altura}">
+"#
+        );
+        svg.push_str(&self.diagrama.definicoes());
+        self.diagrama.escrever_tabuleiro(&mut svg);
+        self.diagrama.escrever_coordenadas(&mut svg);
+        svg
+    }
+
+    fn legenda(&self, texto: &str, atributos: &str, filhos: &str) -> String {
+        let (largura, altura) = self.dimensoes();
+        let fonte = self.altura_legenda() * 0.6;
+        format!(
+            r#"<text x="{}" y="{}" font-size="{fonte}" font-family="sans-serif" text-anchor="middle" fill="{}" {atributos}>{texto}{filhos}</text>
+"#,
+            largura / 2.0,
+            altura - self.altura_legenda() * 0.3,
+            self.diagrama.cores.borda
+        )
+    }
+
+    /// SVG animado com SMIL: cada pedra desliza salto por salto e as capturadas
+    /// somem no fim do lance.
+    pub fn svg(&self, partida: &Partida) -> String {
+        let lances = lances(partida);
+        let segundos = |ms: u32| ms as f32 / 1000.0;
+        let mut svg = self.cabecalho();
+
+        // Cada pedra do tabuleiro inicial ganha um índice; `ocupante` diz qual pedra está em cada casa
+        let inicial = partida.posicao_inicial();
+        let mut pedras = vec![];
+        let mut animacoes: Vec<String> = vec![];
+        let mut ocupante = [[None::<usize>; 8]; 8];
+        for y in 0..8 {
+            for x in 0..8 {
+                if let Some(pedra) = inicial.pedra(Coord { x, y }) {
+                    ocupante[y as usize][x as usize] = Some(pedras.len());
+                    pedras.push((pedra, Coord { x, y }));
+                    animacoes.push(String::new());
+                }
+            }
+        }
+
+        let mut legendas = String::new();
+        let mut t = 0.0;
+        for (i, lance) in lances.iter().enumerate() {
+            let inicio = t;
+            let origem = lance.sequencia[0].origem();
+            let pedra = ocupante[origem.y as usize][origem.x as usize].unwrap();
+            let mut capturadas = vec![];
+            for jogada in &lance.sequencia {
+                let (x1, y1) = self.diagrama.centro(jogada.origem());
+                let (x2, y2) = self.diagrama.centro(jogada.destino());
+                write!(
+                    animacoes[pedra],
+                    r#"<animateTransform attributeName="transform" type="translate" from="{x1} {y1}" to="{x2} {y2}" begin="{t}s" dur="{}s" fill="freeze"/>"#,
+                    segundos(self.salto)
+                )
+                .unwrap();
+                t += segundos(self.salto);
+                if let Jogada::Capturar(_, capturada, _) = jogada {
+                    let alvo = ocupante[capturada.y as usize][capturada.x as usize].unwrap();
+                    write!(animacoes[alvo], r#"<set attributeName="opacity" to="0.4" begin="{t}s"/>"#).unwrap();
+                    capturadas.push(alvo);
+                }
+            }
+            for alvo in capturadas {
+                write!(animacoes[alvo], r#"<set attributeName="opacity" to="0" begin="{t}s"/>"#).unwrap();
+            }
+            let destino = lance.sequencia.last().unwrap().destino();
+            let virou_dama = match (lance.antes[origem.y as usize][origem.x as usize], lance.depois[destino.y as usize][destino.x as usize]) {
+                (Casa::Ocupada(antes), Casa::Ocupada(depois)) => !antes.é_dama() && depois.é_dama(),
+                _ => false,
+            };
+            if virou_dama {
+                let set = format!(r#"<set attributeName="opacity" to="1" begin="{t}s"/>"#);
+                animacoes[pedra].push_str(&self.diagrama.coroa(r#"opacity="0""#, &set));
+            }
+            ocupante[origem.y as usize][origem.x as usize] = None;
+            for jogada in &lance.sequencia {
+                if let Jogada::Capturar(_, capturada, _) = jogada {
+                    ocupante[capturada.y as usize][capturada.x as usize] = None;
+                }
+            }
+            ocupante[destino.y as usize][destino.x as usize] = Some(pedra);
+            t += segundos(self.pausa);
+
+            if self.legenda {
+                let fim = if i + 1 < lances.len() { format!(r#" end="{t}s""#) } else { String::new() };
+                let set = format!(r#"<set attributeName="visibility" to="visible" begin="{inicio}s"{fim}/>"#);
+                legendas.push_str(&self.legenda(&lance.legenda, r#"visibility="hidden""#, &set));
+            }
+        }
+
+        for ((pedra, casa), animacao) in pedras.into_iter().zip(animacoes) {
+            svg.push_str(&self.diagrama.pedra(pedra, casa, &animacao));
+        }
+        svg.push_str(&legendas);
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Quadros estáticos da animação, em SVG, com a duração de cada um em milissegundos.
+    pub fn quadros(&self, partida: &Partida) -> Vec<(String, u32)> {
+        let lances = lances(partida);
+        let quadro = |tabuleiro: &[[Casa; 8]; 8], marcas: &[Coord], legenda: &str| {
+            let mut svg = self.cabecalho();
+            self.diagrama.escrever_pedras(&mut svg, tabuleiro);
+            for casa in marcas {
+                svg.push_str(&self.diagrama.marca_de_captura(*casa));
+            }
+            if self.legenda {
+                svg.push_str(&self.legenda(legenda, "", ""));
+            }
+            svg.push_str("</svg>\n");
+            svg
+        };
+
+        let mut quadros = vec![(quadro(partida.posicao_inicial().get_tabuleiro(), &[], ""), self.pausa)];
+        for lance in &lances {
+            // Saltos intermediários: a pedra anda e as capturadas ficam marcadas até o fim do lance
+            let mut tabuleiro = lance.antes;
+            let mut marcas = vec![];
+            for jogada in &lance.sequencia[..lance.sequencia.len() - 1] {
+                let (o, d) = (jogada.origem(), jogada.destino());
+                tabuleiro[d.y as usize][d.x as usize] = tabuleiro[o.y as usize][o.x as usize];
+                tabuleiro[o.y as usize][o.x as usize] = Casa::Vazia;
+                if let Jogada::Capturar(_, capturada, _) = jogada {
+                    marcas.push(*capturada);
+                }
+                quadros.push((quadro(&tabuleiro, &marcas, &lance.legenda), self.salto));
+            }
+            quadros.push((quadro(&lance.depois, &[], &lance.legenda), self.pausa));
+        }
+        quadros
+    }
+
+    /// GIF animado com um quadro por salto.
+    #[cfg(feature = "gif")]
+    pub fn gif(&self, partida: &Partida) -> Result<Vec<u8>, String> {
+        use crate::diagrama::rasterizar_rgba;
+
+        let mut bytes = vec![];
+        {
+            let (largura, altura) = self.dimensoes();
+            let mut encoder = gif::Encoder::new(&mut bytes, largura.ceil() as u16, altura.ceil() as u16, &[])
+                .map_err(|erro| erro.to_string())?;
+            encoder.set_repeat(gif::Repeat::Infinite).map_err(|erro| erro.to_string())?;
+            for (svg, duracao) in self.quadros(partida) {
+                let (largura, altura, mut pixels) = rasterizar_rgba(&svg)?;
+                let mut quadro = gif::Frame::from_rgba_speed(largura as u16, altura as u16, &mut pixels, 10);
+                quadro.delay = (duracao / 10) as u16;
+                encoder.write_frame(&quadro).map_err(|erro| erro.to_string())?;
+            }
+        }
+        Ok(bytes)
+    }
+}
+
+#[test]
+fn testar_animacao() {
+    let mut partida = Partida::from_fen("W:W22:B18,11").unwrap();
+    partida.jogar(0);
+    let animacao = Animacao::new();
+
+    let svg = animacao.svg(&partida);
+    assert_eq!(svg.matches("<animateTransform").count(), 2);
+    assert!(svg.contains("1. 22x15x8"));
+
+    // Quadro inicial, um salto intermediário e a posição final
+    let quadros = animacao.quadros(&partida);
+    assert_eq!(quadros.len(), 3);
+    assert_eq!(quadros[1].1, animacao.salto);
+}
@@ -0,0 +1,157 @@
+use crate::bot::{Bot, VITORIA};
+use crate::golpes::BuscaDeGolpes;
+use crate::notacao::notacao;
+use crate::pdn::{ErroPdn, JogoPdn};
+use crate::{Cor, Partida, Resultado};
+
+// Anotação automática de partidas: cada posição é analisada pelo bot e a
+// diferença entre o melhor lance e o jogado decide os sinais. As avaliações
+// estão na escala do bot, em que uma pedra vale 100.
+
+// Perdas a partir das quais o lance leva ? e ??
+const ERRO: i32 = 100;
+const ERRO_GRAVE: i32 = 300;
+// Vantagem do melhor lance sobre o segundo para ele levar !
+const LANCE_UNICO: i32 = 100;
+// Avaliações acima disto são vitórias encontradas pela busca
+const VITORIA_VISTA: i32 = VITORIA - 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Anotador {
+    pub bot: Bot,
+    /// Máximo de lances da variante sugerida depois de um erro.
+    pub lances_da_variante: usize,
+}
+
+impl Default for Anotador {
+    fn default() -> Self {
+        Anotador { bot: Bot::new(6), lances_da_variante: 8 }
+    }
+}
+
+/// Avaliação do ponto de vista das brancas, em pedras, como vai no comentário.
+fn placar(avaliacao: i32, vez: Cor) -> String {
+    let avaliacao = if vez == Cor::Branco { avaliacao } else { -avaliacao };
+    if avaliacao >= VITORIA_VISTA {
+        "brancas ganham".to_string()
+    } else if avaliacao <= -VITORIA_VISTA {
+        "pretas ganham".to_string()
+    } else {
+        format!("{:+.2}", avaliacao as f64 / 100.0)
+    }
+}
+
+impl Anotador {
+    pub fn com_bot(mut self, bot: Bot) -> Self {
+        self.bot = bot;
+        self
+    }
+
+    /// Anota os lances da partida, a partir da posição inicial dela.
+    pub fn anotar(&self, partida: &Partida) -> JogoPdn {
+        let mut jogo = JogoPdn::de_partida(partida);
+        let mut posicao = partida.posicao_inicial();
+        let mut analise = self.bot.analisar(&posicao);
+        for (i, sequencia) in partida.historico().iter().enumerate() {
+            let Some(indice) = posicao.todas_jogadas_possiveis().iter().position(|possivel| possivel == sequencia) else {
+                break;
+            };
+            let vez = *posicao.get_vez();
+            let mut depois = posicao.clone();
+            let resultado = depois.jogar(indice);
+            let seguinte = match resultado {
+                Resultado::FimDoJogo(_) => None,
+                _ => self.bot.analisar(&depois),
+            };
+            // Avaliação do lance jogado para quem o jogou
+            let jogado = match resultado {
+                Resultado::FimDoJogo(Some(vencedor)) if vencedor == vez => VITORIA,
+                Resultado::FimDoJogo(Some(_)) => -VITORIA,
+                Resultado::FimDoJogo(None) => 0,
+                _ => seguinte.as_ref().map_or(VITORIA, |seguinte| -seguinte.avaliacao),
+            };
+            let melhor = analise.as_ref().map_or(jogado, |analise| analise.avaliacao.max(jogado));
+            let perda = melhor - jogado;
+
+            let anotacao = jogo.anotacao_mut(i);
+            anotacao.comentario = Some(placar(jogado, vez));
+            if perda >= ERRO {
+                anotacao.nag = Some(if perda >= ERRO_GRAVE { "??" } else { "?" }.to_string());
+                if let Some(analise) = &analise {
+                    let variante = analise.pv.iter().take(self.lances_da_variante).map(|sequencia| notacao(sequencia));
+                    anotacao.variantes.push(variante.collect());
+                }
+            } else if analise.as_ref().is_some_and(|analise| analise.indice == indice) {
+                anotacao.nag = self.elogio(&posicao, indice, melhor);
+            }
+            posicao = depois;
+            analise = seguinte;
+        }
+        jogo
+    }
+
+    // ! para o único lance bom, !! quando ele ainda entrega pedras num golpe
+    fn elogio(&self, partida: &Partida, indice: usize, melhor: i32) -> Option<String> {
+        let total = partida.todas_jogadas_possiveis().len();
+        if total < 2 {
+            return None;
+        }
+        let rapido = Bot { profundidade: self.bot.profundidade.saturating_sub(1).max(1), ..self.bot };
+        let segundo = (0..total)
+            .filter(|&outro| outro != indice)
+            .map(|outro| {
+                let mut filho = partida.clone();
+                match filho.jogar(outro) {
+                    Resultado::FimDoJogo(Some(vencedor)) if vencedor == *partida.get_vez() => VITORIA,
+                    Resultado::FimDoJogo(_) => 0,
+                    _ => rapido.analisar(&filho).map_or(VITORIA, |analise| -analise.avaliacao),
+                }
+            })
+            .max()?;
+        if melhor - segundo < LANCE_UNICO {
+            return None;
+        }
+        let golpe = BuscaDeGolpes::default().procurar(partida).into_iter().find(|golpe| golpe.indice == indice);
+        Some(if golpe.is_some_and(|golpe| golpe.sacrificios > 0) { "!!" } else { "!" }.to_string())
+    }
+
+    /// Anota um jogo em PDN, mantendo as tags e o resultado.
+    pub fn anotar_pdn(&self, jogo: &JogoPdn) -> Result<JogoPdn, ErroPdn> {
+        let partida = jogo.partida()?;
+        let mut anotado = self.anotar(&partida);
+        for (chave, valor) in &jogo.tags {
+            anotado.set_tag(chave, valor);
+        }
+        // Desistências e acordos não aparecem nos lances
+        if !partida.encerrada() {
+            anotado.resultado = jogo.resultado.clone();
+        }
+        Ok(anotado)
+    }
+}
+
+#[test]
+fn testar_anotador() {
+    let anotador = Anotador::default().com_bot(Bot::new(4));
+    let jogar = |lances: &[&str]| {
+        let mut partida = Partida::from_fen("W:W14,23,25,27,28,29,30,31,32:B3,4,5,6,8,11,12,13,16").unwrap();
+        for lance in lances {
+            partida.jogar(partida.encontrar_jogada(lance).unwrap());
+        }
+        partida
+    };
+
+    // O golpe de duas entregas leva !!
+    let golpe = anotador.anotar(&jogar(&["14-9", "5x14", "23-19", "16x23", "27x18x9x2"]));
+    assert_eq!(golpe.anotacao(0).unwrap().nag.as_deref(), Some("!!"));
+    assert!(golpe.anotacao(0).unwrap().comentario.as_ref().unwrap().starts_with("+2."));
+    assert_eq!(golpe.anotacao(1).unwrap().nag, None);
+
+    // Deixar o golpe passar é um erro, e a variante mostra o que havia
+    let perdido = anotador.anotar(&jogar(&["30-26"]));
+    let anotacao = perdido.anotacao(0).unwrap();
+    assert_eq!(anotacao.nag.as_deref(), Some("??"));
+    assert_eq!(anotacao.variantes[0][..2], ["14-9", "5x14"]);
+    let relido = &crate::pdn::ler(&perdido.to_string()).unwrap()[0];
+    assert_eq!(relido.anotacoes, perdido.anotacoes);
+}
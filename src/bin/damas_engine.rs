@@ -0,0 +1,14 @@
+use damas::hub::Motor;
+
+use std::io;
+
+// Motor para interfaces gráficas e para o `damas_match`: fala o protocolo Hub
+// na entrada e na saída padrão. Veja `damas::hub` para os comandos.
+
+fn main() {
+    if std::env::args().skip(1).any(|arg| arg == "-h" || arg == "--ajuda") {
+        println!("Uso: damas_engine   (protocolo Hub na entrada e na saída padrão)");
+        return;
+    }
+    Motor::executar(io::BufReader::new(io::stdin()), io::stdout());
+}
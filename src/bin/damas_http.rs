@@ -0,0 +1,182 @@
+use damas::bot::Bot;
+use damas::notacao::notacao;
+use damas::{Cor, Partida, Resultado};
+
+use serde_json::{json, Value};
+use std::process;
+use std::thread;
+use std::time::Duration;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+// API HTTP sem estado para outros serviços usarem as regras. Todo pedido é um
+// POST com um objeto JSON; a posição vem sempre em FEN e, se faltar, é a inicial.
+//
+//   POST /jogadas   {fen}                             -> {fen, vez, jogadas}
+//   POST /jogar     {fen, lance}                      -> {lance, resultado, vencedor, fen, vez, jogadas}
+//   POST /analisar  {fen, profundidade?, tempo_ms?}   -> {lance, avaliacao, profundidade, pv, nos}
+//
+// Erros voltam como {erro} com status 400, 404 ou 405.
+
+const USO: &str = "Uso: damas_http [endereço]   (padrão: 127.0.0.1:8080)";
+
+// Limites para um pedido não prender o servidor numa busca sem fim
+const PROFUNDIDADE_MAXIMA: u32 = 12;
+const TEMPO_MAXIMO: Duration = Duration::from_secs(10);
+
+fn cor(jogador: Cor) -> &'static str {
+    match jogador {
+        Cor::Branco => "branco",
+        Cor::Preto => "preto",
+    }
+}
+
+fn jogadas(partida: &Partida) -> Vec<String> {
+    partida.todas_jogadas_possiveis().iter().map(|sequencia| notacao(sequencia)).collect()
+}
+
+fn posicao(pedido: &Value) -> Result<Partida, String> {
+    match pedido.get("fen") {
+        None | Some(Value::Null) => Ok(Partida::default()),
+        Some(Value::String(fen)) => Partida::from_fen(fen).map_err(|erro| format!("FEN inválida: {erro}")),
+        Some(_) => Err("fen deve ser um texto".to_string()),
+    }
+}
+
+fn numero(pedido: &Value, campo: &str) -> Result<Option<u64>, String> {
+    match pedido.get(campo) {
+        None | Some(Value::Null) => Ok(None),
+        Some(valor) => valor.as_u64().map(Some).ok_or_else(|| format!("{campo} deve ser um inteiro positivo")),
+    }
+}
+
+fn listar(pedido: &Value) -> Result<Value, String> {
+    let partida = posicao(pedido)?;
+    Ok(json!({
+        "fen": partida.fen(),
+        "vez": cor(*partida.get_vez()),
+        "jogadas": jogadas(&partida),
+    }))
+}
+
+fn jogar(pedido: &Value) -> Result<Value, String> {
+    let mut partida = posicao(pedido)?;
+    let lance = pedido.get("lance").and_then(Value::as_str).ok_or("falta o lance")?;
+    let indice = partida.encontrar_jogada(lance).map_err(|erro| erro.to_string())?;
+    let lance = notacao(&partida.todas_jogadas_possiveis()[indice]);
+    let (resultado, vencedor) = match partida.jogar(indice) {
+        Resultado::Falha => ("falha", None),
+        Resultado::Sucesso => ("sucesso", None),
+        Resultado::FimDoJogo(vencedor) => ("fim_do_jogo", vencedor.map(cor)),
+    };
+    Ok(json!({
+        "lance": lance,
+        "resultado": resultado,
+        "vencedor": vencedor,
+        "fen": partida.fen(),
+        "vez": cor(*partida.get_vez()),
+        "jogadas": jogadas(&partida),
+    }))
+}
+
+fn analisar(pedido: &Value) -> Result<Value, String> {
+    let partida = posicao(pedido)?;
+    let profundidade = numero(pedido, "profundidade")?.map_or(Bot::default().profundidade, |p| p as u32);
+    let mut bot = Bot::new(profundidade.clamp(1, PROFUNDIDADE_MAXIMA));
+    let tempo = numero(pedido, "tempo_ms")?.map_or(TEMPO_MAXIMO, Duration::from_millis);
+    bot = bot.com_tempo(tempo.min(TEMPO_MAXIMO));
+    // Sem jogadas, quem tem a vez perdeu e não há o que analisar
+    let Some(analise) = bot.analisar(&partida) else {
+        return Ok(json!({ "lance": null, "avaliacao": null, "profundidade": 0, "pv": [], "nos": 0 }));
+    };
+    Ok(json!({
+        "lance": notacao(&partida.todas_jogadas_possiveis()[analise.indice]),
+        "avaliacao": analise.avaliacao,
+        "profundidade": analise.profundidade,
+        "pv": analise.pv.iter().map(|sequencia| notacao(sequencia)).collect::<Vec<_>>(),
+        "nos": analise.nos,
+    }))
+}
+
+/// Status e corpo da resposta para um pedido.
+fn responder(metodo: &Method, caminho: &str, corpo: &str) -> (u16, Value) {
+    let rota: fn(&Value) -> Result<Value, String> = match caminho {
+        "/jogadas" => listar,
+        "/jogar" => jogar,
+        "/analisar" => analisar,
+        _ => return (404, json!({ "erro": format!("rota desconhecida: {caminho}") })),
+    };
+    if *metodo != Method::Post {
+        return (405, json!({ "erro": "use POST" }));
+    }
+    let pedido = if corpo.trim().is_empty() {
+        json!({})
+    } else {
+        match serde_json::from_str::<Value>(corpo) {
+            Ok(pedido) if pedido.is_object() => pedido,
+            Ok(_) => return (400, json!({ "erro": "o corpo deve ser um objeto JSON" })),
+            Err(erro) => return (400, json!({ "erro": format!("JSON inválido: {erro}") })),
+        }
+    };
+    match rota(&pedido) {
+        Ok(resposta) => (200, resposta),
+        Err(erro) => (400, json!({ "erro": erro })),
+    }
+}
+
+fn atender(mut pedido: Request) {
+    let mut corpo = String::new();
+    let (status, resposta) = match pedido.as_reader().read_to_string(&mut corpo) {
+        Ok(_) => {
+            let caminho = pedido.url().split('?').next().unwrap_or("").to_string();
+            responder(pedido.method(), &caminho, &corpo)
+        }
+        Err(_) => (400, json!({ "erro": "o corpo deve ser UTF-8" })),
+    };
+    let tipo = Header::from_bytes("Content-Type", "application/json").unwrap();
+    let resposta = Response::from_string(resposta.to_string()).with_status_code(status).with_header(tipo);
+    // O cliente pode ter desistido de esperar; não há o que fazer
+    pedido.respond(resposta).ok();
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let endereco = args.next().unwrap_or_else(|| "127.0.0.1:8080".to_string());
+    if endereco == "-h" || endereco == "--ajuda" || args.next().is_some() {
+        println!("{USO}");
+        return;
+    }
+    let servidor = match Server::http(&endereco) {
+        Ok(servidor) => servidor,
+        Err(erro) => {
+            eprintln!("Erro: não foi possível escutar em {endereco}: {erro}");
+            process::exit(1);
+        }
+    };
+    println!("API de damas escutando em http://{endereco}");
+    for pedido in servidor.incoming_requests() {
+        // Análises podem demorar, então cada pedido tem a sua thread
+        thread::spawn(move || atender(pedido));
+    }
+}
+
+#[test]
+fn testar_rotas() {
+    let (status, resposta) = responder(&Method::Post, "/jogadas", "");
+    assert_eq!(status, 200);
+    assert_eq!(resposta["vez"], "branco");
+    assert_eq!(resposta["jogadas"].as_array().unwrap().len(), 7);
+
+    let (status, resposta) = responder(&Method::Post, "/jogar", r#"{"fen": "W:W22:B18,11", "lance": "22x8"}"#);
+    assert_eq!(status, 200);
+    assert_eq!(resposta["lance"], "22x15x8");
+    assert_eq!(resposta["resultado"], "fim_do_jogo");
+    assert_eq!(resposta["vencedor"], "branco");
+
+    let (status, resposta) = responder(&Method::Post, "/analisar", r#"{"fen": "W:W22:B18,11", "profundidade": 2}"#);
+    assert_eq!(status, 200);
+    assert_eq!(resposta["lance"], "22x15x8");
+
+    assert_eq!(responder(&Method::Post, "/jogar", r#"{"lance": "22-15"}"#).0, 400);
+    assert_eq!(responder(&Method::Get, "/jogadas", "").0, 405);
+    assert_eq!(responder(&Method::Post, "/", "").0, 404);
+}
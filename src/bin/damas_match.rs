@@ -0,0 +1,131 @@
+use damas::bot::Bot;
+use damas::confronto::{Confronto, DecisaoSprt, Placar, Sprt};
+use damas::hub::{LinhaHub, MotorExterno};
+use damas::pdn;
+use damas::Jogador;
+
+use std::fs;
+use std::process;
+use std::time::Duration;
+
+const USO: &str = "\
+Uso: damas_match --primeiro <motor> --segundo <motor> [opções]
+  <motor> é bot[:profundidade[:ms por lance]] para o bot do crate,
+  ou o comando de um motor externo que fale Hub (por exemplo \"damas_engine\")
+  --partidas <n>          número máximo de partidas (padrão: 100)
+  --aberturas <arquivo>   PDN com as aberturas, usando a posição final de cada jogo
+                          (padrão: todas as posições depois de dois lances)
+  --sprt <elo0>,<elo1>    para quando o SPRT decidir, com alfa = beta = 0,05
+  --tempo <ms>            tempo por lance dos motores externos
+  --pdn <arquivo>         grava as partidas em PDN";
+
+struct Motor {
+    nome: String,
+    jogador: Box<dyn Jogador>,
+}
+
+fn motor(descricao: &str, tempo: Option<Duration>) -> Result<Motor, String> {
+    let mut partes = descricao.split(':');
+    if partes.next() == Some("bot") {
+        let mut bot = Bot::default();
+        if let Some(profundidade) = partes.next() {
+            bot.profundidade = profundidade.parse().map_err(|_| format!("{descricao}: profundidade inválida"))?;
+        }
+        if let Some(ms) = partes.next() {
+            bot.tempo = Some(Duration::from_millis(ms.parse().map_err(|_| format!("{descricao}: tempo inválido"))?));
+        }
+        return Ok(Motor { nome: descricao.to_string(), jogador: Box::new(bot) });
+    }
+    let mut externo = MotorExterno::iniciar(descricao)?;
+    if let Some(tempo) = tempo {
+        externo.enviar(&LinhaHub::new("level").campo("move-time", tempo.as_secs_f64()))?;
+    }
+    Ok(Motor { nome: externo.nome.clone(), jogador: Box::new(externo) })
+}
+
+fn placar(placar: &Placar, sprt: Option<Sprt>) -> String {
+    let mut texto = format!("+{} ={} -{}", placar.vitorias, placar.empates, placar.derrotas);
+    match placar.elo() {
+        Some((elo, margem)) => texto.push_str(&format!(" | Elo {elo:+.1} ± {margem:.1}")),
+        None => texto.push_str(" | Elo --"),
+    }
+    if let Some(sprt) = sprt {
+        let (inferior, superior) = sprt.limites();
+        texto.push_str(&format!(" | LLR {:.2} ({inferior:.2}, {superior:.2})", sprt.llr(placar)));
+    }
+    texto
+}
+
+fn executar(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let (mut primeiro, mut segundo) = (None, None);
+    let mut confronto = Confronto::default();
+    let mut tempo = None;
+    let mut saida: Option<String> = None;
+    while let Some(arg) = args.next() {
+        if arg == "-h" || arg == "--ajuda" {
+            println!("{USO}");
+            return Ok(());
+        }
+        let valor = args.next().ok_or(format!("{arg} precisa de um valor"))?;
+        let numero = || valor.parse::<u64>().map_err(|_| format!("{arg}: '{valor}' não é um número"));
+        match arg.as_str() {
+            "--primeiro" => primeiro = Some(valor),
+            "--segundo" => segundo = Some(valor),
+            "--partidas" => confronto.partidas = numero()? as usize,
+            "--tempo" => tempo = Some(Duration::from_millis(numero()?)),
+            "--pdn" => saida = Some(valor),
+            "--sprt" => {
+                let (elo0, elo1) = valor.split_once(',').ok_or("--sprt: use <elo0>,<elo1>")?;
+                let elo = |texto: &str| texto.trim().parse::<f64>().map_err(|_| format!("--sprt: '{texto}' não é um número"));
+                confronto.sprt = Some(Sprt::new(elo(elo0)?, elo(elo1)?));
+            }
+            "--aberturas" => {
+                let texto = fs::read_to_string(&valor).map_err(|erro| format!("{valor}: {erro}"))?;
+                let jogos = pdn::ler(&texto).map_err(|erro| format!("{valor}: {erro}"))?;
+                confronto.aberturas =
+                    jogos.iter().map(|jogo| jogo.partida()).collect::<Result<_, _>>().map_err(|erro| format!("{valor}: {erro}"))?;
+            }
+            _ => return Err(format!("opção desconhecida: {arg}")),
+        }
+    }
+    let primeiro = motor(&primeiro.ok_or("informe --primeiro")?, tempo)?;
+    let segundo = motor(&segundo.ok_or("informe --segundo")?, tempo)?;
+    let Motor { nome: nome_primeiro, jogador: mut a } = primeiro;
+    let Motor { nome: nome_segundo, jogador: mut b } = segundo;
+
+    let mut jogos = String::new();
+    let sprt = confronto.sprt;
+    let total = confronto.jogar(a.as_mut(), b.as_mut(), |jogada, parcial| {
+        let jogo = jogada.pdn(&nome_primeiro, &nome_segundo);
+        println!(
+            "Partida {}: {} x {} {} | {}",
+            jogada.rodada,
+            jogo.tag("White").unwrap_or("?"),
+            jogo.tag("Black").unwrap_or("?"),
+            jogo.resultado,
+            placar(parcial, sprt)
+        );
+        jogos.push_str(&jogo.to_string());
+        jogos.push('\n');
+    })?;
+
+    println!("\n{nome_primeiro} x {nome_segundo}: {}", placar(&total, sprt));
+    if let Some(decisao) = sprt.and_then(|sprt| sprt.decidir(&total)) {
+        let decisao = match decisao {
+            DecisaoSprt::H0 => "H0 aceita: a diferença não chega a elo1",
+            DecisaoSprt::H1 => "H1 aceita: a diferença é de pelo menos elo1",
+        };
+        println!("SPRT: {decisao}");
+    }
+    if let Some(arquivo) = saida {
+        fs::write(&arquivo, jogos).map_err(|erro| format!("{arquivo}: {erro}"))?;
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(erro) = executar(std::env::args().skip(1)) {
+        eprintln!("Erro: {erro}\n{USO}");
+        process::exit(2);
+    }
+}
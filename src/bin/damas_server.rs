@@ -0,0 +1,26 @@
+use damas::rede::servidor::Servidor;
+
+use std::process;
+
+const USO: &str = "Uso: damas_server [endereço]   (padrão: 127.0.0.1:7878)";
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let endereco = args.next().unwrap_or_else(|| "127.0.0.1:7878".to_string());
+    if endereco == "-h" || endereco == "--ajuda" || args.next().is_some() {
+        println!("{USO}");
+        return;
+    }
+    let servidor = match Servidor::escutar(&endereco) {
+        Ok(servidor) => servidor,
+        Err(erro) => {
+            eprintln!("Erro: não foi possível escutar em {endereco}: {erro}");
+            process::exit(1);
+        }
+    };
+    println!("Servidor de damas escutando em {endereco}");
+    if let Err(erro) = servidor.servir() {
+        eprintln!("Erro: {erro}");
+        process::exit(1);
+    }
+}
@@ -0,0 +1,361 @@
+use damas::notacao::{corresponde, notacao};
+use damas::{Casa, Coord, Cor, Estado, Jogada, MotivoVitoria, Partida, Pedra, Resultado};
+
+use crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, MouseButton, MouseEventKind,
+};
+use crossterm::style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{cursor, execute, queue};
+
+use std::io::{self, Write};
+
+// Cada casa ocupa LARGURA x ALTURA caracteres na tela
+const LARGURA: u16 = 5;
+const ALTURA: u16 = 2;
+const MARGEM_X: u16 = 3;
+const MARGEM_Y: u16 = 1;
+const PAINEL_X: u16 = MARGEM_X + LARGURA * 8 + 4;
+
+const CASA_CLARA: Color = Color::Rgb { r: 222, g: 200, b: 160 };
+const CASA_ESCURA: Color = Color::Rgb { r: 120, g: 80, b: 50 };
+const COR_CURSOR: Color = Color::Rgb { r: 70, g: 110, b: 200 };
+const COR_SELECAO: Color = Color::Rgb { r: 60, g: 150, b: 200 };
+const COR_DESTINO: Color = Color::Rgb { r: 60, g: 160, b: 70 };
+const COR_POUSO: Color = Color::Rgb { r: 190, g: 170, b: 40 };
+const COR_CAPTURA: Color = Color::Rgb { r: 190, g: 60, b: 50 };
+const COR_MOVEL: Color = Color::Rgb { r: 140, g: 100, b: 60 };
+
+struct Tui {
+    partida: Partida,
+    cursor: Coord,
+    // Origem seguida das casas já escolhidas para a sequência
+    selecao: Vec<Coord>,
+    virado: bool,
+    fim: Option<String>,
+    mensagem: String,
+}
+
+impl Tui {
+    fn new() -> Self {
+        Tui {
+            partida: Partida::default(),
+            cursor: Coord { x: 2, y: 5 },
+            selecao: vec![],
+            virado: false,
+            fim: None,
+            mensagem: String::new(),
+        }
+    }
+
+    // Sequências possíveis compatíveis com as casas escolhidas até agora
+    fn candidatas(&self, selecao: &[Coord]) -> Vec<usize> {
+        self.partida
+            .todas_jogadas_possiveis()
+            .iter()
+            .enumerate()
+            .filter(|(_, sequencia)| corresponde(sequencia, selecao))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    fn selecionar(&mut self, casa: Coord) {
+        self.mensagem.clear();
+        if self.fim.is_some() {
+            self.mensagem = "O jogo acabou: n para nova partida, u para desfazer".to_string();
+            return;
+        }
+        if self.selecao == [casa] {
+            self.selecao.clear();
+            return;
+        }
+        if self.selecao.is_empty() {
+            if !self.candidatas(&[casa]).is_empty() {
+                self.selecao.push(casa);
+            } else if self.partida.pedra(casa).is_some() {
+                self.mensagem = "Essa pedra não tem jogadas".to_string();
+            }
+            return;
+        }
+
+        let mut nova = self.selecao.clone();
+        nova.push(casa);
+        let candidatas = self.candidatas(&nova);
+        if candidatas.is_empty() {
+            // Clicar em outra pedra que pode jogar troca a seleção
+            self.selecao.clear();
+            self.selecionar(casa);
+            return;
+        }
+        let jogadas = self.partida.todas_jogadas_possiveis();
+        let terminam_aqui: Vec<usize> = candidatas
+            .iter()
+            .copied()
+            .filter(|&i| jogadas[i].last().unwrap().destino() == casa)
+            .collect();
+        match terminam_aqui[..] {
+            [indice] => self.jogar(indice),
+            [] => self.selecao = nova,
+            _ => {
+                self.selecao = nova;
+                self.mensagem = "Mais de um caminho: escolha as casas intermediárias".to_string();
+            }
+        }
+    }
+
+    fn jogar(&mut self, indice: usize) {
+        self.selecao.clear();
+        match self.partida.jogar(indice) {
+            Resultado::FimDoJogo(_) => self.checar_fim(),
+            Resultado::Sucesso => (),
+            Resultado::Falha => self.mensagem = "Jogada inválida".to_string(),
+        }
+    }
+
+    fn checar_fim(&mut self) {
+        self.fim = match self.partida.estado() {
+            Estado::EmAndamento => None,
+            Estado::Vitoria(ganhador, MotivoVitoria::SemJogadas) => {
+                Some(format!("{:?} não tem jogadas e perdeu!", ganhador.adversario()))
+            }
+            Estado::Vitoria(ganhador, _) => Some(format!("{:?} ganhou!", ganhador)),
+            Estado::Empate(_) => Some("Empate!".to_string()),
+        };
+    }
+
+    fn desfazer(&mut self) {
+        self.selecao.clear();
+        if self.partida.desfazer() {
+            self.checar_fim();
+        } else {
+            self.mensagem = "Não há jogadas para desfazer".to_string();
+        }
+    }
+
+    fn mover_cursor(&mut self, dx: i32, dy: i32) {
+        let (dx, dy) = if self.virado { (-dx, -dy) } else { (dx, dy) };
+        let nova = Coord { x: self.cursor.x + dx, y: self.cursor.y + dy };
+        if nova.é_valida() {
+            self.cursor = nova;
+        }
+    }
+
+    // Posição na tela (coluna, linha) do canto superior esquerdo da casa
+    fn tela(&self, casa: Coord) -> (u16, u16) {
+        let (x, y) = if self.virado { (7 - casa.x, 7 - casa.y) } else { (casa.x, casa.y) };
+        (MARGEM_X + x as u16 * LARGURA, MARGEM_Y + y as u16 * ALTURA)
+    }
+
+    fn casa_na_tela(&self, coluna: u16, linha: u16) -> Option<Coord> {
+        if coluna < MARGEM_X || linha < MARGEM_Y {
+            return None;
+        }
+        let x = ((coluna - MARGEM_X) / LARGURA) as i32;
+        let y = ((linha - MARGEM_Y) / ALTURA) as i32;
+        let casa = if self.virado { Coord { x: 7 - x, y: 7 - y } } else { Coord { x, y } };
+        casa.é_valida().then_some(casa)
+    }
+
+    fn cor_de_fundo(&self, casa: Coord) -> Color {
+        if (casa.x + casa.y) % 2 == 0 {
+            return CASA_CLARA;
+        }
+        if casa == self.cursor {
+            return COR_CURSOR;
+        }
+        if self.selecao.contains(&casa) {
+            return COR_SELECAO;
+        }
+        if self.selecao.is_empty() {
+            let movel = self
+                .partida
+                .todas_jogadas_possiveis()
+                .iter()
+                .any(|sequencia| sequencia[0].origem() == casa);
+            return if movel && self.fim.is_none() { COR_MOVEL } else { CASA_ESCURA };
+        }
+        let jogadas = self.partida.todas_jogadas_possiveis();
+        let mut cor = CASA_ESCURA;
+        for indice in self.candidatas(&self.selecao) {
+            for jogada in &jogadas[indice] {
+                if let Jogada::Capturar(_, capturada, _) = jogada {
+                    if *capturada == casa {
+                        return COR_CAPTURA;
+                    }
+                }
+                if jogada.destino() == casa {
+                    cor = if jogada == jogadas[indice].last().unwrap() { COR_DESTINO } else { COR_POUSO };
+                }
+            }
+        }
+        cor
+    }
+
+    fn desenhar(&self, saida: &mut impl Write) -> io::Result<()> {
+        queue!(saida, ResetColor, Clear(ClearType::All))?;
+
+        // Coordenadas
+        for i in 0..8 {
+            let casa = Coord { x: i, y: i };
+            let (coluna, linha) = self.tela(casa);
+            let letra = (b'a' + i as u8) as char;
+            queue!(
+                saida,
+                cursor::MoveTo(coluna + LARGURA / 2, MARGEM_Y + 8 * ALTURA),
+                Print(letra),
+                cursor::MoveTo(1, linha + ALTURA / 2),
+                Print(8 - i),
+            )?;
+        }
+
+        // Tabuleiro
+        for y in 0..8 {
+            for x in 0..8 {
+                let casa = Coord { x, y };
+                let (coluna, linha) = self.tela(casa);
+                let fundo = self.cor_de_fundo(casa);
+                let (simbolo, cor) = match self.partida.casa(casa) {
+                    Casa::Ocupada(Pedra::Branca) => ("⛀", Color::White),
+                    Casa::Ocupada(Pedra::DamaBranca) => ("⛁", Color::White),
+                    Casa::Ocupada(Pedra::Preta) => ("⛂", Color::Black),
+                    Casa::Ocupada(Pedra::DamaPreta) => ("⛃", Color::Black),
+                    Casa::Vazia => (" ", Color::Reset),
+                };
+                for dy in 0..ALTURA {
+                    let conteudo = if dy == ALTURA / 2 {
+                        format!("{:^width$}", simbolo, width = LARGURA as usize)
+                    } else {
+                        " ".repeat(LARGURA as usize)
+                    };
+                    queue!(
+                        saida,
+                        cursor::MoveTo(coluna, linha + dy),
+                        SetBackgroundColor(fundo),
+                        SetForegroundColor(cor),
+                        Print(conteudo),
+                    )?;
+                }
+            }
+        }
+        queue!(saida, ResetColor)?;
+
+        // Lista de lances
+        queue!(saida, cursor::MoveTo(PAINEL_X, MARGEM_Y), Print("Lances"))?;
+        let historico = self.partida.historico();
+        let preto_começou = *self.partida.posicao_inicial().get_vez() == Cor::Preto;
+        let mut linhas = vec![];
+        let mut i = 0;
+        while i < historico.len() {
+            let numero = linhas.len() + 1;
+            if i == 0 && preto_começou {
+                linhas.push(format!("{numero:>3}. {:<10} {}", "...", notacao(&historico[0])));
+                i += 1;
+                continue;
+            }
+            let branco = notacao(&historico[i]);
+            let preto = historico.get(i + 1).map(|sequencia| notacao(sequencia)).unwrap_or_default();
+            linhas.push(format!("{numero:>3}. {branco:<10} {preto}"));
+            i += 2;
+        }
+        let visiveis = (ALTURA * 8 - 1) as usize;
+        let inicio = linhas.len().saturating_sub(visiveis);
+        for (j, linha) in linhas[inicio..].iter().enumerate() {
+            queue!(saida, cursor::MoveTo(PAINEL_X, MARGEM_Y + 1 + j as u16), Print(linha))?;
+        }
+
+        // Barra de status
+        let base = MARGEM_Y + ALTURA * 8 + 2;
+        let situacao = match &self.fim {
+            Some(fim) => fim.clone(),
+            None => format!("Vez do {:?}", self.partida.get_vez()),
+        };
+        queue!(
+            saida,
+            cursor::MoveTo(1, base),
+            SetBackgroundColor(Color::DarkGrey),
+            SetForegroundColor(Color::White),
+            Print(format!(
+                " {situacao}  |  contador de empate: {:.1}  |  lance {} ",
+                self.partida.get_contador_empate(),
+                historico.len() / 2 + 1
+            )),
+            ResetColor,
+            cursor::MoveTo(1, base + 1),
+            Print(&self.mensagem),
+            cursor::MoveTo(1, base + 2),
+            Print("setas: mover  enter/espaço/clique: escolher  esc: cancelar  u: desfazer  f: virar  n: nova  q: sair"),
+        )?;
+        saida.flush()
+    }
+
+    // Retorna false quando o programa deve terminar
+    fn tratar(&mut self, evento: Event) -> bool {
+        match evento {
+            Event::Key(tecla) if tecla.kind != KeyEventKind::Release => match tecla.code {
+                KeyCode::Char('q') => return false,
+                KeyCode::Up => self.mover_cursor(0, -1),
+                KeyCode::Down => self.mover_cursor(0, 1),
+                KeyCode::Left => self.mover_cursor(-1, 0),
+                KeyCode::Right => self.mover_cursor(1, 0),
+                KeyCode::Enter | KeyCode::Char(' ') => self.selecionar(self.cursor),
+                KeyCode::Esc => self.selecao.clear(),
+                KeyCode::Char('u') => self.desfazer(),
+                KeyCode::Char('f') => self.virado = !self.virado,
+                KeyCode::Char('n') => *self = Tui { virado: self.virado, ..Tui::new() },
+                _ => (),
+            },
+            Event::Mouse(mouse) if mouse.kind == MouseEventKind::Down(MouseButton::Left) => {
+                if let Some(casa) = self.casa_na_tela(mouse.column, mouse.row) {
+                    self.cursor = casa;
+                    self.selecionar(casa);
+                }
+            }
+            _ => (),
+        }
+        true
+    }
+}
+
+fn executar(saida: &mut impl Write) -> io::Result<()> {
+    let mut tui = Tui::new();
+    loop {
+        tui.desenhar(saida)?;
+        if !tui.tratar(event::read()?) {
+            return Ok(());
+        }
+    }
+}
+
+fn restaurar_terminal() {
+    execute!(io::stdout(), cursor::Show, DisableMouseCapture, LeaveAlternateScreen).ok();
+    terminal::disable_raw_mode().ok();
+}
+
+// Devolve o terminal ao normal em qualquer saída de `main`, inclusive por erro
+struct ModoTela;
+
+impl ModoTela {
+    fn ativar() -> io::Result<ModoTela> {
+        terminal::enable_raw_mode()?;
+        let modo = ModoTela;
+        execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture, cursor::Hide)?;
+        Ok(modo)
+    }
+}
+
+impl Drop for ModoTela {
+    fn drop(&mut self) {
+        restaurar_terminal();
+    }
+}
+
+fn main() -> io::Result<()> {
+    // Num pânico o terminal volta antes da mensagem, para ela aparecer na tela normal
+    let padrao = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restaurar_terminal();
+        padrao(info);
+    }));
+    let _modo = ModoTela::ativar()?;
+    executar(&mut io::stdout())
+}
@@ -0,0 +1,41 @@
+use damas::rede::ws::{ServidorWs, TEMPO_PADRAO};
+
+use std::process;
+use std::time::Duration;
+
+const USO: &str = "Uso: damas_ws [endereço] [minutos por jogador]   (padrão: 127.0.0.1:7879, 10 minutos)";
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let endereco = args.next().unwrap_or_else(|| "127.0.0.1:7879".to_string());
+    if endereco == "-h" || endereco == "--ajuda" {
+        println!("{USO}");
+        return;
+    }
+    let tempo = match args.next().map(|minutos| minutos.parse::<f64>()) {
+        None => Some(TEMPO_PADRAO),
+        // Infinito ou grande demais não cabe numa Duration
+        Some(Ok(minutos)) if minutos > 0.0 => Duration::try_from_secs_f64(minutos * 60.0).ok(),
+        Some(_) => None,
+    };
+    let Some(tempo) = tempo else {
+        eprintln!("{USO}");
+        process::exit(1);
+    };
+    if args.next().is_some() {
+        eprintln!("{USO}");
+        process::exit(1);
+    }
+    let servidor = match ServidorWs::escutar(&endereco) {
+        Ok(servidor) => servidor.com_tempo(tempo),
+        Err(erro) => {
+            eprintln!("Erro: não foi possível escutar em {endereco}: {erro}");
+            process::exit(1);
+        }
+    };
+    println!("Servidor WebSocket de damas escutando em ws://{endereco}");
+    if let Err(erro) = servidor.servir() {
+        eprintln!("Erro: {erro}");
+        process::exit(1);
+    }
+}
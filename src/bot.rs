@@ -0,0 +1,236 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::{Casa, Jogada, Partida, Pedra, Resultado};
+
+// Busca negamax com poda alfa-beta e aprofundamento iterativo. As avaliações
+// são sempre do ponto de vista de quem tem a vez.
+
+pub const VITORIA: i32 = 100_000;
+
+const VALOR_PEÃO: i32 = 100;
+const VALOR_DAMA: i32 = 300;
+const LIMITE_CAPTURAS: u32 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bot {
+    pub profundidade: u32,
+    pub tempo: Option<Duration>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Analise {
+    /// Índice da melhor jogada em `todas_jogadas_possiveis`.
+    pub indice: usize,
+    pub avaliacao: i32,
+    pub profundidade: u32,
+    /// Variante principal, começando pela melhor jogada.
+    pub pv: Vec<Vec<Jogada>>,
+    pub nos: u64,
+}
+
+impl Default for Bot {
+    fn default() -> Self {
+        Bot::new(6)
+    }
+}
+
+struct Busca<'a> {
+    limite: Option<Instant>,
+    parar: &'a AtomicBool,
+    // Capturas pendentes continuam sendo exploradas depois da profundidade
+    // nominal, mas só até este ply.
+    teto: u32,
+    nos: u64,
+    esgotou: bool,
+}
+
+impl Bot {
+    pub fn new(profundidade: u32) -> Self {
+        Bot { profundidade, tempo: None }
+    }
+
+    pub fn com_tempo(mut self, tempo: Duration) -> Self {
+        self.tempo = Some(tempo);
+        self
+    }
+
+    pub fn escolher(&self, partida: &Partida) -> Option<usize> {
+        self.analisar(partida).map(|analise| analise.indice)
+    }
+
+    /// Busca a melhor jogada até a profundidade ou o tempo configurados.
+    /// Retorna None se quem tem a vez não tem jogadas.
+    pub fn analisar(&self, partida: &Partida) -> Option<Analise> {
+        self.analisar_com(partida, &AtomicBool::new(false), |_| ())
+    }
+
+    /// Como `analisar`, mas interrompe a busca quando `parar` vira true e chama
+    /// `progresso` ao fim de cada iteração do aprofundamento.
+    pub fn analisar_com(&self, partida: &Partida, parar: &AtomicBool, mut progresso: impl FnMut(&Analise)) -> Option<Analise> {
+        let jogadas = partida.todas_jogadas_possiveis();
+        if jogadas.is_empty() {
+            return None;
+        }
+        let mut busca = Busca {
+            limite: self.tempo.map(|tempo| Instant::now() + tempo),
+            parar,
+            teto: 0,
+            nos: 0,
+            esgotou: false,
+        };
+        let mut ordem: Vec<usize> = (0..jogadas.len()).collect();
+        let mut melhor: Option<Analise> = None;
+
+        for profundidade in 1..=self.profundidade.max(1) {
+            busca.teto = profundidade + LIMITE_CAPTURAS;
+            let mut alfa = -VITORIA - 1;
+            let mut resultado: Option<(usize, i32, Vec<Vec<Jogada>>)> = None;
+            for &indice in &ordem {
+                let (valor, mut pv) = match jogar_clone(partida, indice) {
+                    Filho::Fim(valor) => (valor, vec![]),
+                    Filho::Continua(filho) => {
+                        let (valor, pv) = busca.negamax(&filho, profundidade - 1, 1, -VITORIA - 1, -alfa);
+                        (-valor, pv)
+                    }
+                };
+                if busca.esgotou {
+                    break;
+                }
+                if valor > alfa {
+                    alfa = valor;
+                    pv.insert(0, jogadas[indice].clone());
+                    resultado = Some((indice, valor, pv));
+                }
+            }
+            // Uma iteração interrompida só é usada se nada foi concluído ainda
+            if busca.esgotou && melhor.is_some() {
+                break;
+            }
+            if let Some((indice, avaliacao, pv)) = resultado {
+                ordem.retain(|&i| i != indice);
+                ordem.insert(0, indice);
+                let analise = Analise { indice, avaliacao, profundidade, pv, nos: busca.nos };
+                progresso(&analise);
+                melhor = Some(analise);
+            }
+            if busca.esgotou || alfa.abs() >= VITORIA - 1000 {
+                break;
+            }
+        }
+        // Sem tempo nem para a primeira iteração: a primeira jogada serve, já
+        // que quem tem jogadas nunca fica sem resposta
+        let melhor = melhor.unwrap_or_else(|| Analise {
+            indice: ordem[0],
+            avaliacao: avaliar(partida),
+            profundidade: 0,
+            pv: vec![jogadas[ordem[0]].clone()],
+            nos: 0,
+        });
+        Some(Analise { nos: busca.nos, ..melhor })
+    }
+}
+
+enum Filho {
+    Fim(i32),
+    Continua(Partida),
+}
+
+// Joga numa cópia. Se o jogo acabou, devolve a avaliação do ponto de vista de quem jogou.
+fn jogar_clone(partida: &Partida, indice: usize) -> Filho {
+    let mut filho = partida.clone();
+    match filho.jogar(indice) {
+        Resultado::FimDoJogo(Some(_)) => Filho::Fim(VITORIA),
+        Resultado::FimDoJogo(None) => Filho::Fim(0),
+        _ => Filho::Continua(filho),
+    }
+}
+
+impl Busca<'_> {
+    fn negamax(&mut self, partida: &Partida, profundidade: u32, ply: u32, mut alfa: i32, beta: i32) -> (i32, Vec<Vec<Jogada>>) {
+        self.nos += 1;
+        // O primeiro nó já confere, para um orçamento que acabou antes da busca
+        if self.nos % 1024 == 1
+            && (self.limite.is_some_and(|limite| Instant::now() >= limite) || self.parar.load(Ordering::Relaxed))
+        {
+            self.esgotou = true;
+        }
+        if self.esgotou {
+            return (0, vec![]);
+        }
+
+        let jogadas = partida.todas_jogadas_possiveis();
+        if jogadas.is_empty() {
+            // Sem jogadas, quem tem a vez perde
+            return (-VITORIA + ply as i32, vec![]);
+        }
+        let tem_captura = matches!(jogadas[0].first(), Some(Jogada::Capturar(..)));
+        if profundidade == 0 && (!tem_captura || ply >= self.teto) {
+            return (avaliar(partida), vec![]);
+        }
+
+        let mut melhor_pv = vec![];
+        let mut melhor = -VITORIA - 1;
+        for (indice, sequencia) in jogadas.iter().enumerate() {
+            let (valor, mut pv) = match jogar_clone(partida, indice) {
+                Filho::Fim(valor) => (valor - ply as i32, vec![]),
+                Filho::Continua(filho) => {
+                    let (valor, pv) = self.negamax(&filho, profundidade.saturating_sub(1), ply + 1, -beta, -alfa);
+                    (-valor, pv)
+                }
+            };
+            if valor > melhor {
+                melhor = valor;
+                pv.insert(0, sequencia.clone());
+                melhor_pv = pv;
+            }
+            alfa = alfa.max(valor);
+            if alfa >= beta {
+                break;
+            }
+        }
+        (melhor, melhor_pv)
+    }
+}
+
+/// Avaliação estática do ponto de vista de quem tem a vez: material e avanço dos peões.
+pub fn avaliar(partida: &Partida) -> i32 {
+    let mut pontos = 0;
+    for (y, linha) in partida.get_tabuleiro().iter().enumerate() {
+        for casa in linha {
+            if let Casa::Ocupada(pedra) = *casa {
+                let valor = match pedra {
+                    Pedra::Branca => VALOR_PEÃO + (7 - y as i32) * 2,
+                    Pedra::Preta => VALOR_PEÃO + y as i32 * 2,
+                    Pedra::DamaBranca | Pedra::DamaPreta => VALOR_DAMA,
+                };
+                pontos += if pedra.é_branca() { valor } else { -valor };
+            }
+        }
+    }
+    if partida.é_a_vez_do_branco() { pontos } else { -pontos }
+}
+
+#[test]
+fn testar_bot_captura_o_maximo() {
+    // O branco em 22 é obrigado a capturar; o bot deve achar a única sequência
+    let partida = Partida::from_fen("W:W22:B18,11").unwrap();
+    let analise = Bot::new(4).analisar(&partida).unwrap();
+    assert_eq!(crate::notacao::notacao(&analise.pv[0]), "22x15x8");
+    assert!(analise.avaliacao >= VITORIA - 10);
+}
+
+#[test]
+fn testar_bot_sem_jogadas() {
+    let partida = Partida::from_fen("W:W:B1").unwrap();
+    assert!(Bot::default().analisar(&partida).is_none());
+}
+
+#[test]
+fn testar_bot_sem_tempo() {
+    // Parado antes de concluir a primeira iteração, ainda devolve uma jogada
+    let partida = Partida::default();
+    let analise = Bot::new(6).analisar_com(&partida, &AtomicBool::new(true), |_| ()).unwrap();
+    assert!(analise.indice < partida.todas_jogadas_possiveis().len());
+    assert_eq!(analise.profundidade, 0);
+}
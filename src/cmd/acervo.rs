@@ -0,0 +1,150 @@
+use damas::aberturas::Catalogo;
+use damas::acervo::{Acervo, Consulta};
+use damas::pdn;
+use damas::{Cor, Partida};
+
+use std::collections::BTreeMap;
+use std::fs;
+
+pub const USO: &str = "\
+Uso: damas_cmd acervo importar <arquivo.pdn>...   acrescenta as partidas ao acervo
+     damas_cmd acervo buscar [filtros]              lista as partidas encontradas
+     damas_cmd acervo exportar <saida.pdn> [filtros] grava as partidas encontradas em PDN
+     damas_cmd acervo mostrar <n> [lance]           mostra a posição da partida n no lance dado
+     damas_cmd acervo aberturas [filtros]           resultados das partidas encontradas por abertura
+  --arquivo <arquivo>     arquivo do acervo (padrão: acervo.dat)
+  --limite <n>            máximo de partidas listadas por buscar (padrão: 50)
+Filtros:
+  --jogador <nome>        nome nas brancas ou nas pretas (basta parte do nome)
+  --branco <nome>, --preto <nome>
+  --desde <data>, --ate <data>   datas no formato AAAA.MM.DD
+  --resultado <r>         2-0, 1-1 ou 0-2
+  --fen <fen>             partidas que passam pela posição
+  --material <m>          partidas que chegam ao material, por exemplo \"3D x 1D\"
+  --lances <lances>       partidas que começam com os lances, por exemplo \"22-18 11-15\"";
+
+const ARQUIVO_PADRAO: &str = "acervo.dat";
+
+pub fn executar(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let comando = args.next().ok_or("informe o comando: importar, buscar, exportar, mostrar ou aberturas")?;
+    let mut arquivo = ARQUIVO_PADRAO.to_string();
+    let mut limite = 50;
+    let mut consulta = Consulta::default();
+    let mut posicionais = vec![];
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            posicionais.push(arg);
+            continue;
+        }
+        let valor = args.next().ok_or(format!("{arg} precisa de um valor"))?;
+        match arg.as_str() {
+            "--arquivo" => arquivo = valor,
+            "--limite" => limite = valor.parse().map_err(|_| format!("{arg}: '{valor}' não é um número"))?,
+            "--jogador" => consulta.jogador = Some(valor),
+            "--branco" => consulta.branco = Some(valor),
+            "--preto" => consulta.preto = Some(valor),
+            "--desde" => consulta.desde = Some(valor),
+            "--ate" => consulta.ate = Some(valor),
+            "--resultado" => consulta.resultado = Some(valor),
+            "--fen" => consulta = consulta.posicao(&Partida::from_fen(&valor).map_err(|erro| erro.to_string())?),
+            "--material" => consulta = consulta.material(valor.parse().map_err(|erro| format!("{arg}: {erro}"))?),
+            "--lances" => consulta.prefixo = valor.split_whitespace().map(str::to_string).collect(),
+            _ => return Err(format!("opção desconhecida: {arg}")),
+        }
+    }
+
+    let mut acervo = Acervo::abrir(&arquivo).map_err(|erro| format!("{arquivo}: {erro}"))?;
+    let catalogo = Catalogo::padrao();
+    match comando.as_str() {
+        "importar" => {
+            if posicionais.is_empty() {
+                return Err("informe os arquivos PDN".to_string());
+            }
+            for caminho in &posicionais {
+                let texto = fs::read_to_string(caminho).map_err(|erro| format!("{caminho}: {erro}"))?;
+                let importacao = acervo.importar(&texto).map_err(|erro| format!("{caminho}: {erro}"))?;
+                for (jogo, erro) in &importacao.erros {
+                    eprintln!("{caminho}: partida {jogo} ignorada: {erro}");
+                }
+                println!("{caminho}: {} partidas importadas", importacao.importados);
+            }
+            acervo.salvar(&arquivo).map_err(|erro| format!("{arquivo}: {erro}"))?;
+            println!("{} partidas no acervo", acervo.len());
+        }
+        "buscar" => {
+            let achados = acervo.buscar(&consulta);
+            for achado in achados.iter().take(limite) {
+                let jogo = acervo.jogo(achado.jogo).unwrap();
+                let abertura = catalogo.classificar(&jogo.partida()).map_or("-".to_string(), |abertura| abertura.to_string());
+                println!(
+                    "{:>6}  {} x {}  {}  {}  {abertura}  lance {}",
+                    achado.jogo + 1,
+                    jogo.tag("White").unwrap_or("?"),
+                    jogo.tag("Black").unwrap_or("?"),
+                    jogo.tag("Date").unwrap_or("????.??.??"),
+                    jogo.resultado,
+                    achado.lance
+                );
+            }
+            if achados.len() > limite {
+                println!("... e mais {} partidas", achados.len() - limite);
+            }
+            println!("{} partidas encontradas", achados.len());
+        }
+        "exportar" => {
+            let saida = posicionais.first().ok_or("informe o arquivo de saída")?;
+            let texto: Vec<String> =
+                acervo.buscar(&consulta).iter().map(|achado| acervo.jogo(achado.jogo).unwrap().pdn().to_string()).collect();
+            fs::write(saida, texto.join("\n")).map_err(|erro| format!("{saida}: {erro}"))?;
+            println!("{} partidas gravadas em {saida}", texto.len());
+        }
+        "aberturas" => {
+            // Vitórias das brancas, empates e vitórias das pretas por abertura
+            let mut placares: BTreeMap<String, [u32; 4]> = BTreeMap::new();
+            for achado in acervo.buscar(&consulta) {
+                let jogo = acervo.jogo(achado.jogo).unwrap();
+                let abertura = catalogo.classificar(&jogo.partida()).map_or("-- sem abertura".to_string(), |abertura| abertura.to_string());
+                let placar = placares.entry(abertura).or_default();
+                placar[0] += 1;
+                match pdn::vencedor(&jogo.resultado) {
+                    Some(Some(Cor::Branco)) => placar[1] += 1,
+                    Some(None) => placar[2] += 1,
+                    Some(Some(Cor::Preto)) => placar[3] += 1,
+                    None => (),
+                }
+            }
+            println!("{:<36} {:>8} {:>8} {:>8} {:>8}", "Abertura", "Partidas", "Brancas", "Empates", "Pretas");
+            for (abertura, [partidas, brancas, empates, pretas]) in placares {
+                let decididas = (brancas + empates + pretas).max(1) as f64;
+                let percentual = |n: u32| 100.0 * n as f64 / decididas;
+                println!(
+                    "{abertura:<36} {partidas:>8} {:>7.1}% {:>7.1}% {:>7.1}%",
+                    percentual(brancas),
+                    percentual(empates),
+                    percentual(pretas)
+                );
+            }
+        }
+        "mostrar" => {
+            let numero = posicionais.first().ok_or("informe o número da partida")?;
+            let jogo = numero
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| acervo.jogo(n.wrapping_sub(1)))
+                .ok_or(format!("não há partida {numero} no acervo"))?;
+            let lance = match posicionais.get(1) {
+                Some(lance) => lance.parse().map_err(|_| format!("'{lance}' não é um número"))?,
+                None => jogo.lances(),
+            };
+            let partida = jogo.partida_ate(lance);
+            println!("{} x {}  {}", jogo.tag("White").unwrap_or("?"), jogo.tag("Black").unwrap_or("?"), jogo.resultado);
+            if let Some(abertura) = catalogo.classificar(&jogo.partida()) {
+                println!("{abertura}");
+            }
+            print!("{partida}");
+            println!("Lance {} de {}  {}", lance.min(jogo.lances()), jogo.lances(), partida.fen());
+        }
+        _ => return Err(format!("comando desconhecido: {comando}")),
+    }
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use damas::animacao::Animacao;
+use damas::pdn;
+use damas::Partida;
+
+use std::fs;
+
+pub const USO: &str = "\
+Uso: damas_cmd animacao <arquivo.pdn> -o <saida.svg|saida.gif> [opções]
+  --jogo <n>            qual jogo do arquivo animar (padrão: 1)
+  --salto <ms>          duração de cada salto (padrão: 400)
+  --pausa <ms>          pausa depois de cada lance (padrão: 800)
+  --tamanho <px>        largura do tabuleiro (padrão: 400)
+  --virado              desenha do lado do preto
+  --sem-legenda         omite o número do lance e a notação";
+
+pub fn executar(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let arquivo = args.next().ok_or("informe o arquivo PDN")?;
+    let mut animacao = Animacao::new();
+    let mut saida: Option<String> = None;
+    let mut jogo = 1;
+
+    while let Some(arg) = args.next() {
+        let mut numero = || {
+            let valor = args.next().ok_or(format!("{arg} precisa de um valor"))?;
+            valor.parse::<u32>().map_err(|_| format!("{arg}: '{valor}' não é um número"))
+        };
+        match arg.as_str() {
+            "-o" => saida = Some(args.next().ok_or("-o precisa de um valor")?),
+            "--jogo" => jogo = numero()? as usize,
+            "--salto" => animacao.salto = numero()?,
+            "--pausa" => animacao.pausa = numero()?,
+            "--tamanho" => animacao.diagrama.tamanho = numero()?,
+            "--virado" => animacao.diagrama.virado = true,
+            "--sem-legenda" => animacao.legenda = false,
+            _ => return Err(format!("opção desconhecida: {arg}")),
+        }
+    }
+    let saida = saida.ok_or("informe o arquivo de saída com -o")?;
+
+    let texto = fs::read_to_string(&arquivo).map_err(|erro| format!("{arquivo}: {erro}"))?;
+    let jogos = pdn::ler(&texto).map_err(|erro| erro.to_string())?;
+    let partida = jogos
+        .get(jogo.wrapping_sub(1))
+        .ok_or(format!("{arquivo} tem {} jogo(s)", jogos.len()))?
+        .partida()
+        .map_err(|erro| erro.to_string())?;
+
+    let bytes = if saida.to_lowercase().ends_with(".gif") {
+        gif(&animacao, &partida)?
+    } else {
+        animacao.svg(&partida).into_bytes()
+    };
+    fs::write(&saida, bytes).map_err(|erro| format!("{saida}: {erro}"))
+}
+
+#[cfg(feature = "gif")]
+fn gif(animacao: &Animacao, partida: &Partida) -> Result<Vec<u8>, String> {
+    animacao.gif(partida)
+}
+
+#[cfg(not(feature = "gif"))]
+fn gif(_: &Animacao, _: &Partida) -> Result<Vec<u8>, String> {
+    Err("damas_cmd foi compilado sem a feature 'gif'".to_string())
+}
@@ -0,0 +1,58 @@
+use damas::anotador::Anotador;
+use damas::bot::Bot;
+use damas::pdn;
+
+use std::fs;
+
+pub const USO: &str = "\
+Uso: damas_cmd anotar <arquivo.pdn> [-o <saida.pdn>] [opções]
+  Analisa cada lance das partidas e grava o PDN com a avaliação depois de cada
+  lance, sinais (!, !!, ?, ??) e a melhor continuação depois dos erros.
+  -o <saida.pdn>        arquivo de saída (padrão: mostra na tela)
+  --jogo <n>            anota só o jogo n do arquivo
+  --profundidade <n>    profundidade da análise (padrão: 6)
+  --variante <n>        máximo de lances da continuação sugerida (padrão: 8)";
+
+pub fn executar(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let arquivo = args.next().ok_or("informe o arquivo PDN")?;
+    let mut anotador = Anotador::default();
+    let mut saida: Option<String> = None;
+    let mut jogo = None;
+
+    while let Some(arg) = args.next() {
+        let mut numero = || {
+            let valor = args.next().ok_or(format!("{arg} precisa de um valor"))?;
+            valor.parse::<u32>().map_err(|_| format!("{arg}: '{valor}' não é um número"))
+        };
+        match arg.as_str() {
+            "-o" => saida = Some(args.next().ok_or("-o precisa de um valor")?),
+            "--jogo" => jogo = Some(numero()? as usize),
+            "--profundidade" => anotador = anotador.com_bot(Bot::new(numero()?)),
+            "--variante" => anotador.lances_da_variante = numero()? as usize,
+            _ => return Err(format!("opção desconhecida: {arg}")),
+        }
+    }
+
+    let texto = fs::read_to_string(&arquivo).map_err(|erro| format!("{arquivo}: {erro}"))?;
+    let jogos = pdn::ler(&texto).map_err(|erro| format!("{arquivo}: {erro}"))?;
+    let escolhidos: Vec<(usize, &pdn::JogoPdn)> = match jogo {
+        Some(n) => vec![(n, jogos.get(n.wrapping_sub(1)).ok_or(format!("{arquivo} não tem o jogo {n}"))?)],
+        None => jogos.iter().enumerate().map(|(i, jogo)| (i + 1, jogo)).collect(),
+    };
+
+    let mut anotados = vec![];
+    for (n, jogo) in escolhidos {
+        eprintln!("Anotando o jogo {n} ({} lances)", jogo.lances.len());
+        let anotado = anotador.anotar_pdn(jogo).map_err(|erro| format!("{arquivo}: jogo {n}: {erro}"))?;
+        anotados.push(anotado.to_string());
+    }
+    let texto = anotados.join("\n");
+    match saida {
+        Some(saida) => {
+            fs::write(&saida, texto).map_err(|erro| format!("{saida}: {erro}"))?;
+            println!("{} jogos anotados em {saida}", anotados.len());
+        }
+        None => print!("{texto}"),
+    }
+    Ok(())
+}
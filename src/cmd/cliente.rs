@@ -0,0 +1,92 @@
+use damas::rede::cliente::Cliente;
+use damas::rede::protocolo::{Comando, Mensagem, Motivo};
+use damas::render::{Coordenadas, Renderizador};
+use damas::{Cor, Partida};
+
+use std::io::{self, BufRead};
+use std::process;
+use std::thread;
+
+pub const USO: &str = "\
+Uso: damas_cmd conectar <endereço> [nome]
+Durante a partida digite um lance (22-18, c3-d4) ou:
+  empate     oferece ou aceita empate
+  recusar    recusa a oferta de empate
+  desistir   abandona a partida
+  sair       desconecta";
+
+pub fn executar(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let endereco = args.next().ok_or("informe o endereço do servidor")?;
+    let nome = args.next().unwrap_or_else(|| "anônimo".to_string());
+    let cliente = Cliente::conectar(&endereco, &nome).map_err(|erro| format!("{endereco}: {erro}"))?;
+    let (mut remetente, mut receptor) = cliente.dividir();
+
+    thread::spawn(move || {
+        let mut renderizador = Renderizador::new().coordenadas(Coordenadas::Algebrica);
+        loop {
+            let mensagem = match receptor.receber() {
+                Ok(mensagem) => mensagem,
+                Err(erro) => {
+                    println!("Conexão encerrada: {erro}");
+                    process::exit(1);
+                }
+            };
+            match mensagem {
+                Mensagem::BemVindo(cor) => {
+                    renderizador.virado = cor == Cor::Preto;
+                    println!("Você joga com {:?}", cor);
+                }
+                Mensagem::Aguardando => println!("Aguardando adversário..."),
+                Mensagem::Inicio { branco, preto } => println!("Partida iniciada: {branco} (Branco) x {preto} (Preto)"),
+                Mensagem::Lance(cor, lance) => println!("{:?} jogou {lance}", cor),
+                Mensagem::Tabuleiro(fen) => {
+                    if let Ok(partida) = Partida::from_fen(&fen) {
+                        print!("{}", renderizador.renderizar(&partida));
+                    }
+                }
+                Mensagem::Vez(cor) => println!("Vez do {:?}", cor),
+                Mensagem::Empate(cor) => println!("{:?} ofereceu empate ('empate' aceita, 'recusar' recusa)", cor),
+                Mensagem::Recusado(cor) => println!("{:?} recusou o empate", cor),
+                Mensagem::Erro(erro) => println!("Erro: {erro}"),
+                Mensagem::Fim { vencedor, motivo } => {
+                    let motivo = match motivo {
+                        Motivo::Vitoria => "sem pedras",
+                        Motivo::SemJogadas => "sem jogadas",
+                        Motivo::Desistencia => "desistência",
+                        Motivo::Desconexao => "desconexão",
+                        Motivo::Empate => "regra de empate",
+                        Motivo::Acordo => "acordo",
+                        Motivo::Tempo => "tempo esgotado",
+                    };
+                    match vencedor {
+                        Some(cor) => println!("Fim de jogo: {:?} venceu ({motivo})", cor),
+                        None => println!("Fim de jogo: empate ({motivo})"),
+                    }
+                    process::exit(0);
+                }
+            }
+        }
+    });
+
+    for linha in io::stdin().lock().lines() {
+        let linha = linha.map_err(|erro| erro.to_string())?;
+        let comando = match linha.trim() {
+            "" => continue,
+            "ajuda" => {
+                println!("{USO}");
+                continue;
+            }
+            "empate" => Comando::Empate,
+            "recusar" => Comando::Recusar,
+            "desistir" => Comando::Desistir,
+            "sair" => Comando::Sair,
+            lance => Comando::Jogar(lance.to_string()),
+        };
+        let saindo = comando == Comando::Sair;
+        remetente.enviar(&comando).map_err(|erro| erro.to_string())?;
+        if saindo {
+            break;
+        }
+    }
+    Ok(())
+}
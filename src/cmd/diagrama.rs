@@ -0,0 +1,83 @@
+use damas::diagrama::{self, Diagrama};
+use damas::Partida;
+
+use std::fs;
+
+pub const USO: &str = "\
+Uso: damas_cmd diagrama <fen> [opções]
+  -o <arquivo>          grava em arquivo (.svg ou .png); sem -o escreve o SVG na saída
+  --tamanho <px>        largura do tabuleiro (padrão: 400)
+  --virado              desenha do lado do preto
+  --sem-coordenadas     omite as letras e números das bordas
+  --jogadas <lances>    desenha setas para os lances, jogados em sequência a partir da posição
+  --cor <nome>=<cor>    muda uma cor: clara, escura, branca, preta, borda, seta, captura ou coroa";
+
+pub fn executar(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let fen = args.next().ok_or("informe o FEN da posição")?;
+    let partida = Partida::from_fen(&fen).map_err(|erro| erro.to_string())?;
+    let mut diagrama = Diagrama::new();
+    let mut saida: Option<String> = None;
+
+    while let Some(arg) = args.next() {
+        let mut valor = || args.next().ok_or(format!("{arg} precisa de um valor"));
+        match arg.as_str() {
+            "-o" => saida = Some(valor()?),
+            "--tamanho" => {
+                let valor = valor()?;
+                diagrama.tamanho = valor.parse().map_err(|_| format!("--tamanho: '{valor}' não é um número"))?;
+            }
+            "--virado" => diagrama.virado = true,
+            "--sem-coordenadas" => diagrama.coordenadas = false,
+            "--jogadas" => {
+                let mut atual = partida.clone();
+                for lance in valor()?.split_whitespace() {
+                    let indice = atual.encontrar_jogada(lance).map_err(|erro| erro.to_string())?;
+                    diagrama.setas.push(atual.todas_jogadas_possiveis()[indice].clone());
+                    atual.jogar(indice);
+                }
+            }
+            "--cor" => {
+                let valor = valor()?;
+                let (nome, cor) = valor.split_once('=').ok_or("--cor: use <nome>=<cor>")?;
+                let cores = &mut diagrama.cores;
+                let alvo = match nome {
+                    "clara" => &mut cores.clara,
+                    "escura" => &mut cores.escura,
+                    "branca" => &mut cores.branca,
+                    "preta" => &mut cores.preta,
+                    "borda" => &mut cores.borda,
+                    "seta" => &mut cores.seta,
+                    "captura" => &mut cores.captura,
+                    "coroa" => &mut cores.coroa,
+                    _ => return Err(format!("--cor: cor desconhecida '{nome}'")),
+                };
+                if !diagrama::cor_valida(cor) {
+                    return Err(format!("--cor: '{cor}' não é uma cor; use #rrggbb, um nome ou rgb(r, g, b)"));
+                }
+                *alvo = cor.to_string();
+            }
+            _ => return Err(format!("opção desconhecida: {arg}")),
+        }
+    }
+
+    match saida {
+        None => print!("{}", diagrama.svg(&partida)),
+        Some(arquivo) if arquivo.to_lowercase().ends_with(".png") => {
+            fs::write(&arquivo, png(&diagrama, &partida)?).map_err(|erro| format!("{arquivo}: {erro}"))?;
+        }
+        Some(arquivo) => {
+            fs::write(&arquivo, diagrama.svg(&partida)).map_err(|erro| format!("{arquivo}: {erro}"))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "png")]
+fn png(diagrama: &Diagrama, partida: &Partida) -> Result<Vec<u8>, String> {
+    diagrama.png(partida)
+}
+
+#[cfg(not(feature = "png"))]
+fn png(_: &Diagrama, _: &Partida) -> Result<Vec<u8>, String> {
+    Err("damas_cmd foi compilado sem a feature 'png'".to_string())
+}
@@ -0,0 +1,90 @@
+use damas::bot::Bot;
+use damas::pdn::{self, JogoPdn};
+use damas::rede::dxp::{AvisoDxp, Convite, SessaoDxp};
+use damas::{Cor, Partida};
+
+use std::fs;
+use std::net::{TcpListener, TcpStream};
+
+pub const USO: &str = "\
+Uso: damas_cmd dxp iniciar <endereço> [opções]   conecta e convida o outro programa
+     damas_cmd dxp seguir <endereço> [opções]    espera o convite de outro programa
+  --nome <nome>           nome enviado ao adversário (padrão: damas)
+  --profundidade <n>      profundidade máxima do bot (padrão: 6)
+  --cor branco|preto      ao iniciar, cor de quem segue (padrão: preto)
+  --minutos <n>           ao iniciar, tempo de cada jogador (padrão: 5)
+  --lances <n>            ao iniciar, lances dentro do tempo (padrão: 75)
+  --fen <fen>             ao iniciar, posição inicial
+  --pdn <arquivo>         grava a partida em PDN no fim";
+
+pub fn executar(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let papel = args.next().ok_or("informe o papel: iniciar ou seguir")?;
+    let endereco = args.next().ok_or("informe o endereço")?;
+    let mut nome = "damas".to_string();
+    let mut bot = Bot::default();
+    let mut convite = Convite::default();
+    let mut pdn: Option<String> = None;
+
+    while let Some(arg) = args.next() {
+        let valor = args.next().ok_or(format!("{arg} precisa de um valor"))?;
+        let numero = || valor.parse::<u32>().map_err(|_| format!("{arg}: '{valor}' não é um número"));
+        match arg.as_str() {
+            "--nome" => nome = valor,
+            "--profundidade" => bot.profundidade = numero()?,
+            "--cor" => {
+                convite.cor_do_seguidor = match valor.as_str() {
+                    "branco" => Cor::Branco,
+                    "preto" => Cor::Preto,
+                    _ => return Err(format!("{arg}: use 'branco' ou 'preto'")),
+                }
+            }
+            "--minutos" => convite.minutos = numero()?,
+            "--lances" => convite.lances = numero()?,
+            "--fen" => convite = convite.com_partida(&Partida::from_fen(&valor).map_err(|erro| erro.to_string())?),
+            "--pdn" => pdn = Some(valor),
+            _ => return Err(format!("opção desconhecida: {arg}")),
+        }
+    }
+
+    let mut sessao = match papel.as_str() {
+        "iniciar" => {
+            let conexao = TcpStream::connect(&endereco).map_err(|erro| format!("{endereco}: {erro}"))?;
+            SessaoDxp::iniciar(conexao, &nome, convite)
+        }
+        "seguir" => {
+            let escuta = TcpListener::bind(&endereco).map_err(|erro| format!("{endereco}: {erro}"))?;
+            println!("Esperando conexão em {endereco}...");
+            let (conexao, _) = escuta.accept().map_err(|erro| erro.to_string())?;
+            SessaoDxp::seguir(conexao, &nome)
+        }
+        _ => return Err(format!("papel desconhecido: {papel}")),
+    }
+    .map_err(|erro| erro.to_string())?;
+    println!("Partida contra {} ({:?} para nós)", sessao.adversario, sessao.cor);
+
+    let vencedor = sessao
+        .jogar(&bot, |aviso| match aviso {
+            AvisoDxp::Lance(cor, lance) => println!("{:?}: {lance}", cor),
+            AvisoDxp::Chat(texto) => println!("[chat] {texto}"),
+            AvisoDxp::Voltou(lances) => println!("O adversário voltou {lances} lance(s)"),
+        })
+        .map_err(|erro| erro.to_string())?;
+    let resultado = pdn::resultado(vencedor);
+    println!("Fim de jogo: {resultado}");
+
+    if let Some(arquivo) = pdn {
+        let mut jogo = JogoPdn::de_partida(&sessao.partida);
+        let (branco, preto) = match sessao.cor {
+            Cor::Branco => (nome, sessao.adversario.clone()),
+            Cor::Preto => (sessao.adversario.clone(), nome),
+        };
+        jogo.set_tag("White", &branco);
+        jogo.set_tag("Black", &preto);
+        // O adversário pode ter encerrado a partida antes do fim dos lances
+        if !sessao.partida.encerrada() {
+            jogo.set_resultado(vencedor);
+        }
+        fs::write(&arquivo, jogo.to_string()).map_err(|erro| format!("{arquivo}: {erro}"))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use damas::aberturas::Catalogo;
+use damas::acervo::Acervo;
+use damas::explorador::{explorar, Estatistica};
+use damas::Partida;
+
+use std::fs;
+use std::io::{self, BufRead, Write};
+
+pub const USO: &str = "\
+Uso: damas_cmd explorar [opções]
+  --acervo <arquivo>      acervo criado com damas_cmd acervo (padrão: acervo.dat)
+  --pdn <arquivo>         usa as partidas do PDN em vez do acervo; pode ser repetido
+  --fen <fen>             começa na posição dada
+Comandos:
+  <n> ou <lance>          joga a jogada da linha n da tabela, ou em notação (22-18)
+  voltar                  volta um lance
+  inicio                  volta à posição inicial
+  partidas                lista as partidas que passam pela posição
+  sair                    encerra";
+
+fn mostrar(partida: &Partida, estatisticas: &[Estatistica]) {
+    print!("{partida}");
+    println!("{}", partida.fen());
+    if let Some(abertura) = Catalogo::padrao().classificar(partida) {
+        println!("Abertura: {abertura}");
+    }
+    println!("{:>3}  {:<12} {:>8} {:>8} {:>8} {:>8} {:>7}", "#", "Lance", "Partidas", "Brancas", "Empates", "Pretas", "Rating");
+    for (i, estatistica) in estatisticas.iter().enumerate() {
+        let (brancas, empates, pretas) = estatistica.percentuais();
+        let rating = estatistica.rating_medio.map_or("-".to_string(), |rating| format!("{rating:.0}"));
+        if estatistica.partidas == 0 {
+            println!("{:>3}  {:<12} {:>8}", i + 1, estatistica.lance, 0);
+        } else {
+            println!(
+                "{:>3}  {:<12} {:>8} {brancas:>7.1}% {empates:>7.1}% {pretas:>7.1}% {rating:>7}",
+                i + 1,
+                estatistica.lance,
+                estatistica.partidas
+            );
+        }
+    }
+}
+
+pub fn executar(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let mut arquivo = "acervo.dat".to_string();
+    let mut pdns = vec![];
+    let mut inicial = Partida::default();
+    while let Some(arg) = args.next() {
+        let valor = args.next().ok_or(format!("{arg} precisa de um valor"))?;
+        match arg.as_str() {
+            "--acervo" => arquivo = valor,
+            "--pdn" => pdns.push(valor),
+            "--fen" => inicial = Partida::from_fen(&valor).map_err(|erro| erro.to_string())?,
+            _ => return Err(format!("opção desconhecida: {arg}")),
+        }
+    }
+
+    let acervo = if pdns.is_empty() {
+        Acervo::abrir(&arquivo).map_err(|erro| format!("{arquivo}: {erro}"))?
+    } else {
+        let mut acervo = Acervo::default();
+        for caminho in &pdns {
+            let texto = fs::read_to_string(caminho).map_err(|erro| format!("{caminho}: {erro}"))?;
+            acervo.importar(&texto).map_err(|erro| format!("{caminho}: {erro}"))?;
+        }
+        acervo
+    };
+    if acervo.is_empty() {
+        return Err("não há partidas para explorar".to_string());
+    }
+    println!("{} partidas carregadas", acervo.len());
+
+    let mut partida = inicial.clone();
+    let mut linhas = io::stdin().lock().lines();
+    loop {
+        let estatisticas = explorar(&acervo, &partida);
+        mostrar(&partida, &estatisticas);
+        print!("> ");
+        io::stdout().flush().ok();
+        let Some(Ok(linha)) = linhas.next() else { break };
+        match linha.trim() {
+            "" => (),
+            "sair" => break,
+            "voltar" => {
+                if !partida.desfazer() {
+                    println!("Já está na posição inicial");
+                }
+            }
+            "inicio" => partida = inicial.clone(),
+            "partidas" => {
+                for achado in acervo.ocorrencias(&partida).iter().take(10) {
+                    let jogo = acervo.jogo(achado.jogo).unwrap();
+                    println!(
+                        "{:>6}  {} x {}  {}  {}",
+                        achado.jogo + 1,
+                        jogo.tag("White").unwrap_or("?"),
+                        jogo.tag("Black").unwrap_or("?"),
+                        jogo.tag("Date").unwrap_or("????.??.??"),
+                        jogo.resultado
+                    );
+                }
+            }
+            texto => {
+                let indice = match texto.parse::<usize>() {
+                    Ok(n) => estatisticas.get(n.wrapping_sub(1)).map(|estatistica| estatistica.indice).ok_or(format!("não há linha {n}")),
+                    Err(_) => partida.encontrar_jogada(texto).map_err(|erro| erro.to_string()),
+                };
+                match indice {
+                    Ok(indice) => {
+                        partida.jogar(indice);
+                    }
+                    Err(erro) => println!("{erro}"),
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,10 @@
+pub mod diagrama;
+pub mod animacao;
+pub mod cliente;
+pub mod dxp;
+pub mod ranking;
+pub mod acervo;
+pub mod explorar;
+pub mod puzzles;
+pub mod problema;
+pub mod anotar;
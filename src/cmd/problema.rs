@@ -0,0 +1,38 @@
+use damas::problemas::Solucionador;
+use damas::Partida;
+
+pub const USO: &str = "\
+Uso: damas_cmd problema <fen> [opções]
+  Prova que quem tem a vez ganha contra qualquer defesa, mostra a árvore da
+  solução e aponta cooks (outros lances iniciais que ganham) e duais.
+  --profundidade <n>      máximo de meios-lances até a vitória (padrão: 21)
+  --limite <n>            máximo de posições visitadas (padrão: 20000000)";
+
+pub fn executar(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let mut solucionador = Solucionador::default();
+    let mut fen = None;
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            fen = Some(arg);
+            continue;
+        }
+        let valor = args.next().ok_or(format!("{arg} precisa de um valor"))?;
+        let numero = || valor.parse::<u64>().map_err(|_| format!("{arg}: '{valor}' não é um número"));
+        match arg.as_str() {
+            "--profundidade" => solucionador = solucionador.com_profundidade(numero()? as usize),
+            "--limite" => solucionador.limite_nos = numero()?,
+            _ => return Err(format!("opção desconhecida: {arg}")),
+        }
+    }
+    let fen = fen.ok_or("informe o FEN do problema")?;
+    let partida = Partida::from_fen(&fen).map_err(|erro| erro.to_string())?;
+    print!("{partida}");
+
+    let solucao = solucionador.resolver(&partida).map_err(|erro| erro.to_string())?;
+    print!("{solucao}");
+    if solucao.correto() {
+        println!("Solução única");
+    }
+    println!("{} posições examinadas", solucao.nos);
+    Ok(())
+}
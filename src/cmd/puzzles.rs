@@ -0,0 +1,74 @@
+use damas::bot::Bot;
+use damas::exercicios::{self, Minerador};
+use damas::pdn;
+
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+
+pub const USO: &str = "\
+Uso: damas_cmd puzzles <arquivo.pdn>... [opções]
+  Procura nas partidas posições em que um único lance ganha material ou a partida
+  e acrescenta as novas ao arquivo de exercícios (FEN, solução, ganho, temas e origem).
+  --saida <arquivo>       arquivo de exercícios (padrão: exercicios.tsv)
+  --margem <n>            ganho mínimo da solução em pedras, com a dama valendo 3 (padrão: 2)
+  --profundidade <n>      profundidade do bot que confere as outras jogadas (padrão: 4)";
+
+const ARQUIVO_PADRAO: &str = "exercicios.tsv";
+
+pub fn executar(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let mut saida = ARQUIVO_PADRAO.to_string();
+    let mut minerador = Minerador::default();
+    let mut arquivos = vec![];
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            arquivos.push(arg);
+            continue;
+        }
+        let valor = args.next().ok_or(format!("{arg} precisa de um valor"))?;
+        let numero = || valor.parse::<u32>().map_err(|_| format!("{arg}: '{valor}' não é um número"));
+        match arg.as_str() {
+            "--saida" => saida = valor.clone(),
+            "--margem" => minerador = minerador.com_margem(numero()? as i32),
+            "--profundidade" => minerador = minerador.com_bot(Bot::new(numero()?)),
+            _ => return Err(format!("opção desconhecida: {arg}")),
+        }
+    }
+    if arquivos.is_empty() {
+        return Err("informe os arquivos PDN".to_string());
+    }
+
+    // Posições que já estão no arquivo não são gravadas de novo
+    let existentes = match fs::read_to_string(&saida) {
+        Ok(texto) => exercicios::ler(&texto).map_err(|(linha, erro)| format!("{saida}: linha {linha}: {erro}"))?,
+        Err(erro) if erro.kind() == ErrorKind::NotFound => vec![],
+        Err(erro) => return Err(format!("{saida}: {erro}")),
+    };
+    let mut vistas: HashSet<String> = existentes.into_iter().map(|exercicio| exercicio.fen).collect();
+
+    let mut arquivo = OpenOptions::new().create(true).append(true).open(&saida).map_err(|erro| format!("{saida}: {erro}"))?;
+    let mut novos = 0;
+    for caminho in &arquivos {
+        let texto = fs::read_to_string(caminho).map_err(|erro| format!("{caminho}: {erro}"))?;
+        let jogos = pdn::ler(&texto).map_err(|erro| format!("{caminho}: {erro}"))?;
+        for (i, jogo) in jogos.iter().enumerate() {
+            let encontrados = match minerador.minerar(jogo) {
+                Ok(encontrados) => encontrados,
+                Err(erro) => {
+                    eprintln!("{caminho}: partida {} ignorada: {erro}", i + 1);
+                    continue;
+                }
+            };
+            for exercicio in encontrados {
+                if !vistas.insert(exercicio.fen.clone()) {
+                    continue;
+                }
+                writeln!(arquivo, "{exercicio}").map_err(|erro| format!("{saida}: {erro}"))?;
+                println!("{}  {}  {}", exercicio.origem, exercicio.fen, exercicio.solucao.join(" "));
+                novos += 1;
+            }
+        }
+    }
+    println!("{novos} exercícios novos em {saida}");
+    Ok(())
+}
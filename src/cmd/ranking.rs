@@ -0,0 +1,123 @@
+use damas::pdn;
+use damas::ranking::{instante_da_data, Metodo, Ranking};
+
+use std::fs;
+
+pub const USO: &str = "\
+Uso: damas_cmd ranking [comando] [opções]
+  (sem comando)                             mostra a classificação
+  registrar <branco> <preto> <resultado>    registra uma partida: 2-0, 1-1 ou 0-2
+  importar <arquivo.pdn>                    registra as partidas terminadas de um PDN (tags White, Black e Date)
+  historico [<nome>]                        lista as partidas e a variação dos ratings
+  --arquivo <arquivo>   arquivo do ranking (padrão: ranking.tsv)
+  --metodo elo|glicko   rating mostrado e usado na classificação (padrão: elo)
+  --k <k>               fator K do Elo, guardado no arquivo (padrão: 32)";
+
+const ARQUIVO_PADRAO: &str = "ranking.tsv";
+
+pub fn executar(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let mut arquivo = ARQUIVO_PADRAO.to_string();
+    let mut metodo = Metodo::Elo;
+    let mut k = None;
+    let mut posicionais = vec![];
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            posicionais.push(arg);
+            continue;
+        }
+        let valor = args.next().ok_or(format!("{arg} precisa de um valor"))?;
+        match arg.as_str() {
+            "--arquivo" => arquivo = valor,
+            "--metodo" => {
+                metodo = match valor.as_str() {
+                    "elo" => Metodo::Elo,
+                    "glicko" => Metodo::Glicko2,
+                    _ => return Err(format!("{arg}: use 'elo' ou 'glicko'")),
+                }
+            }
+            "--k" => {
+                let numero = valor.parse::<f64>().ok().filter(|k| k.is_finite() && *k > 0.0);
+                k = Some(numero.ok_or(format!("{arg}: '{valor}' não é um número positivo"))?);
+            }
+            _ => return Err(format!("opção desconhecida: {arg}")),
+        }
+    }
+
+    let mut ranking = Ranking::abrir(&arquivo).map_err(|erro| format!("{arquivo}: {erro}"))?;
+    if let Some(k) = k {
+        ranking.k = k;
+        ranking.recalcular();
+        ranking.salvar(&arquivo).map_err(|erro| format!("{arquivo}: {erro}"))?;
+    }
+    let rating = |elo: f64, glicko: f64| match metodo {
+        Metodo::Elo => elo,
+        Metodo::Glicko2 => glicko,
+    };
+
+    let mut posicionais = posicionais.into_iter();
+    match posicionais.next().as_deref() {
+        None => {
+            println!("{:>3}  {:<24} {:>7} {:>6} {:>5} {:>3} {:>3} {:>3}", "#", "Nome", "Rating", "Desvio", "J", "V", "E", "D");
+            for (i, (nome, avaliacao)) in ranking.classificacao(metodo).into_iter().enumerate() {
+                let desvio = match metodo {
+                    Metodo::Elo => "-".to_string(),
+                    Metodo::Glicko2 => format!("{:.0}", avaliacao.glicko.desvio),
+                };
+                println!(
+                    "{:>3}  {:<24} {:>7.0} {:>6} {:>5} {:>3} {:>3} {:>3}",
+                    i + 1,
+                    nome,
+                    avaliacao.rating(metodo),
+                    desvio,
+                    avaliacao.partidas(),
+                    avaliacao.vitorias,
+                    avaliacao.empates,
+                    avaliacao.derrotas
+                );
+            }
+        }
+        Some("registrar") => {
+            let (Some(branco), Some(preto), Some(resultado)) = (posicionais.next(), posicionais.next(), posicionais.next()) else {
+                return Err("uso: registrar <branco> <preto> <resultado>".to_string());
+            };
+            let vencedor = pdn::vencedor(&resultado).ok_or(format!("resultado inválido: {resultado}"))?;
+            let registro = ranking.registrar(&branco, &preto, vencedor).map_err(|erro| erro.to_string())?;
+            let (a, b) = (rating(registro.variacao_elo.0, registro.variacao_glicko.0), rating(registro.variacao_elo.1, registro.variacao_glicko.1));
+            println!("{branco} {a:+.1}, {preto} {b:+.1}");
+            ranking.salvar(&arquivo).map_err(|erro| format!("{arquivo}: {erro}"))?;
+        }
+        Some("importar") => {
+            let caminho = posicionais.next().ok_or("uso: importar <arquivo.pdn>")?;
+            let texto = fs::read_to_string(&caminho).map_err(|erro| format!("{caminho}: {erro}"))?;
+            let jogos = pdn::ler(&texto).map_err(|erro| format!("{caminho}: {erro}"))?;
+            let mut importados = 0;
+            for jogo in &jogos {
+                let (Some(branco), Some(preto), Some(vencedor)) = (jogo.tag("White"), jogo.tag("Black"), pdn::vencedor(&jogo.resultado)) else {
+                    continue;
+                };
+                // Sem data completa, a partida conta como jogada agora
+                let registro = match jogo.tag("Date").and_then(instante_da_data) {
+                    Some(quando) => ranking.registrar_em(quando, branco, preto, vencedor),
+                    None => ranking.registrar(branco, preto, vencedor),
+                };
+                registro.map_err(|erro| erro.to_string())?;
+                importados += 1;
+            }
+            ranking.recalcular();
+            println!("{importados} de {} partidas importadas", jogos.len());
+            ranking.salvar(&arquivo).map_err(|erro| format!("{arquivo}: {erro}"))?;
+        }
+        Some("historico") => {
+            let nome = posicionais.next();
+            if let Some(nome) = &nome {
+                ranking.jogador(nome).ok_or(format!("jogador desconhecido: {nome}"))?;
+            }
+            for registro in ranking.historico(nome.as_deref()) {
+                let (a, b) = (rating(registro.variacao_elo.0, registro.variacao_glicko.0), rating(registro.variacao_elo.1, registro.variacao_glicko.1));
+                println!("{}  {} ({a:+.1}) {} {} ({b:+.1})", registro.data(), registro.branco, registro.resultado(), registro.preto);
+            }
+        }
+        Some(comando) => return Err(format!("comando desconhecido: {comando}")),
+    }
+    Ok(())
+}
@@ -0,0 +1,260 @@
+use crate::pdn::JogoPdn;
+use crate::sessao::{Jogador, Sessao};
+use crate::{Cor, Partida};
+
+// Confronto entre dois motores para medir se uma mudança ajuda: as partidas
+// alternam as cores sobre um conjunto de aberturas, e o placar dá a diferença
+// de Elo com margem de erro e a decisão do SPRT.
+
+/// Partidas mais longas que isto, em lances de cada lado somados, contam como empate.
+pub const LIMITE_DE_LANCES: usize = 300;
+
+/// Joga a partida a partir da abertura até o fim e devolve o vencedor, ou None para empate.
+pub fn jogar_partida(
+    abertura: &Partida,
+    branco: &mut dyn Jogador,
+    preto: &mut dyn Jogador,
+) -> Result<(Partida, Option<Cor>), String> {
+    let inicio = abertura.historico().len();
+    let mut sessao = Sessao::new(branco, preto).com_partida(abertura.clone());
+    while sessao.partida().historico().len() - inicio < LIMITE_DE_LANCES {
+        let estado = sessao.jogar_lance().map_err(|erro| erro.to_string())?;
+        if estado.encerrada() {
+            return Ok((sessao.into_partida(), estado.vencedor()));
+        }
+    }
+    Ok((sessao.into_partida(), None))
+}
+
+/// Todas as posições depois de `lances` lances a partir da posição inicial.
+pub fn aberturas(lances: usize) -> Vec<Partida> {
+    let mut posicoes = vec![Partida::default()];
+    for _ in 0..lances {
+        posicoes = posicoes
+            .iter()
+            .flat_map(|posicao| {
+                (0..posicao.todas_jogadas_possiveis().len()).map(move |indice| {
+                    let mut filho = posicao.clone();
+                    filho.jogar(indice);
+                    filho
+                })
+            })
+            .collect();
+    }
+    posicoes
+}
+
+/// Vitórias, empates e derrotas do primeiro motor.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Placar {
+    pub vitorias: u32,
+    pub empates: u32,
+    pub derrotas: u32,
+}
+
+fn pontuacao_esperada(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+fn elo_da_pontuacao(pontuacao: f64) -> f64 {
+    -400.0 * (1.0 / pontuacao - 1.0).log10()
+}
+
+impl Placar {
+    pub fn partidas(&self) -> u32 {
+        self.vitorias + self.empates + self.derrotas
+    }
+
+    /// Fração dos pontos possíveis, de 0 a 1.
+    pub fn pontuacao(&self) -> f64 {
+        (self.vitorias as f64 + self.empates as f64 / 2.0) / self.partidas() as f64
+    }
+
+    // Variância da pontuação de uma partida
+    fn variancia(&self) -> f64 {
+        let n = self.partidas() as f64;
+        let s = self.pontuacao();
+        (self.vitorias as f64 * (1.0 - s).powi(2) + self.empates as f64 * (0.5 - s).powi(2) + self.derrotas as f64 * s.powi(2)) / n
+    }
+
+    /// Diferença de Elo e a margem do intervalo de 95%. None antes da primeira
+    /// partida ou enquanto um lado tiver todos os pontos, quando o Elo é infinito.
+    pub fn elo(&self) -> Option<(f64, f64)> {
+        let s = self.pontuacao();
+        if self.partidas() == 0 || s <= 0.0 || s >= 1.0 {
+            return None;
+        }
+        let desvio = (self.variancia() / self.partidas() as f64).sqrt();
+        let limite = |s: f64| elo_da_pontuacao(s.clamp(1e-6, 1.0 - 1e-6));
+        let margem = (limite(s + 1.96 * desvio) - limite(s - 1.96 * desvio)) / 2.0;
+        Some((elo_da_pontuacao(s), margem))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DecisaoSprt {
+    /// A diferença é no máximo `elo0`.
+    H0,
+    /// A diferença é pelo menos `elo1`.
+    H1,
+}
+
+/// Teste sequencial da razão de verossimilhança entre as hipóteses elo0 e elo1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alfa: f64,
+    pub beta: f64,
+}
+
+impl Sprt {
+    pub fn new(elo0: f64, elo1: f64) -> Self {
+        Sprt { elo0, elo1, alfa: 0.05, beta: 0.05 }
+    }
+
+    /// Limites inferior e superior do LLR.
+    pub fn limites(&self) -> (f64, f64) {
+        ((self.beta / (1.0 - self.alfa)).ln(), ((1.0 - self.beta) / self.alfa).ln())
+    }
+
+    /// Log da razão de verossimilhança, pela aproximação normal do modelo trinomial.
+    pub fn llr(&self, placar: &Placar) -> f64 {
+        if placar.partidas() == 0 {
+            return 0.0;
+        }
+        let variancia = placar.variancia();
+        if variancia == 0.0 {
+            return 0.0;
+        }
+        let (s0, s1) = (pontuacao_esperada(self.elo0), pontuacao_esperada(self.elo1));
+        placar.partidas() as f64 * (s1 - s0) * (2.0 * placar.pontuacao() - s0 - s1) / (2.0 * variancia)
+    }
+
+    pub fn decidir(&self, placar: &Placar) -> Option<DecisaoSprt> {
+        let llr = self.llr(placar);
+        let (inferior, superior) = self.limites();
+        if llr >= superior {
+            Some(DecisaoSprt::H1)
+        } else if llr <= inferior {
+            Some(DecisaoSprt::H0)
+        } else {
+            None
+        }
+    }
+}
+
+/// Uma partida terminada do confronto.
+pub struct PartidaJogada {
+    /// Número da partida, a partir de 1.
+    pub rodada: usize,
+    pub partida: Partida,
+    /// Se o primeiro motor jogou com as brancas.
+    pub primeiro_de_brancas: bool,
+    pub vencedor: Option<Cor>,
+}
+
+impl PartidaJogada {
+    /// A partida em PDN com os nomes dos motores e o resultado.
+    pub fn pdn(&self, primeiro: &str, segundo: &str) -> JogoPdn {
+        let mut jogo = JogoPdn::de_partida(&self.partida);
+        let (branco, preto) = if self.primeiro_de_brancas { (primeiro, segundo) } else { (segundo, primeiro) };
+        jogo.set_tag("Event", "damas_match");
+        jogo.set_tag("Round", &self.rodada.to_string());
+        jogo.set_tag("White", branco);
+        jogo.set_tag("Black", preto);
+        // A partida cortada pelo limite de lances não terminou, mas conta como empate
+        if !self.partida.encerrada() {
+            jogo.set_resultado(self.vencedor);
+        }
+        jogo
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Confronto {
+    /// Cada abertura é jogada duas vezes, uma com cada motor de brancas.
+    pub aberturas: Vec<Partida>,
+    pub partidas: usize,
+    pub sprt: Option<Sprt>,
+}
+
+impl Default for Confronto {
+    fn default() -> Self {
+        Confronto { aberturas: aberturas(2), partidas: 100, sprt: None }
+    }
+}
+
+impl Confronto {
+    /// Joga até `partidas` partidas, ou até o SPRT decidir. `ao_terminar` recebe
+    /// cada partida com o placar atualizado.
+    pub fn jogar(
+        &self,
+        primeiro: &mut dyn Jogador,
+        segundo: &mut dyn Jogador,
+        mut ao_terminar: impl FnMut(&PartidaJogada, &Placar),
+    ) -> Result<Placar, String> {
+        let mut placar = Placar::default();
+        let inicial = [Partida::default()];
+        let aberturas = if self.aberturas.is_empty() { &inicial[..] } else { &self.aberturas[..] };
+        for i in 0..self.partidas {
+            let abertura = &aberturas[(i / 2) % aberturas.len()];
+            let primeiro_de_brancas = i % 2 == 0;
+            let (partida, vencedor) = if primeiro_de_brancas {
+                jogar_partida(abertura, primeiro, segundo)?
+            } else {
+                jogar_partida(abertura, segundo, primeiro)?
+            };
+            let cor_do_primeiro = if primeiro_de_brancas { Cor::Branco } else { Cor::Preto };
+            match vencedor {
+                None => placar.empates += 1,
+                Some(cor) if cor == cor_do_primeiro => placar.vitorias += 1,
+                Some(_) => placar.derrotas += 1,
+            }
+            ao_terminar(&PartidaJogada { rodada: i + 1, partida, primeiro_de_brancas, vencedor }, &placar);
+            if self.sprt.is_some_and(|sprt| sprt.decidir(&placar).is_some()) {
+                break;
+            }
+        }
+        Ok(placar)
+    }
+}
+
+#[test]
+fn testar_elo_e_sprt() {
+    let placar = Placar { vitorias: 60, empates: 20, derrotas: 20 };
+    let (elo, margem) = placar.elo().unwrap();
+    assert!((elo - 147.2).abs() < 0.1);
+    assert!(margem > 50.0 && margem < 100.0);
+    assert_eq!(Placar { vitorias: 3, empates: 0, derrotas: 0 }.elo(), None);
+
+    let sprt = Sprt::new(0.0, 10.0);
+    assert_eq!(sprt.decidir(&placar), None);
+    assert_eq!(sprt.decidir(&Placar { vitorias: 180, empates: 60, derrotas: 60 }), Some(DecisaoSprt::H1));
+    assert_eq!(sprt.decidir(&Placar { vitorias: 60, empates: 60, derrotas: 180 }), Some(DecisaoSprt::H0));
+    assert_eq!(sprt.decidir(&Placar { vitorias: 2, empates: 1, derrotas: 1 }), None);
+}
+
+#[test]
+fn testar_confronto() {
+    use crate::bot::Bot;
+    use crate::sessao::Roteiro;
+
+    let confronto = Confronto {
+        aberturas: vec![Partida::from_fen("W:W22,K30:B18,11,1").unwrap()],
+        partidas: 2,
+        sprt: None,
+    };
+    let mut rodadas = vec![];
+    let placar = confronto
+        .jogar(&mut Bot::new(4), &mut Bot::new(1), |jogada, _| rodadas.push((jogada.primeiro_de_brancas, jogada.vencedor)))
+        .unwrap();
+    assert_eq!(placar.partidas(), 2);
+    assert_eq!(rodadas.len(), 2);
+    assert!(rodadas[0].0 && !rodadas[1].0);
+    assert_eq!(aberturas(1).len(), 7);
+
+    // A falha de um jogador interrompe o confronto
+    let erro = jogar_partida(&Partida::default(), &mut Roteiro::new(["22-14"]), &mut Bot::new(1)).unwrap_err();
+    assert!(erro.contains("'22-14'"));
+}
@@ -0,0 +1,308 @@
+use std::fmt::Write;
+
+use crate::notacao::caminho;
+use crate::{Casa, Coord, ErroFen, Jogada, Partida, Pedra};
+
+// Diagramas do tabuleiro em SVG, para material impresso e páginas web.
+// Com a feature `png` o SVG também pode ser rasterizado.
+
+/// Se `texto` pode ir num atributo fill/stroke: "#rgb", "#rrggbb", "#rrggbbaa",
+/// um nome como "red" ou rgb()/rgba() com números.
+pub fn cor_valida(texto: &str) -> bool {
+    if let Some(hex) = texto.strip_prefix('#') {
+        return [3, 4, 6, 8].contains(&hex.len()) && hex.chars().all(|ch| ch.is_ascii_hexdigit());
+    }
+    if let Some(argumentos) = texto.strip_prefix("rgb(").or_else(|| texto.strip_prefix("rgba(")) {
+        return argumentos
+            .strip_suffix(')')
+            .is_some_and(|dentro| dentro.chars().all(|ch| ch.is_ascii_digit() || " ,.%".contains(ch)));
+    }
+    !texto.is_empty() && texto.chars().all(|ch| ch.is_ascii_alphabetic())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cores {
+    pub clara: String,
+    pub escura: String,
+    pub branca: String,
+    pub preta: String,
+    pub borda: String,
+    pub seta: String,
+    pub captura: String,
+    pub coroa: String,
+}
+
+impl Default for Cores {
+    fn default() -> Self {
+        Cores {
+            clara: "#f0d9b5".to_string(),
+            escura: "#b58863".to_string(),
+            branca: "#fafafa".to_string(),
+            preta: "#222222".to_string(),
+            borda: "#555555".to_string(),
+            seta: "#2e7d32".to_string(),
+            captura: "#c62828".to_string(),
+            coroa: "#d4a017".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagrama {
+    pub cores: Cores,
+    /// Largura do tabuleiro em pixels, sem as margens das coordenadas.
+    pub tamanho: u32,
+    pub coordenadas: bool,
+    pub virado: bool,
+    /// Sequências desenhadas como setas, salto por salto.
+    pub setas: Vec<Vec<Jogada>>,
+    /// Marca com um X as pedras capturadas pelas sequências em `setas`.
+    pub marcar_capturas: bool,
+}
+
+impl Default for Diagrama {
+    fn default() -> Self {
+        Diagrama {
+            cores: Cores::default(),
+            tamanho: 400,
+            coordenadas: true,
+            virado: false,
+            setas: vec![],
+            marcar_capturas: true,
+        }
+    }
+}
+
+impl Diagrama {
+    pub fn new() -> Self {
+        Diagrama::default()
+    }
+
+    fn casa(&self) -> f32 {
+        self.tamanho as f32 / 8.0
+    }
+
+    fn margem(&self) -> f32 {
+        if self.coordenadas { self.casa() / 2.0 } else { 0.0 }
+    }
+
+    /// Largura e altura da imagem inteira, incluindo as margens.
+    pub fn dimensoes(&self) -> (f32, f32) {
+        let lado = self.tamanho as f32 + 2.0 * self.margem();
+        (lado, lado)
+    }
+
+    // Canto superior esquerdo da casa na imagem
+    fn posicao(&self, casa: Coord) -> (f32, f32) {
+        let (x, y) = if self.virado { (7 - casa.x, 7 - casa.y) } else { (casa.x, casa.y) };
+        (self.margem() + x as f32 * self.casa(), self.margem() + y as f32 * self.casa())
+    }
+
+    pub(crate) fn centro(&self, casa: Coord) -> (f32, f32) {
+        let (x, y) = self.posicao(casa);
+        (x + self.casa() / 2.0, y + self.casa() / 2.0)
+    }
+
+    pub fn svg_fen(&self, fen: &str) -> Result<String, ErroFen> {
+        Partida::from_fen(fen).map(|partida| self.svg(&partida))
+    }
+
+    pub fn svg(&self, partida: &Partida) -> String {
+        let (largura, altura) = self.dimensoes();
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{largura}" height="{altura}" viewBox="0 0 {largura} {altura}">"#
+        )
+        .unwrap();
+        svg.push_str(&self.definicoes());
+        self.escrever_corpo(&mut svg, partida);
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Marcadores usados pelas setas; precisam estar em `<defs>` no documento.
+    pub(crate) fn definicoes(&self) -> String {
+        format!(
+            r#"<defs><marker id="ponta" viewBox="0 0 10 10" refX="5" refY="5" markerWidth="4" markerHeight="4" orient="auto-start-reverse"><path d="M 0 0 L 10 5 L 0 10 z" fill="{}"/></marker></defs>
+"#,
+            self.cores.seta
+        )
+    }
+
+    /// Tabuleiro, pedras, coordenadas e setas, sem o elemento `<svg>` em volta.
+    pub(crate) fn escrever_corpo(&self, svg: &mut String, partida: &Partida) {
+        self.escrever_tabuleiro(svg);
+        self.escrever_coordenadas(svg);
+        self.escrever_pedras(svg, partida.get_tabuleiro());
+        self.escrever_setas(svg);
+    }
+
+    pub(crate) fn escrever_tabuleiro(&self, svg: &mut String) {
+        let (largura, altura) = self.dimensoes();
+        let lado = self.casa();
+        writeln!(svg, r#"<rect width="{largura}" height="{altura}" fill="{}"/>"#, self.cores.clara).unwrap();
+        for y in 0..8 {
+            for x in 0..8 {
+                let casa = Coord { x, y };
+                let (px, py) = self.posicao(casa);
+                let cor = if casa.numero().is_some() { &self.cores.escura } else { &self.cores.clara };
+                writeln!(svg, r#"<rect x="{px}" y="{py}" width="{lado}" height="{lado}" fill="{cor}"/>"#).unwrap();
+            }
+        }
+        let m = self.margem();
+        let t = self.tamanho;
+        writeln!(svg, r#"<rect x="{m}" y="{m}" width="{t}" height="{t}" fill="none" stroke="{}" stroke-width="2"/>"#, self.cores.borda).unwrap();
+    }
+
+    pub(crate) fn escrever_coordenadas(&self, svg: &mut String) {
+        if !self.coordenadas {
+            return;
+        }
+        let fonte = self.margem() * 0.6;
+        let base = self.margem() + self.tamanho as f32 + self.margem() * 0.7;
+        for i in 0..8 {
+            let (cx, _) = self.centro(Coord { x: i, y: 0 });
+            let (_, cy) = self.centro(Coord { x: 0, y: i });
+            let letra = (b'a' + i as u8) as char;
+            writeln!(
+                svg,
+                r#"<text x="{cx}" y="{base}" font-size="{fonte}" font-family="sans-serif" text-anchor="middle" fill="{}">{letra}</text>"#,
+                self.cores.borda
+            )
+            .unwrap();
+            writeln!(
+                svg,
+                r#"<text x="{}" y="{}" font-size="{fonte}" font-family="sans-serif" text-anchor="middle" fill="{}">{}</text>"#,
+                self.margem() / 2.0,
+                cy + fonte / 3.0,
+                self.cores.borda,
+                8 - i
+            )
+            .unwrap();
+        }
+    }
+
+    pub(crate) fn escrever_pedras(&self, svg: &mut String, tabuleiro: &[[Casa; 8]; 8]) {
+        for (y, linha) in tabuleiro.iter().enumerate() {
+            for (x, casa) in linha.iter().enumerate() {
+                if let Casa::Ocupada(pedra) = casa {
+                    svg.push_str(&self.pedra(*pedra, Coord { x: x as i32, y: y as i32 }, ""));
+                }
+            }
+        }
+    }
+
+    /// Elemento `<g>` com a pedra desenhada na casa; `extra` vai dentro do grupo.
+    pub(crate) fn pedra(&self, pedra: Pedra, casa: Coord, extra: &str) -> String {
+        let (cx, cy) = self.centro(casa);
+        let raio = self.casa() * 0.38;
+        let (cor, contorno) = if pedra.é_branca() {
+            (&self.cores.branca, &self.cores.preta)
+        } else {
+            (&self.cores.preta, &self.cores.branca)
+        };
+        let mut g = format!(r#"<g transform="translate({cx} {cy})">"#);
+        write!(g, r#"<circle r="{raio}" fill="{cor}" stroke="{contorno}" stroke-width="{}"/>"#, raio * 0.08).unwrap();
+        write!(g, r#"<circle r="{}" fill="none" stroke="{contorno}" stroke-width="{}"/>"#, raio * 0.7, raio * 0.05).unwrap();
+        if pedra.é_dama() {
+            g.push_str(&self.coroa("", ""));
+        }
+        g.push_str(extra);
+        g.push_str("</g>\n");
+        g
+    }
+
+    /// Coroa simples desenhada no centro da dama, relativa ao centro da pedra.
+    pub(crate) fn coroa(&self, atributos: &str, filhos: &str) -> String {
+        let s = self.casa() * 0.38 * 0.45;
+        format!(
+            r#"<path d="M {} {} L {} {} L 0 {} L {} {} L {} {} z" fill="{}" {atributos}>{filhos}</path>"#,
+            -s, s * 0.5, -s, -s * 0.5, -s * 0.1, s, -s * 0.5, s, s * 0.5, self.cores.coroa
+        )
+    }
+
+    fn escrever_setas(&self, svg: &mut String) {
+        let largura = self.casa() * 0.1;
+        for sequencia in &self.setas {
+            let casas = caminho(sequencia);
+            for par in casas.windows(2) {
+                let (x1, y1) = self.centro(par[0]);
+                let (x2, y2) = self.centro(par[1]);
+                writeln!(
+                    svg,
+                    r#"<line x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}" stroke="{}" stroke-width="{largura}" stroke-linecap="round" marker-end="url(#ponta)" opacity="0.85"/>"#,
+                    self.cores.seta
+                )
+                .unwrap();
+            }
+            if !self.marcar_capturas {
+                continue;
+            }
+            for jogada in sequencia {
+                if let Jogada::Capturar(_, capturada, _) = jogada {
+                    svg.push_str(&self.marca_de_captura(*capturada));
+                }
+            }
+        }
+    }
+
+    pub(crate) fn marca_de_captura(&self, casa: Coord) -> String {
+        let (cx, cy) = self.centro(casa);
+        let s = self.casa() * 0.3;
+        format!(
+            r#"<path d="M {} {} L {} {} M {} {} L {} {}" stroke="{}" stroke-width="{}" stroke-linecap="round"/>
+"#,
+            cx - s, cy - s, cx + s, cy + s, cx - s, cy + s, cx + s, cy - s,
+            self.cores.captura,
+            self.casa() * 0.1
+        )
+    }
+
+    /// Rasteriza o diagrama como PNG.
+    #[cfg(feature = "png")]
+    pub fn png(&self, partida: &Partida) -> Result<Vec<u8>, String> {
+        rasterizar(&self.svg(partida))
+    }
+}
+
+/// Converte um documento SVG em PNG.
+#[cfg(feature = "png")]
+pub fn rasterizar(svg: &str) -> Result<Vec<u8>, String> {
+    pixmap(svg)?.encode_png().map_err(|erro| erro.to_string())
+}
+
+/// Converte um documento SVG em pixels RGBA, devolvendo largura, altura e os pixels.
+#[cfg(feature = "png")]
+pub(crate) fn rasterizar_rgba(svg: &str) -> Result<(u32, u32, Vec<u8>), String> {
+    let pixmap = pixmap(svg)?;
+    Ok((pixmap.width(), pixmap.height(), pixmap.take()))
+}
+
+#[cfg(feature = "png")]
+fn pixmap(svg: &str) -> Result<resvg::tiny_skia::Pixmap, String> {
+    use resvg::{tiny_skia, usvg};
+
+    let mut opcoes = usvg::Options::default();
+    opcoes.fontdb_mut().load_system_fonts();
+    let arvore = usvg::Tree::from_str(svg, &opcoes).map_err(|erro| erro.to_string())?;
+    let tamanho = arvore.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(tamanho.width(), tamanho.height()).ok_or("imagem vazia")?;
+    resvg::render(&arvore, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    Ok(pixmap)
+}
+
+#[test]
+fn testar_svg() {
+    let partida = Partida::from_fen("W:W22,K30:B18,11").unwrap();
+    let mut diagrama = Diagrama::new();
+    diagrama.setas = vec![partida.todas_jogadas_possiveis()[0].clone()];
+    let svg = diagrama.svg(&partida);
+    assert!(svg.starts_with("<svg"));
+    assert_eq!(svg.matches("<circle").count(), 8);
+    // Dois saltos, duas setas e duas marcas de captura
+    assert_eq!(svg.matches("<line").count(), 2);
+    assert_eq!(svg.matches(&format!("stroke=\"{}\"", diagrama.cores.captura)).count(), 2);
+    assert!(cor_valida("#c62828") && cor_valida("navy") && cor_valida("rgb(10, 20, 30)"));
+    assert!(!cor_valida("red\" onload=\"x") && !cor_valida("#12345") && !cor_valida("rgb(1)<"));
+}
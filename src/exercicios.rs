@@ -0,0 +1,275 @@
+use crate::bot::{self, Bot};
+use crate::golpes::{BuscaDeGolpes, Golpe};
+use crate::pdn::{ErroPdn, JogoPdn};
+use crate::{Cor, ErroFen, Estado, Partida};
+
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+// Exercícios tirados de partidas de verdade: posições em que um único lance
+// ganha material ou a partida por uma margem clara. O lance vem da busca de
+// golpes; as outras jogadas são conferidas pelo bot, e basta uma delas também
+// ganhar pelo menos meia pedra para a posição ser descartada.
+
+// Quanto uma alternativa pode ganhar, na escala do bot, e ainda contar como falha
+const TOLERANCIA: i32 = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Tema {
+    /// A solução entrega pedras para obrigar o adversário a tomar.
+    Sacrificio,
+    /// Uma tomada de três ou mais pedras de uma vez.
+    TomadaMultipla,
+    /// Uma pedra vira dama durante a solução.
+    Coroacao,
+    /// A solução termina a partida.
+    Vitoria,
+}
+
+impl Display for Tema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Tema::Sacrificio => "sacrificio",
+            Tema::TomadaMultipla => "tomada-multipla",
+            Tema::Coroacao => "coroacao",
+            Tema::Vitoria => "vitoria",
+        })
+    }
+}
+
+impl FromStr for Tema {
+    type Err = ErroExercicio;
+
+    fn from_str(texto: &str) -> Result<Tema, ErroExercicio> {
+        match texto {
+            "sacrificio" => Ok(Tema::Sacrificio),
+            "tomada-multipla" => Ok(Tema::TomadaMultipla),
+            "coroacao" => Ok(Tema::Coroacao),
+            "vitoria" => Ok(Tema::Vitoria),
+            _ => Err(ErroExercicio::Tema(texto.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErroExercicio {
+    /// A linha não tem os campos FEN, solução, ganho, temas e origem.
+    Campos,
+    Fen(ErroFen),
+    Ganho(String),
+    Tema(String),
+}
+
+impl Display for ErroExercicio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErroExercicio::Campos => write!(f, "use FEN, solução, ganho, temas e origem separados por tabulação"),
+            ErroExercicio::Fen(erro) => write!(f, "FEN inválido: {erro}"),
+            ErroExercicio::Ganho(ganho) => write!(f, "'{ganho}' não é um ganho válido"),
+            ErroExercicio::Tema(tema) => write!(f, "tema desconhecido: {tema}"),
+        }
+    }
+}
+
+impl std::error::Error for ErroExercicio {}
+
+/// Uma posição com solução única. Uma linha do arquivo de exercícios.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exercicio {
+    pub fen: String,
+    /// Lances da solução em notação, com as respostas forçadas do adversário.
+    pub solucao: Vec<String>,
+    /// Material ganho em pedras, com a dama valendo três.
+    pub ganho: i32,
+    pub temas: Vec<Tema>,
+    /// De onde a posição saiu, por exemplo "Ana x Bia, lance 14".
+    pub origem: String,
+}
+
+impl Display for Exercicio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let temas: Vec<String> = self.temas.iter().map(Tema::to_string).collect();
+        write!(f, "{}\t{}\t{}\t{}\t{}", self.fen, self.solucao.join(" "), self.ganho, temas.join(","), self.origem)
+    }
+}
+
+impl FromStr for Exercicio {
+    type Err = ErroExercicio;
+
+    fn from_str(linha: &str) -> Result<Exercicio, ErroExercicio> {
+        let campos: Vec<&str> = linha.split('\t').collect();
+        let [fen, solucao, ganho, temas, origem] = campos[..] else { return Err(ErroExercicio::Campos) };
+        Partida::from_fen(fen).map_err(ErroExercicio::Fen)?;
+        Ok(Exercicio {
+            fen: fen.to_string(),
+            solucao: solucao.split_whitespace().map(str::to_string).collect(),
+            ganho: ganho.parse().map_err(|_| ErroExercicio::Ganho(ganho.to_string()))?,
+            temas: temas.split(',').filter(|tema| !tema.is_empty()).map(str::parse).collect::<Result<_, _>>()?,
+            origem: origem.to_string(),
+        })
+    }
+}
+
+impl Exercicio {
+    pub fn partida(&self) -> Partida {
+        Partida::from_fen(&self.fen).unwrap()
+    }
+}
+
+/// Lê um arquivo de exercícios, um por linha. Linhas com # são comentários.
+pub fn ler(texto: &str) -> Result<Vec<Exercicio>, (usize, ErroExercicio)> {
+    texto
+        .lines()
+        .enumerate()
+        .filter(|(_, linha)| !linha.trim().is_empty() && !linha.starts_with('#'))
+        .map(|(i, linha)| linha.parse().map_err(|erro| (i + 1, erro)))
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Minerador {
+    pub golpes: BuscaDeGolpes,
+    /// Ganho mínimo da solução, em pedras.
+    pub margem: i32,
+    /// Bot que confere se as outras jogadas falham.
+    pub bot: Bot,
+}
+
+impl Default for Minerador {
+    fn default() -> Self {
+        Minerador { golpes: BuscaDeGolpes::default(), margem: 2, bot: Bot::new(4) }
+    }
+}
+
+impl Minerador {
+    pub fn com_margem(mut self, margem: i32) -> Self {
+        self.margem = margem;
+        self
+    }
+
+    pub fn com_bot(mut self, bot: Bot) -> Self {
+        self.bot = bot;
+        self
+    }
+
+    /// O exercício da posição, se um único lance ganha pela margem e todos os
+    /// outros falham. `origem` vai para o exercício como está.
+    pub fn examinar(&self, partida: &Partida, origem: &str) -> Option<Exercicio> {
+        let total = partida.todas_jogadas_possiveis().len();
+        if total < 2 {
+            return None;
+        }
+        let busca = BuscaDeGolpes { ganho_minimo: self.margem, ..self.golpes };
+        let golpes = busca.procurar(partida);
+        let [golpe] = &golpes[..] else { return None };
+
+        let atacante = *partida.get_vez();
+        let base = bot::avaliar(partida);
+        for indice in (0..total).filter(|&indice| indice != golpe.indice) {
+            let mut filho = partida.clone();
+            filho.jogar(indice);
+            // A partida terminada não tem jogadas para o bot analisar
+            let avaliacao = match filho.estado() {
+                Estado::EmAndamento => self.bot.analisar(&filho).map_or(0, |analise| -analise.avaliacao),
+                estado => match estado.vencedor() {
+                    Some(vencedor) if vencedor == atacante => bot::VITORIA,
+                    Some(_) => -bot::VITORIA,
+                    None => 0,
+                },
+            };
+            if avaliacao - base >= TOLERANCIA {
+                return None;
+            }
+        }
+        Some(Exercicio {
+            fen: partida.fen(),
+            solucao: golpe.lances.clone(),
+            ganho: golpe.ganho,
+            temas: temas(partida, golpe),
+            origem: origem.to_string(),
+        })
+    }
+
+    /// Reproduz o jogo e examina a posição antes de cada lance. As posições no
+    /// meio da solução de um exercício já encontrado são puladas.
+    pub fn minerar(&self, jogo: &JogoPdn) -> Result<Vec<Exercicio>, ErroPdn> {
+        let nomes = format!("{} x {}", jogo.tag("White").unwrap_or("?"), jogo.tag("Black").unwrap_or("?"));
+        let mut partida = jogo.posicao_inicial()?;
+        let mut exercicios = vec![];
+        let mut pular_ate = 0;
+        for (i, lance) in jogo.lances.iter().enumerate() {
+            if i >= pular_ate {
+                if let Some(exercicio) = self.examinar(&partida, &format!("{nomes}, lance {}", i + 1)) {
+                    pular_ate = i + exercicio.solucao.len();
+                    exercicios.push(exercicio);
+                }
+            }
+            let indice = partida.encontrar_jogada(lance).map_err(|erro| ErroPdn::Jogada { lance: i + 1, erro })?;
+            partida.jogar(indice);
+        }
+        Ok(exercicios)
+    }
+}
+
+fn temas(partida: &Partida, golpe: &Golpe) -> Vec<Tema> {
+    let atacante = *partida.get_vez();
+    let damas = |partida: &Partida| {
+        let material = partida.material();
+        match atacante {
+            Cor::Branco => material.damas_brancas,
+            Cor::Preto => material.damas_pretas,
+        }
+    };
+    let mut temas = vec![];
+    if golpe.sacrificios > 0 {
+        temas.push(Tema::Sacrificio);
+    }
+    let mut posicao = partida.clone();
+    for lance in &golpe.lances {
+        let nosso = *posicao.get_vez() == atacante;
+        if nosso && lance.matches('x').count() >= 3 {
+            temas.push(Tema::TomadaMultipla);
+        }
+        let antes = damas(&posicao);
+        let Ok(indice) = posicao.encontrar_jogada(lance) else { break };
+        posicao.jogar(indice);
+        if nosso && damas(&posicao) > antes {
+            temas.push(Tema::Coroacao);
+        }
+    }
+    if golpe.vitoria {
+        temas.push(Tema::Vitoria);
+    }
+    temas.sort();
+    temas.dedup();
+    temas
+}
+
+#[test]
+fn testar_exercicios() {
+    let pdn = r#"[White "Ana"] [Black "Bia"] [FEN "W:W14,23,25,27,28,29,30,31,32:B3,4,5,6,8,11,12,13,16"]
+1. 14-9 5x14 2. 23-19 16x23 3. 27x18x9x2 *"#;
+    let jogo = &crate::pdn::ler(pdn).unwrap()[0];
+    let exercicios = Minerador::default().minerar(jogo).unwrap();
+    assert_eq!(exercicios.len(), 1);
+    let exercicio = &exercicios[0];
+    assert_eq!(exercicio.fen, jogo.tag("FEN").unwrap());
+    assert_eq!(exercicio.solucao.join(" "), "14-9 5x14 23-19 16x23 27x18x9x2");
+    assert_eq!(exercicio.ganho, 3);
+    assert_eq!(exercicio.temas, [Tema::Sacrificio, Tema::TomadaMultipla, Tema::Coroacao]);
+    assert_eq!(exercicio.origem, "Ana x Bia, lance 1");
+
+    let linha = exercicio.to_string();
+    assert_eq!(ler(&format!("# exercícios\n{linha}\n")).unwrap(), exercicios);
+    assert_eq!(ler("W:W1:B5\t1-2"), Err((1, ErroExercicio::Campos)));
+
+    // Com o contador de empate no limite, os lances da dama só empatam e não refutam o golpe
+    let mut partida = Partida::from_fen("W:W14,23,25,27,28,29,30,31,32,K20:B3,4,5,6,8,11,12,13,16").unwrap();
+    partida.com_contador_inicial(19.5);
+    let exercicio = Minerador::default().examinar(&partida, "").unwrap();
+    assert_eq!(exercicio.solucao.join(" "), "14-9 5x14 23-19 16x23 27x18x9x2");
+
+    // Na posição inicial nada ganha material
+    assert_eq!(Minerador::default().examinar(&Partida::default(), ""), None);
+}
+
@@ -0,0 +1,112 @@
+use crate::acervo::Acervo;
+use crate::notacao::notacao;
+use crate::pdn;
+use crate::{Cor, Partida};
+
+// Explorador de aberturas: para uma posição, quantas vezes cada jogada possível
+// foi feita nas partidas do acervo e como essas partidas terminaram. Como a
+// busca é pela chave da posição, transposições entram na conta.
+
+/// Números de uma jogada a partir da posição explorada.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Estatistica {
+    /// Índice em `todas_jogadas_possiveis`.
+    pub indice: usize,
+    pub lance: String,
+    pub partidas: u32,
+    pub brancas: u32,
+    pub empates: u32,
+    pub pretas: u32,
+    /// Média dos ratings das tags WhiteElo e BlackElo, quando houver.
+    pub rating_medio: Option<f64>,
+}
+
+impl Estatistica {
+    /// Percentuais de vitória das brancas, de empate e de vitória das pretas
+    /// entre as partidas com resultado conhecido.
+    pub fn percentuais(&self) -> (f64, f64, f64) {
+        let total = (self.brancas + self.empates + self.pretas) as f64;
+        if total == 0.0 {
+            return (0.0, 0.0, 0.0);
+        }
+        let percentual = |n: u32| 100.0 * n as f64 / total;
+        (percentual(self.brancas), percentual(self.empates), percentual(self.pretas))
+    }
+}
+
+/// Todas as jogadas possíveis na posição, das mais jogadas para as menos;
+/// as que nunca foram jogadas vêm no fim com zero partidas.
+pub fn explorar(acervo: &Acervo, partida: &Partida) -> Vec<Estatistica> {
+    let mut estatisticas: Vec<Estatistica> = partida
+        .todas_jogadas_possiveis()
+        .iter()
+        .enumerate()
+        .map(|(indice, sequencia)| Estatistica {
+            indice,
+            lance: notacao(sequencia),
+            partidas: 0,
+            brancas: 0,
+            empates: 0,
+            pretas: 0,
+            rating_medio: None,
+        })
+        .collect();
+    let mut ratings = vec![(0.0, 0); estatisticas.len()];
+
+    for achado in acervo.ocorrencias(partida) {
+        let jogo = acervo.jogo(achado.jogo).unwrap();
+        let Some(estatistica) = jogo.jogada(achado.lance).and_then(|indice| estatisticas.get_mut(indice)) else {
+            continue;
+        };
+        estatistica.partidas += 1;
+        match pdn::vencedor(&jogo.resultado) {
+            Some(Some(Cor::Branco)) => estatistica.brancas += 1,
+            Some(None) => estatistica.empates += 1,
+            Some(Some(Cor::Preto)) => estatistica.pretas += 1,
+            None => (),
+        }
+        let (soma, quantos) = &mut ratings[estatistica.indice];
+        for tag in ["WhiteElo", "BlackElo"] {
+            if let Some(rating) = jogo.tag(tag).and_then(|rating| rating.trim().parse::<f64>().ok()) {
+                *soma += rating;
+                *quantos += 1;
+            }
+        }
+    }
+    for (estatistica, (soma, quantos)) in estatisticas.iter_mut().zip(ratings) {
+        estatistica.rating_medio = (quantos > 0).then(|| soma / quantos as f64);
+    }
+    estatisticas.sort_by(|a, b| b.partidas.cmp(&a.partidas).then(a.indice.cmp(&b.indice)));
+    estatisticas
+}
+
+#[test]
+fn testar_explorador() {
+    let pdn = r#"
+[White "Ana"] [Black "Bia"] [WhiteElo "1600"] [BlackElo "1400"]
+1. 22-18 12-16 2-0
+[White "Bia"] [Black "Caio"] [WhiteElo "1500"]
+1. 22-18 11-15 2. 18x11 8x15 1-1
+[White "Caio"] [Black "Ana"]
+1. 21-17 12-16 2. 22-18 16-20 0-2
+"#;
+    let mut acervo = Acervo::default();
+    acervo.importar(pdn).unwrap();
+    let inicio = explorar(&acervo, &Partida::default());
+    assert_eq!(inicio.len(), 7);
+    assert_eq!((inicio[0].lance.as_str(), inicio[0].partidas, inicio[0].brancas, inicio[0].empates), ("22-18", 2, 1, 1));
+    assert_eq!(inicio[0].rating_medio, Some(1500.0));
+    assert_eq!(inicio[0].percentuais(), (50.0, 50.0, 0.0));
+    assert_eq!((inicio[1].lance.as_str(), inicio[1].partidas, inicio[1].rating_medio), ("21-17", 1, None));
+    assert_eq!(inicio[2].partidas, 0);
+
+    // O terceiro jogo chega a esta posição por transposição
+    let mut partida = Partida::default();
+    for lance in ["22-18", "12-16", "21-17"] {
+        partida.jogar(partida.encontrar_jogada(lance).unwrap());
+    }
+    let depois = explorar(&acervo, &partida);
+    assert_eq!(depois[0].partidas, 1);
+    assert_eq!(depois[0].lance, "16-20");
+    assert_eq!(depois[0].pretas, 1);
+}
@@ -0,0 +1,175 @@
+use crate::notacao::notacao;
+use crate::{Cor, Jogada, Partida, Resultado};
+
+// Procura de golpes: sequências em que quem tem a vez entrega pedras para
+// obrigar o adversário a tomar (a captura é obrigatória e pela maioria) e
+// depois toma mais do que entregou. Diferente da busca do bot, só entram
+// lances forçantes: do nosso lado capturas ou entregas que deixam o adversário
+// com captura obrigatória, do lado dele apenas as capturas que a regra impõe.
+// A linha acaba quando o adversário fica livre para jogar.
+
+/// Valor de uma dama em pedras no saldo de material.
+pub const VALOR_DAMA: i32 = 3;
+
+// Saldo de quem vence a partida dentro da linha
+const VITORIA: i32 = 1000;
+
+/// Um golpe encontrado a partir da posição.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Golpe {
+    /// Índice do primeiro lance em `todas_jogadas_possiveis`.
+    pub indice: usize,
+    /// Linha principal em notação, começando pelo primeiro lance.
+    pub lances: Vec<String>,
+    /// Material ganho em pedras, com a dama valendo `VALOR_DAMA`.
+    pub ganho: i32,
+    /// Se a linha termina com o adversário sem pedras ou sem jogadas.
+    pub vitoria: bool,
+    /// Quantas vezes a linha entrega pedras sem capturar.
+    pub sacrificios: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BuscaDeGolpes {
+    /// Máximo de lances (meios-lances) de uma linha.
+    pub profundidade: usize,
+    /// Ganho mínimo, em pedras, para a linha contar como golpe.
+    pub ganho_minimo: i32,
+}
+
+impl Default for BuscaDeGolpes {
+    fn default() -> Self {
+        BuscaDeGolpes { profundidade: 24, ganho_minimo: 1 }
+    }
+}
+
+fn é_captura(sequencia: &[Jogada]) -> bool {
+    matches!(sequencia.first(), Some(Jogada::Capturar(..)))
+}
+
+fn deve_capturar(partida: &Partida) -> bool {
+    partida.todas_jogadas_possiveis().first().is_some_and(|sequencia| é_captura(sequencia))
+}
+
+/// Material de `jogador` menos o do adversário.
+fn saldo(partida: &Partida, jogador: Cor) -> i32 {
+    let material = partida.material();
+    let brancas = material.pedras_brancas as i32 + VALOR_DAMA * material.damas_brancas as i32;
+    let pretas = material.pedras_pretas as i32 + VALOR_DAMA * material.damas_pretas as i32;
+    match jogador {
+        Cor::Branco => brancas - pretas,
+        Cor::Preto => pretas - brancas,
+    }
+}
+
+impl BuscaDeGolpes {
+    /// Golpes de quem tem a vez, um por primeiro lance, do maior ganho para o menor.
+    pub fn procurar(&self, partida: &Partida) -> Vec<Golpe> {
+        let atacante = *partida.get_vez();
+        let base = saldo(partida, atacante);
+        let capturas = deve_capturar(partida);
+        let mut golpes = vec![];
+        for indice in 0..partida.todas_jogadas_possiveis().len() {
+            let mut filho = partida.clone();
+            let resultado = filho.jogar(indice);
+            let mut linha = vec![indice];
+            let valor = match resultado {
+                Resultado::FimDoJogo(Some(vencedor)) if vencedor == atacante => VITORIA,
+                Resultado::FimDoJogo(_) => continue,
+                _ if !capturas && !deve_capturar(&filho) => continue,
+                _ => self.no(&filho, atacante, base, 1, &mut linha),
+            };
+            if valor < self.ganho_minimo {
+                continue;
+            }
+            golpes.push(self.golpe(partida, &linha, valor));
+        }
+        golpes.sort_by(|a, b| b.vitoria.cmp(&a.vitoria).then(b.ganho.cmp(&a.ganho)).then(a.indice.cmp(&b.indice)));
+        golpes
+    }
+
+    /// O melhor golpe de quem tem a vez, se houver.
+    pub fn melhor(&self, partida: &Partida) -> Option<Golpe> {
+        self.procurar(partida).into_iter().next()
+    }
+
+    // Valor da posição para o atacante, relativo ao saldo da raiz. `linha`
+    // recebe os índices da continuação escolhida.
+    fn no(&self, partida: &Partida, atacante: Cor, base: i32, ply: usize, linha: &mut Vec<usize>) -> i32 {
+        let vez = *partida.get_vez();
+        let jogadas = partida.todas_jogadas_possiveis();
+        if jogadas.is_empty() {
+            return if vez == atacante { -VITORIA } else { VITORIA };
+        }
+        let parado = saldo(partida, atacante) - base;
+        let capturas = é_captura(&jogadas[0]);
+        if ply >= self.profundidade || (vez != atacante && !capturas) {
+            return parado;
+        }
+
+        let nosso = vez == atacante;
+        // Sem captura obrigatória, podemos encerrar a linha com um lance tranquilo
+        let mut melhor = match (nosso, capturas) {
+            (true, false) => Some((parado, vec![])),
+            _ => None,
+        };
+        for indice in 0..jogadas.len() {
+            let mut filho = partida.clone();
+            let mut continuacao = vec![indice];
+            let valor = match filho.jogar(indice) {
+                Resultado::FimDoJogo(Some(vencedor)) => {
+                    if vencedor == atacante { VITORIA } else { -VITORIA }
+                }
+                Resultado::FimDoJogo(None) => 0,
+                _ if nosso && !capturas && !deve_capturar(&filho) => continue,
+                _ => self.no(&filho, atacante, base, ply + 1, &mut continuacao),
+            };
+            let melhora = match &melhor {
+                None => true,
+                Some((atual, _)) if nosso => valor > *atual,
+                Some((atual, _)) => valor < *atual,
+            };
+            if melhora {
+                melhor = Some((valor, continuacao));
+            }
+        }
+        let (valor, continuacao) = melhor.unwrap();
+        linha.extend(continuacao);
+        valor
+    }
+
+    fn golpe(&self, partida: &Partida, linha: &[usize], valor: i32) -> Golpe {
+        let atacante = *partida.get_vez();
+        let mut posicao = partida.clone();
+        let mut lances = vec![];
+        let mut sacrificios = 0;
+        for &indice in linha {
+            let sequencia = &posicao.todas_jogadas_possiveis()[indice];
+            if *posicao.get_vez() == atacante && !é_captura(sequencia) {
+                sacrificios += 1;
+            }
+            lances.push(notacao(sequencia));
+            posicao.jogar(indice);
+        }
+        // Numa vitória o valor da busca não diz o material; ele sai do fim da linha
+        let vitoria = valor >= VITORIA;
+        let ganho = if vitoria { saldo(&posicao, atacante) - saldo(partida, atacante) } else { valor };
+        Golpe { indice: linha[0], lances, ganho, vitoria, sacrificios }
+    }
+}
+
+#[test]
+fn testar_golpes() {
+    let busca = BuscaDeGolpes::default();
+    assert!(busca.procurar(&Partida::default()).is_empty());
+
+    // Duas entregas seguidas de uma tomada tripla que termina em dama
+    let partida = Partida::from_fen("W:W14,23,25,27,28,29,30,31,32:B3,4,5,6,8,11,12,13,16").unwrap();
+    let golpe = busca.melhor(&partida).unwrap();
+    assert_eq!(golpe.lances, ["14-9", "5x14", "23-19", "16x23", "27x18x9x2"]);
+    assert_eq!((golpe.ganho, golpe.sacrificios, golpe.vitoria), (3, 2, false));
+    assert_eq!(notacao(&partida.todas_jogadas_possiveis()[golpe.indice]), "14-9");
+
+    // Pedindo mais do que o golpe ganha, nada é encontrado
+    assert!(BuscaDeGolpes { ganho_minimo: 4, ..busca }.procurar(&partida).is_empty());
+}
@@ -0,0 +1,579 @@
+use std::fmt::Display;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::bot::{Analise, Bot};
+use crate::notacao::notacao;
+use crate::sessao::Jogador;
+use crate::{Casa, Coord, Cor, Jogada, Partida, Pedra, PosicaoBuilder};
+
+// Protocolo Hub (o do Scan), para interfaces gráficas e o `damas_match` usarem
+// a busca deste crate como motor. Cada linha é um comando seguido de campos
+// nome=valor; valores com espaços vão entre aspas.
+//
+// Interface -> motor:
+//   hub                        apresentação; o motor responde id e wait
+//   init                       o motor responde ready
+//   new-game                   volta à posição inicial
+//   pos [start] [pos=<posição>] [fen=<fen>] [moves="<lances>"]
+//   level [depth=n] [move-time=s] [time=s] [inc=s] [moves=n] [infinite]
+//   time left=s                tempo restante do motor
+//   go think|ponder|analyze
+//   ponder-hit                 o lance esperado foi jogado; o tempo passa a contar
+//   stop                       encerra a busca, que responde com done
+//   ping                       o motor responde pong
+//   quit
+//
+// Motor -> interface:
+//   info depth=n score=x nodes=n time=s nps=n pv="<lances>"
+//   done move=<lance> [ponder=<lance>]
+//   error message=<texto>
+//
+// A posição do Hub é a vez (W ou B) seguida das 32 casas com w/b (pedras), W/B
+// (damas) e e (vazia). Os lances usam a notação numérica do crate, com todas as
+// casas de pouso das capturas (22x15x8).
+
+/// Profundidade usada quando só o tempo, ou nada, limita a busca.
+const PROFUNDIDADE_MAXIMA: u32 = 64;
+/// Lances que o motor supõe que faltam quando o controle de tempo não diz.
+const LANCES_ESPERADOS: u32 = 30;
+
+/// Uma linha do protocolo: o comando e os campos, na ordem. Campos sem `=` têm valor vazio.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinhaHub {
+    pub comando: String,
+    pub campos: Vec<(String, String)>,
+}
+
+impl LinhaHub {
+    pub fn new(comando: &str) -> Self {
+        LinhaHub { comando: comando.to_string(), campos: vec![] }
+    }
+
+    pub fn campo(mut self, nome: &str, valor: impl Display) -> Self {
+        self.campos.push((nome.to_string(), valor.to_string()));
+        self
+    }
+
+    pub fn ler(linha: &str) -> LinhaHub {
+        let mut palavras = vec![];
+        let mut atual = String::new();
+        let mut entre_aspas = false;
+        for letra in linha.trim().chars() {
+            match letra {
+                '"' => entre_aspas = !entre_aspas,
+                c if c.is_whitespace() && !entre_aspas => {
+                    if !atual.is_empty() {
+                        palavras.push(std::mem::take(&mut atual));
+                    }
+                }
+                c => atual.push(c),
+            }
+        }
+        if !atual.is_empty() {
+            palavras.push(atual);
+        }
+        let mut palavras = palavras.into_iter();
+        let comando = palavras.next().unwrap_or_default();
+        let campos = palavras
+            .map(|palavra| match palavra.split_once('=') {
+                Some((nome, valor)) => (nome.to_string(), valor.to_string()),
+                None => (palavra, String::new()),
+            })
+            .collect();
+        LinhaHub { comando, campos }
+    }
+
+    pub fn valor(&self, nome: &str) -> Option<&str> {
+        self.campos.iter().find(|(campo, _)| campo == nome).map(|(_, valor)| valor.as_str())
+    }
+
+    pub fn tem(&self, nome: &str) -> bool {
+        self.valor(nome).is_some()
+    }
+}
+
+impl Display for LinhaHub {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.comando)?;
+        for (nome, valor) in &self.campos {
+            if valor.is_empty() {
+                write!(f, " {nome}")?;
+            } else if valor.contains(char::is_whitespace) {
+                write!(f, " {nome}=\"{valor}\"")?;
+            } else {
+                write!(f, " {nome}={valor}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Posição no formato do Hub.
+pub fn posicao(partida: &Partida) -> String {
+    let mut texto = String::from(if partida.é_a_vez_do_branco() { "W" } else { "B" });
+    for numero in 1..=32 {
+        texto.push(match partida.casa(Coord::de_numero(numero).expect("32 casas")) {
+            Casa::Vazia => 'e',
+            Casa::Ocupada(Pedra::Branca) => 'w',
+            Casa::Ocupada(Pedra::Preta) => 'b',
+            Casa::Ocupada(Pedra::DamaBranca) => 'W',
+            Casa::Ocupada(Pedra::DamaPreta) => 'B',
+        });
+    }
+    texto
+}
+
+pub fn ler_posicao(texto: &str) -> Result<Partida, String> {
+    let mut letras = texto.chars();
+    let vez = match letras.next() {
+        Some('W') => Cor::Branco,
+        Some('B') => Cor::Preto,
+        _ => return Err(format!("vez desconhecida na posição {texto}")),
+    };
+    let casas: Vec<char> = letras.collect();
+    if casas.len() != 32 {
+        return Err(format!("a posição deve ter 32 casas, tem {}", casas.len()));
+    }
+    let mut builder = PosicaoBuilder::new().vez(vez);
+    for (numero, letra) in (1..=32).zip(casas) {
+        let pedra = match letra {
+            'e' => continue,
+            'w' => Pedra::Branca,
+            'b' => Pedra::Preta,
+            'W' => Pedra::DamaBranca,
+            'B' => Pedra::DamaPreta,
+            outra => return Err(format!("casa desconhecida na posição: {outra}")),
+        };
+        builder = builder.pedra(numero, pedra);
+    }
+    builder.construir().map_err(|erro| format!("posição inválida: {erro}"))
+}
+
+#[derive(Debug, Clone, Default)]
+struct Nivel {
+    profundidade: Option<u32>,
+    tempo_por_lance: Option<Duration>,
+    /// Tempo de cada jogador no controle, usado até chegar um `time left`.
+    tempo: Option<Duration>,
+    incremento: Duration,
+    lances: Option<u32>,
+    infinito: bool,
+}
+
+enum Evento {
+    Linha(String),
+    FimDaEntrada,
+    Concluida(Option<Analise>),
+}
+
+struct BuscaAtiva {
+    parar: Arc<AtomicBool>,
+    ponderando: bool,
+    /// Resultado que chegou enquanto o motor ainda ponderava.
+    concluida: Option<Option<Analise>>,
+    partida: Partida,
+}
+
+type Saida = Arc<Mutex<dyn Write + Send>>;
+
+fn escrever(saida: &Saida, linha: &LinhaHub) {
+    let mut saida = saida.lock().unwrap();
+    // Se a interface fechou a saída não há a quem avisar
+    writeln!(saida, "{linha}").ok();
+    saida.flush().ok();
+}
+
+fn segundos(texto: &str) -> Result<Duration, String> {
+    texto
+        .parse::<f64>()
+        .ok()
+        .filter(|segundos| *segundos >= 0.0)
+        .and_then(|segundos| Duration::try_from_secs_f64(segundos).ok())
+        .ok_or_else(|| format!("tempo inválido: {texto}"))
+}
+
+/// Motor que responde ao protocolo Hub usando o `Bot`.
+pub struct Motor {
+    partida: Partida,
+    nivel: Nivel,
+    restante: Option<Duration>,
+    busca: Option<BuscaAtiva>,
+    saida: Saida,
+    eventos: Sender<Evento>,
+}
+
+impl Motor {
+    /// Lê comandos de `entrada` até `quit` ou o fim da entrada, respondendo em `saida`.
+    /// No fim da entrada a busca em andamento termina normalmente antes de sair.
+    pub fn executar(entrada: impl BufRead + Send + 'static, saida: impl Write + Send + 'static) {
+        let (eventos, recebidos) = mpsc::channel();
+        let leitor = eventos.clone();
+        thread::spawn(move || {
+            for linha in entrada.lines() {
+                let Ok(linha) = linha else { break };
+                if leitor.send(Evento::Linha(linha)).is_err() {
+                    return;
+                }
+            }
+            leitor.send(Evento::FimDaEntrada).ok();
+        });
+
+        let mut motor = Motor {
+            partida: Partida::default(),
+            nivel: Nivel::default(),
+            restante: None,
+            busca: None,
+            saida: Arc::new(Mutex::new(saida)),
+            eventos,
+        };
+        let mut encerrando = false;
+        while let Ok(evento) = recebidos.recv() {
+            match evento {
+                Evento::Linha(linha) => {
+                    let linha = LinhaHub::ler(&linha);
+                    if linha.comando == "quit" {
+                        motor.parar();
+                        return;
+                    }
+                    if let Err(mensagem) = motor.tratar(&linha) {
+                        escrever(&motor.saida, &LinhaHub::new("error").campo("message", mensagem));
+                    }
+                }
+                Evento::FimDaEntrada => {
+                    encerrando = true;
+                    if let Some(busca) = &mut motor.busca {
+                        if busca.ponderando {
+                            busca.ponderando = false;
+                            busca.parar.store(true, Ordering::Relaxed);
+                        }
+                    }
+                }
+                Evento::Concluida(analise) => {
+                    if let Some(busca) = &mut motor.busca {
+                        busca.concluida = Some(analise);
+                        if !busca.ponderando {
+                            motor.terminar();
+                        }
+                    }
+                }
+            }
+            if encerrando && motor.busca.is_none() {
+                return;
+            }
+        }
+    }
+
+    fn tratar(&mut self, linha: &LinhaHub) -> Result<(), String> {
+        match linha.comando.as_str() {
+            "" => (),
+            "hub" => {
+                let id = LinhaHub::new("id").campo("name", "damas").campo("version", env!("CARGO_PKG_VERSION"));
+                escrever(&self.saida, &id);
+                escrever(&self.saida, &LinhaHub::new("wait"));
+            }
+            "init" => escrever(&self.saida, &LinhaHub::new("ready")),
+            "ping" => escrever(&self.saida, &LinhaHub::new("pong")),
+            "set-param" => (),
+            "new-game" => {
+                self.exigir_parado()?;
+                self.partida = Partida::default();
+            }
+            "pos" => {
+                self.exigir_parado()?;
+                self.partida = self.ler_pos(linha)?;
+            }
+            "level" => self.nivel = ler_nivel(linha)?,
+            "time" => {
+                let restante = linha.valor("left").ok_or("time precisa de left=<segundos>")?;
+                self.restante = Some(segundos(restante)?);
+            }
+            "go" => {
+                self.exigir_parado()?;
+                self.pensar(linha.tem("ponder"), linha.tem("analyze"))?;
+            }
+            "ponder-hit" => self.confirmar_ponderacao(),
+            "stop" => self.parar(),
+            outro => return Err(format!("comando desconhecido: {outro}")),
+        }
+        Ok(())
+    }
+
+    fn exigir_parado(&self) -> Result<(), String> {
+        match self.busca {
+            Some(_) => Err("há uma busca em andamento; mande stop antes".to_string()),
+            None => Ok(()),
+        }
+    }
+
+    fn ler_pos(&self, linha: &LinhaHub) -> Result<Partida, String> {
+        let mut partida = match (linha.valor("pos"), linha.valor("fen")) {
+            (Some(posicao), _) => ler_posicao(posicao)?,
+            (None, Some(fen)) => Partida::from_fen(fen).map_err(|erro| erro.to_string())?,
+            (None, None) if linha.tem("start") => Partida::default(),
+            (None, None) => self.partida.clone(),
+        };
+        for lance in linha.valor("moves").unwrap_or("").split_whitespace() {
+            let indice = partida.encontrar_jogada(lance).map_err(|erro| format!("{lance}: {erro}"))?;
+            partida.jogar(indice);
+        }
+        Ok(partida)
+    }
+
+    // Tempo da jogada pelo nível e pelo relógio; None quando só a profundidade limita
+    fn orcamento(&self) -> Option<Duration> {
+        if self.nivel.infinito {
+            return None;
+        }
+        if let Some(tempo) = self.nivel.tempo_por_lance {
+            return Some(tempo);
+        }
+        let restante = self.restante.or(self.nivel.tempo)?;
+        let lances = self.nivel.lances.unwrap_or(LANCES_ESPERADOS).max(1);
+        Some((restante / lances + self.nivel.incremento).min(restante / 2))
+    }
+
+    fn pensar(&mut self, ponderar: bool, analisar: bool) -> Result<(), String> {
+        if self.partida.todas_jogadas_possiveis().is_empty() {
+            return Err("quem tem a vez não tem jogadas".to_string());
+        }
+        let sem_limite = ponderar || analisar;
+        let mut bot = match self.nivel.profundidade {
+            Some(profundidade) => Bot::new(profundidade),
+            None if sem_limite || self.orcamento().is_some() => Bot::new(PROFUNDIDADE_MAXIMA),
+            None => Bot::default(),
+        };
+        if !sem_limite {
+            bot.tempo = self.orcamento();
+        }
+
+        let parar = Arc::new(AtomicBool::new(false));
+        let partida = self.partida.clone();
+        let (saida, eventos, sinal) = (self.saida.clone(), self.eventos.clone(), parar.clone());
+        thread::spawn(move || {
+            let inicio = Instant::now();
+            let analise = bot.analisar_com(&partida, &sinal, |analise| {
+                let tempo = inicio.elapsed().as_secs_f64();
+                let info = LinhaHub::new("info")
+                    .campo("depth", analise.profundidade)
+                    .campo("score", format!("{:.2}", analise.avaliacao as f64 / 100.0))
+                    .campo("nodes", analise.nos)
+                    .campo("time", format!("{tempo:.3}"))
+                    .campo("nps", (analise.nos as f64 / tempo.max(0.001)) as u64)
+                    .campo("pv", analise.pv.iter().map(|sequencia| notacao(sequencia)).collect::<Vec<_>>().join(" "));
+                escrever(&saida, &info);
+            });
+            eventos.send(Evento::Concluida(analise)).ok();
+        });
+        // Analisar também espera um stop antes de responder
+        self.busca = Some(BuscaAtiva { parar, ponderando: sem_limite, concluida: None, partida: self.partida.clone() });
+        Ok(())
+    }
+
+    fn confirmar_ponderacao(&mut self) {
+        let orcamento = self.orcamento();
+        let Some(busca) = &mut self.busca else { return };
+        busca.ponderando = false;
+        if busca.concluida.is_some() {
+            self.terminar();
+            return;
+        }
+        // A busca já corre desde o ponder; a partir de agora ela tem o tempo normal da jogada
+        if let Some(tempo) = orcamento {
+            let parar = busca.parar.clone();
+            thread::spawn(move || {
+                thread::sleep(tempo);
+                parar.store(true, Ordering::Relaxed);
+            });
+        }
+    }
+
+    fn parar(&mut self) {
+        let Some(busca) = &mut self.busca else { return };
+        busca.ponderando = false;
+        busca.parar.store(true, Ordering::Relaxed);
+        if busca.concluida.is_some() {
+            self.terminar();
+        }
+    }
+
+    fn terminar(&mut self) {
+        let Some(busca) = self.busca.take() else { return };
+        let jogadas = busca.partida.todas_jogadas_possiveis();
+        // Parada antes da primeira iteração: qualquer jogada possível serve
+        let analise = busca.concluida.flatten();
+        let indice = analise.as_ref().map_or(0, |analise| analise.indice);
+        let mut done = LinhaHub::new("done").campo("move", notacao(&jogadas[indice]));
+        if let Some(resposta) = analise.as_ref().and_then(|analise| analise.pv.get(1)) {
+            done = done.campo("ponder", notacao(resposta));
+        }
+        escrever(&self.saida, &done);
+    }
+}
+
+fn ler_nivel(linha: &LinhaHub) -> Result<Nivel, String> {
+    let mut nivel = Nivel::default();
+    for (nome, valor) in &linha.campos {
+        let inteiro = || valor.parse::<u32>().map_err(|_| format!("{nome}: '{valor}' não é um número"));
+        match nome.as_str() {
+            "depth" => nivel.profundidade = Some(inteiro()?.max(1)),
+            "move-time" => nivel.tempo_por_lance = Some(segundos(valor)?),
+            "time" => nivel.tempo = Some(segundos(valor)?),
+            "inc" => nivel.incremento = segundos(valor)?,
+            "moves" => nivel.lances = Some(inteiro()?),
+            "infinite" => nivel.infinito = true,
+            outro => return Err(format!("level: campo desconhecido {outro}")),
+        }
+    }
+    Ok(nivel)
+}
+
+/// Um motor externo que fala Hub, rodando como processo filho.
+pub struct MotorExterno {
+    /// Nome que o motor informou no `id`.
+    pub nome: String,
+    processo: Child,
+    entrada: ChildStdin,
+    saida: BufReader<ChildStdout>,
+    falha: Option<String>,
+}
+
+impl MotorExterno {
+    /// Inicia o comando (programa e argumentos separados por espaços) e faz a apresentação.
+    pub fn iniciar(comando: &str) -> Result<MotorExterno, String> {
+        let mut partes = comando.split_whitespace();
+        let programa = partes.next().ok_or("comando vazio")?;
+        let mut processo = Command::new(programa)
+            .args(partes)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|erro| format!("{programa}: {erro}"))?;
+        let entrada = processo.stdin.take().expect("stdin redirecionado");
+        let saida = BufReader::new(processo.stdout.take().expect("stdout redirecionado"));
+        let mut motor = MotorExterno { nome: programa.to_string(), processo, entrada, saida, falha: None };
+        motor.enviar(&LinhaHub::new("hub"))?;
+        let id = motor.esperar("wait")?;
+        if let Some(nome) = id.iter().find(|linha| linha.comando == "id").and_then(|id| id.valor("name")) {
+            motor.nome = nome.to_string();
+        }
+        motor.enviar(&LinhaHub::new("init"))?;
+        motor.esperar("ready")?;
+        Ok(motor)
+    }
+
+    pub fn enviar(&mut self, linha: &LinhaHub) -> Result<(), String> {
+        writeln!(self.entrada, "{linha}").and_then(|_| self.entrada.flush()).map_err(|erro| format!("{}: {erro}", self.nome))
+    }
+
+    // Lê linhas até o comando esperado, devolvendo todas elas
+    fn esperar(&mut self, comando: &str) -> Result<Vec<LinhaHub>, String> {
+        let mut linhas = vec![];
+        loop {
+            let mut texto = String::new();
+            match self.saida.read_line(&mut texto) {
+                Ok(0) | Err(_) => return Err(format!("{} encerrou antes de responder {comando}", self.nome)),
+                Ok(_) => (),
+            }
+            let linha = LinhaHub::ler(&texto);
+            if linha.comando == "error" {
+                return Err(format!("{}: {}", self.nome, linha.valor("message").unwrap_or("erro")));
+            }
+            let fim = linha.comando == comando;
+            linhas.push(linha);
+            if fim {
+                return Ok(linhas);
+            }
+        }
+    }
+
+    /// Pede um lance na posição e devolve o índice em `todas_jogadas_possiveis`.
+    pub fn escolher(&mut self, partida: &Partida) -> Result<usize, String> {
+        self.enviar(&LinhaHub::new("pos").campo("pos", posicao(partida)))?;
+        self.enviar(&LinhaHub::new("go").campo("think", ""))?;
+        let linhas = self.esperar("done")?;
+        let lance = linhas.last().and_then(|done| done.valor("move")).ok_or_else(|| format!("{} não mandou o lance", self.nome))?;
+        partida.encontrar_jogada(lance).map_err(|erro| format!("{}: {lance}: {erro}", self.nome))
+    }
+}
+
+impl Jogador for MotorExterno {
+    fn escolher(&mut self, partida: &Partida) -> Vec<Jogada> {
+        match MotorExterno::escolher(self, partida) {
+            Ok(indice) => partida.todas_jogadas_possiveis()[indice].clone(),
+            Err(erro) => {
+                self.falha = Some(erro);
+                vec![]
+            }
+        }
+    }
+
+    fn falha(&mut self) -> Option<String> {
+        self.falha.take()
+    }
+}
+
+impl Drop for MotorExterno {
+    fn drop(&mut self) {
+        self.enviar(&LinhaHub::new("quit")).ok();
+        // Um motor que não sai sozinho logo é encerrado
+        for _ in 0..20 {
+            if let Ok(Some(_)) = self.processo.try_wait() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        self.processo.kill().ok();
+        self.processo.wait().ok();
+    }
+}
+
+#[test]
+fn testar_linha_hub() {
+    let linha = LinhaHub::ler(r#"pos start moves="22-18 11-15"  extra"#);
+    assert_eq!(linha.comando, "pos");
+    assert_eq!(linha.valor("moves"), Some("22-18 11-15"));
+    assert!(linha.tem("start") && linha.tem("extra"));
+    assert_eq!(linha.to_string(), r#"pos start moves="22-18 11-15" extra"#);
+
+    let partida = Partida::from_fen("B:W22,K30:B18,11").unwrap();
+    assert_eq!(ler_posicao(&posicao(&partida)).unwrap().fen(), partida.fen());
+    assert_eq!(
+        ler_posicao(&format!("Ww{}", "e".repeat(30) + "b")).err(),
+        Some("posição inválida: o peão na casa 1 já teria virado dama".to_string())
+    );
+
+    assert_eq!(segundos("1.5"), Ok(Duration::from_millis(1500)));
+    assert_eq!(segundos("inf"), Err("tempo inválido: inf".to_string()));
+    assert!(segundos("1e30").is_err());
+}
+
+#[test]
+fn testar_motor_hub() {
+    use std::io::Cursor;
+
+    struct Compartilhada(Arc<Mutex<Vec<u8>>>);
+    impl Write for Compartilhada {
+        fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(bytes)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let texto = Arc::new(Mutex::new(vec![]));
+    let entrada = "hub\ninit\npos fen=W:W22:B18,11\nlevel depth=3\ngo think\n";
+    Motor::executar(Cursor::new(entrada), Compartilhada(texto.clone()));
+    let texto = String::from_utf8(texto.lock().unwrap().clone()).unwrap();
+    let linhas: Vec<&str> = texto.lines().collect();
+    assert_eq!(linhas[0], format!("id name=damas version={}", env!("CARGO_PKG_VERSION")));
+    assert_eq!(linhas[1..3], ["wait", "ready"]);
+    assert!(linhas[3].starts_with("info depth=1"));
+    assert_eq!(linhas.last(), Some(&"done move=22x15x8"));
+}
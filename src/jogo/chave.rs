@@ -0,0 +1,134 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use super::casa::Casa;
+use super::cor::Cor;
+use super::pedra::Pedra;
+use super::Partida;
+
+// Chave de Zobrist da posição: um número aleatório fixo para cada pedra em cada
+// casa, combinados com xor, mais um para a vez das pretas. Duas posições iguais
+// têm a mesma chave, não importa a ordem dos lances que levaram a elas.
+
+const fn misturar(mut x: u64) -> u64 {
+    // splitmix64
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+const fn gerar_tabela() -> [[u64; 4]; 64] {
+    let mut tabela = [[0; 4]; 64];
+    let mut i = 0;
+    while i < 64 * 4 {
+        tabela[i / 4][i % 4] = misturar(i as u64);
+        i += 1;
+    }
+    tabela
+}
+
+const ZOBRIST: [[u64; 4]; 64] = gerar_tabela();
+const VEZ_DO_PRETO: u64 = misturar(64 * 4);
+
+/// Quantas pedras e damas cada lado tem, por exemplo "3D x 1D" para três damas
+/// brancas contra uma preta.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Material {
+    pub pedras_brancas: u8,
+    pub damas_brancas: u8,
+    pub pedras_pretas: u8,
+    pub damas_pretas: u8,
+}
+
+impl Display for Material {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lado = |pedras: u8, damas: u8| match (pedras, damas) {
+            (0, 0) => "0".to_string(),
+            (p, 0) => format!("{p}P"),
+            (0, d) => format!("{d}D"),
+            (p, d) => format!("{p}P {d}D"),
+        };
+        write!(f, "{} x {}", lado(self.pedras_brancas, self.damas_brancas), lado(self.pedras_pretas, self.damas_pretas))
+    }
+}
+
+impl FromStr for Material {
+    type Err = String;
+
+    /// Lê o formato do Display; os espaços são opcionais ("3Dx1D").
+    fn from_str(texto: &str) -> Result<Self, Self::Err> {
+        let lado = |lado: &str| -> Result<(u8, u8), String> {
+            let lado: String = lado.chars().filter(|ch| !ch.is_whitespace()).collect();
+            let (mut pedras, mut damas) = (0, 0);
+            let mut numero = String::new();
+            for ch in lado.chars() {
+                match ch {
+                    '0'..='9' => numero.push(ch),
+                    'P' | 'p' | 'D' | 'd' => {
+                        let n = numero.parse().map_err(|_| format!("falta o número antes de {ch} em '{lado}'"))?;
+                        if ch.eq_ignore_ascii_case(&'P') { pedras = n } else { damas = n }
+                        numero.clear();
+                    }
+                    _ => return Err(format!("'{ch}' inválido em '{lado}'")),
+                }
+            }
+            if !numero.is_empty() && numero != "0" {
+                return Err(format!("'{lado}': use P para pedras e D para damas"));
+            }
+            Ok((pedras, damas))
+        };
+        let (brancas, pretas) = texto.split_once(['x', 'X']).ok_or("separe os lados com x, por exemplo 3D x 1D")?;
+        let (pedras_brancas, damas_brancas) = lado(brancas)?;
+        let (pedras_pretas, damas_pretas) = lado(pretas)?;
+        Ok(Material { pedras_brancas, damas_brancas, pedras_pretas, damas_pretas })
+    }
+}
+
+impl Partida {
+    /// Chave de Zobrist da posição e da vez.
+    pub fn chave(&self) -> u64 {
+        let mut chave = if self.vez == Cor::Preto { VEZ_DO_PRETO } else { 0 };
+        for (i, casa) in self.tabuleiro.iter().flatten().enumerate() {
+            if let Casa::Ocupada(pedra) = casa {
+                chave ^= ZOBRIST[i][*pedra as usize];
+            }
+        }
+        chave
+    }
+
+    pub fn material(&self) -> Material {
+        let mut material = Material::default();
+        for casa in self.tabuleiro.iter().flatten() {
+            match casa {
+                Casa::Ocupada(Pedra::Branca) => material.pedras_brancas += 1,
+                Casa::Ocupada(Pedra::DamaBranca) => material.damas_brancas += 1,
+                Casa::Ocupada(Pedra::Preta) => material.pedras_pretas += 1,
+                Casa::Ocupada(Pedra::DamaPreta) => material.damas_pretas += 1,
+                Casa::Vazia => (),
+            }
+        }
+        material
+    }
+}
+
+#[test]
+fn testar_chave_e_material() {
+    let mut a = Partida::default();
+    let mut b = Partida::default();
+    for lance in ["21-17", "12-16", "22-18"] {
+        a.jogar(a.encontrar_jogada(lance).unwrap());
+    }
+    for lance in ["22-18", "12-16", "21-17"] {
+        b.jogar(b.encontrar_jogada(lance).unwrap());
+    }
+    assert_eq!(a.chave(), b.chave());
+    assert_ne!(a.chave(), Partida::default().chave());
+    assert_ne!(Partida::from_fen("W:W22:B3").unwrap().chave(), Partida::from_fen("B:W22:B3").unwrap().chave());
+
+    let material = Partida::from_fen("W:WK1,K2,K3:BK32").unwrap().material();
+    assert_eq!(material.to_string(), "3D x 1D");
+    assert_eq!("3Dx1D".parse(), Ok(material));
+    assert_eq!("12P x 12P".parse::<Material>().unwrap(), Partida::default().material());
+    assert!("3D 1D".parse::<Material>().is_err());
+}
@@ -0,0 +1,16 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Cor {
+    Branco,
+    Preto,
+}
+
+impl Cor {
+    pub fn adversario(self) -> Cor {
+        match self {
+            Cor::Branco => Cor::Preto,
+            Cor::Preto => Cor::Branco,
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use serde::de::Error as _;
+use serde::ser::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::casa::Casa;
+use super::estado::{Estado, MotivoEmpate, MotivoVitoria};
+use super::coord::Coord;
+use super::jogada::Jogada;
+use super::cor::Cor;
+use super::pedra::Pedra;
+use super::resultado::Resultado;
+use super::Partida;
+
+// Formato JSON dos tipos do jogo, com a feature "serde". As casas são sempre
+// os números de 1 a 32 da notação, nunca as coordenadas internas.
+//
+//   Cor         "branco" | "preto"
+//   Pedra       "branca" | "dama_branca" | "preta" | "dama_preta"
+//   Casa        uma Pedra, ou null se vazia
+//   Coord       número da casa: 22
+//   Jogada      {"origem": 22, "destino": 18}
+//               {"origem": 22, "capturada": 18, "destino": 15}
+//   Resultado   "falha" | "sucesso" | {"fim_do_jogo": "branco" | "preto" | null}
+//   Partida     {
+//                 "versao": 1,
+//                 "posicao_inicial": "W:W21,...:B1,...",   FEN de onde a partida começou
+//                 "lances": ["22-18", "11-15"],            notação numérica
+//                 "fen": "W:W...:B...",                    posição atual
+//                 "vez": "branco",
+//                 "casas": [Casa; 32],                     da casa 1 à 32
+//                 "contador_empate": 0.0,
+//                 "estado": "em_andamento" | {"vitoria": ["branco", "desistencia"]}
+//                           | {"empate": "acordo"} | ...
+//               }
+//
+// Na leitura da Partida só versao, posicao_inicial e lances são obrigatórios:
+// a partida é refeita jogando os lances. Se vier o fen, ele precisa bater com
+// a posição final; vez e casas existem só para quem lê o JSON. O FEN não tem o
+// contador de empate: se os lances não o zeraram, o contador_empate diz de
+// quanto ele partiu; se zeraram, precisa bater com o dos lances.
+// O estado é conferido com o dos lances, a não ser pela desistência, pelo
+// tempo e pelo acordo, que não saem dos lances e são aplicados depois deles.
+
+/// Versão do formato da Partida em JSON. Muda quando o formato deixa de ser compatível.
+pub const VERSAO_ESQUEMA: u32 = 1;
+
+impl Serialize for Coord {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let numero = self.numero().ok_or_else(|| S::Error::custom(format!("{self:?} não é uma casa escura")))?;
+        serializer.serialize_u8(numero)
+    }
+}
+
+impl<'de> Deserialize<'de> for Coord {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let numero = u8::deserialize(deserializer)?;
+        Coord::de_numero(numero).ok_or_else(|| D::Error::custom(format!("{numero} não é uma casa de 1 a 32")))
+    }
+}
+
+impl Serialize for Casa {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.pedra().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Casa {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Option::<Pedra>::deserialize(deserializer)?.map_or(Casa::Vazia, Casa::Ocupada))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct JogadaJson {
+    origem: Coord,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    capturada: Option<Coord>,
+    destino: Coord,
+}
+
+impl Serialize for Jogada {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let capturada = match self {
+            Jogada::Mover(..) => None,
+            Jogada::Capturar(_, capturada, _) => Some(*capturada),
+        };
+        JogadaJson { origem: self.origem(), capturada, destino: self.destino() }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Jogada {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let JogadaJson { origem, capturada, destino } = JogadaJson::deserialize(deserializer)?;
+        Ok(match capturada {
+            Some(capturada) => Jogada::Capturar(origem, capturada, destino),
+            None => Jogada::Mover(origem, destino),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PartidaJson {
+    versao: u32,
+    posicao_inicial: String,
+    lances: Vec<String>,
+    #[serde(default)]
+    fen: Option<String>,
+    #[serde(default)]
+    vez: Option<Cor>,
+    #[serde(default)]
+    casas: Option<Vec<Casa>>,
+    #[serde(default)]
+    contador_empate: Option<f32>,
+    #[serde(default)]
+    estado: Option<Estado>,
+}
+
+impl Serialize for Partida {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let casas = (1..=32).map(|numero| self.casa(Coord::de_numero(numero).unwrap())).collect();
+        PartidaJson {
+            versao: VERSAO_ESQUEMA,
+            posicao_inicial: self.posicao_inicial().fen(),
+            lances: self.historico.iter().map(|sequencia| super::notacao::notacao(sequencia)).collect(),
+            fen: Some(self.fen()),
+            vez: Some(self.vez),
+            casas: Some(casas),
+            contador_empate: Some(self.contador_empate),
+            estado: Some(self.estado),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Partida {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let json = PartidaJson::deserialize(deserializer)?;
+        if json.versao != VERSAO_ESQUEMA {
+            return Err(D::Error::custom(format!("versão {} do formato não suportada (esperada {VERSAO_ESQUEMA})", json.versao)));
+        }
+        let refazer = |contador: f32| -> Result<Partida, String> {
+            let mut partida = Partida::from_fen(&json.posicao_inicial).map_err(|erro| erro.to_string())?;
+            partida.com_contador_inicial(contador);
+            for (i, lance) in json.lances.iter().enumerate() {
+                let indice = partida.encontrar_jogada(lance).map_err(|erro| format!("lance {}: {erro}", i + 1))?;
+                if let Resultado::Falha = partida.jogar(indice) {
+                    return Err(format!("lance {}: a partida já tinha terminado", i + 1));
+                }
+            }
+            Ok(partida)
+        };
+        let mut partida = refazer(0.0).map_err(D::Error::custom)?;
+        if let Some(contador) = json.contador_empate {
+            // Cada lance que não zera o contador soma meio
+            let sem_zerar = partida.contador_empate == json.lances.len() as f32 * 0.5;
+            let inicial = contador - partida.contador_empate;
+            if sem_zerar && inicial > 0.0 && (inicial * 2.0).fract() == 0.0 {
+                partida = refazer(inicial).map_err(D::Error::custom)?;
+            }
+            if partida.contador_empate != contador {
+                return Err(D::Error::custom(format!("os lances não levam ao contador de empate {contador}")));
+            }
+        }
+        if let Some(fen) = json.fen {
+            if Partida::from_fen(&fen).map_err(D::Error::custom)?.fen() != partida.fen() {
+                return Err(D::Error::custom("os lances não levam à posição do fen"));
+            }
+        }
+        match json.estado {
+            Some(Estado::Vitoria(vencedor, MotivoVitoria::Desistencia)) => {
+                partida.desistir(vencedor.adversario()).map_err(D::Error::custom)?
+            }
+            Some(Estado::Vitoria(vencedor, MotivoVitoria::Tempo)) => {
+                partida.perder_por_tempo(vencedor.adversario()).map_err(D::Error::custom)?
+            }
+            Some(Estado::Empate(MotivoEmpate::Acordo)) => partida.empatar_por_acordo().map_err(D::Error::custom)?,
+            Some(estado) if estado != partida.estado() => {
+                return Err(D::Error::custom(format!("os lances não levam ao estado '{estado}'")));
+            }
+            _ => {}
+        }
+        Ok(partida)
+    }
+}
+
+#[test]
+fn testar_esquema() {
+    use serde_json::json;
+
+    let mut partida = Partida::default();
+    for lance in ["22-18", "11-15", "18x11"] {
+        partida.jogar(partida.encontrar_jogada(lance).unwrap());
+    }
+    let valor = serde_json::to_value(&partida).unwrap();
+    assert_eq!(valor["versao"], 1);
+    assert_eq!(valor["lances"], json!(["22-18", "11-15", "18x11"]));
+    assert_eq!(valor["vez"], "preto");
+    assert_eq!(valor["casas"][10], "branca");
+    assert_eq!(valor["casas"][17], json!(null));
+    let lida: Partida = serde_json::from_value(valor.clone()).unwrap();
+    assert_eq!(lida.fen(), partida.fen());
+    assert_eq!(lida.historico(), partida.historico());
+
+    // Só os campos obrigatórios, e um fen que não bate
+    let minima = json!({"versao": 1, "posicao_inicial": "W:W21-32:B1-12", "lances": ["c3-d4"]});
+    assert_eq!(serde_json::from_value::<Partida>(minima).unwrap().fen(), Partida::from_fen("B:W18,21,23-32:B1-12").unwrap().fen());
+    let errada = json!({"versao": 1, "posicao_inicial": "W:W21-32:B1-12", "lances": [], "fen": "B:W21-32:B1-12"});
+    assert!(serde_json::from_value::<Partida>(errada).is_err());
+    let futura = json!({"versao": 2, "posicao_inicial": "W:W21-32:B1-12", "lances": []});
+    assert!(serde_json::from_value::<Partida>(futura).unwrap_err().to_string().contains("versão 2"));
+
+    // A desistência não sai dos lances e precisa ir no JSON
+    partida.desistir(Cor::Preto).unwrap();
+    let valor = serde_json::to_value(&partida).unwrap();
+    assert_eq!(valor["estado"], json!({"vitoria": ["branco", "desistencia"]}));
+    assert_eq!(serde_json::from_value::<Partida>(valor).unwrap().estado(), partida.estado());
+    let mentira = json!({"versao": 1, "posicao_inicial": "W:W21-32:B1-12", "lances": [], "estado": {"empate": "duas_damas"}});
+    assert!(serde_json::from_value::<Partida>(mentira).is_err());
+
+    // Lances depois do fim e contador de empate da posição inicial
+    let depois_do_fim = json!({"versao": 1, "posicao_inicial": "W:W22,K30:B18", "lances": ["22x15", "30-26"]});
+    assert!(serde_json::from_value::<Partida>(depois_do_fim).unwrap_err().to_string().contains("lance 2"));
+    let damas = json!({"versao": 1, "posicao_inicial": "W:WK1,K2:BK32", "lances": ["1-5"], "contador_empate": 3.5});
+    let mut damas = serde_json::from_value::<Partida>(damas).unwrap();
+    assert_eq!(damas.get_contador_empate(), 3.5);
+    assert!(damas.desfazer());
+    assert_eq!(damas.get_contador_empate(), 3.0);
+    let errado = json!({"versao": 1, "posicao_inicial": "W:W21-32:B1-12", "lances": ["22-18"], "contador_empate": 2.0});
+    assert!(serde_json::from_value::<Partida>(errado).is_err());
+
+    let captura = &partida.historico()[2][0];
+    assert_eq!(serde_json::to_value(captura).unwrap(), json!({"origem": 18, "capturada": 15, "destino": 11}));
+    assert_eq!(serde_json::from_value::<Jogada>(json!({"origem": 22, "destino": 18})).unwrap(), partida.historico()[0][0]);
+    assert_eq!(serde_json::to_value(crate::Resultado::FimDoJogo(None)).unwrap(), json!({"fim_do_jogo": null}));
+    assert_eq!(serde_json::to_value(Pedra::DamaPreta).unwrap(), "dama_preta");
+    assert!(serde_json::from_value::<Coord>(json!(33)).is_err());
+}
@@ -0,0 +1,188 @@
+use std::fmt::Display;
+
+use super::cor::Cor;
+use super::Partida;
+
+// Estado da partida. Só `jogar`, `desistir`, `perder_por_tempo` e
+// `empatar_por_acordo` mudam o estado, e nenhum deles aceita uma partida que já acabou; `desfazer` volta
+// ao estado de antes do último lance.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum MotivoVitoria {
+    /// O adversário ficou sem pedras.
+    SemPedras,
+    /// O adversário ficou sem jogadas na vez dele.
+    SemJogadas,
+    Desistencia,
+    /// O relógio do adversário chegou a zero.
+    Tempo,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum MotivoEmpate {
+    /// Cinco lances com uma dama contra uma dama.
+    DuasDamas,
+    /// Vinte lances seguidos só de damas e sem captura.
+    LancesDeDama,
+    Acordo,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Estado {
+    EmAndamento,
+    Vitoria(Cor, MotivoVitoria),
+    Empate(MotivoEmpate),
+}
+
+impl Estado {
+    pub fn encerrada(self) -> bool {
+        self != Estado::EmAndamento
+    }
+
+    /// O vencedor, ou None em andamento e no empate.
+    pub fn vencedor(self) -> Option<Cor> {
+        match self {
+            Estado::Vitoria(vencedor, _) => Some(vencedor),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Estado {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Estado::EmAndamento => write!(f, "em andamento"),
+            Estado::Vitoria(vencedor, motivo) => {
+                let cor = if *vencedor == Cor::Branco { "brancas" } else { "pretas" };
+                let motivo = match motivo {
+                    MotivoVitoria::SemPedras => "o adversário ficou sem pedras",
+                    MotivoVitoria::SemJogadas => "o adversário ficou sem jogadas",
+                    MotivoVitoria::Desistencia => "o adversário desistiu",
+                    MotivoVitoria::Tempo => "o tempo do adversário acabou",
+                };
+                write!(f, "vitória das {cor}: {motivo}")
+            }
+            Estado::Empate(motivo) => {
+                let motivo = match motivo {
+                    MotivoEmpate::DuasDamas => "cinco lances de dama contra dama",
+                    MotivoEmpate::LancesDeDama => "vinte lances só de damas sem captura",
+                    MotivoEmpate::Acordo => "acordo entre os jogadores",
+                };
+                write!(f, "empate: {motivo}")
+            }
+        }
+    }
+}
+
+/// Ação recusada porque a partida já acabou.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PartidaEncerrada(pub Estado);
+
+impl Display for PartidaEncerrada {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "a partida já terminou ({})", self.0)
+    }
+}
+
+impl std::error::Error for PartidaEncerrada {}
+
+impl Partida {
+    pub fn estado(&self) -> Estado {
+        self.estado
+    }
+
+    pub fn encerrada(&self) -> bool {
+        self.estado.encerrada()
+    }
+
+    /// `jogador` abandona a partida e o adversário vence.
+    pub fn desistir(&mut self, jogador: Cor) -> Result<(), PartidaEncerrada> {
+        self.encerrar(Estado::Vitoria(jogador.adversario(), MotivoVitoria::Desistencia))
+    }
+
+    /// O relógio de `jogador` chegou a zero e o adversário vence.
+    pub fn perder_por_tempo(&mut self, jogador: Cor) -> Result<(), PartidaEncerrada> {
+        self.encerrar(Estado::Vitoria(jogador.adversario(), MotivoVitoria::Tempo))
+    }
+
+    /// Os dois jogadores aceitam o empate.
+    pub fn empatar_por_acordo(&mut self) -> Result<(), PartidaEncerrada> {
+        self.encerrar(Estado::Empate(MotivoEmpate::Acordo))
+    }
+
+    fn encerrar(&mut self, estado: Estado) -> Result<(), PartidaEncerrada> {
+        if self.encerrada() {
+            return Err(PartidaEncerrada(self.estado));
+        }
+        self.finalizar(estado);
+        Ok(())
+    }
+
+    // Partida encerrada não tem jogadas possíveis
+    pub(super) fn finalizar(&mut self, estado: Estado) {
+        self.estado = estado;
+        if estado.encerrada() {
+            self.jogadas.clear();
+        }
+    }
+
+    // Estado de uma posição recém-montada, sem histórico
+    pub(super) fn estado_inicial(&self) -> Estado {
+        let tem_pedras = |jogador: Cor| {
+            self.tabuleiro.iter().flatten().filter_map(|casa| casa.pedra()).any(|pedra| pedra.é_branca() == (jogador == Cor::Branco))
+        };
+        if !tem_pedras(self.vez) {
+            Estado::Vitoria(self.vez.adversario(), MotivoVitoria::SemPedras)
+        } else if !tem_pedras(self.vez.adversario()) {
+            Estado::Vitoria(self.vez, MotivoVitoria::SemPedras)
+        } else if self.jogadas.is_empty() {
+            Estado::Vitoria(self.vez.adversario(), MotivoVitoria::SemJogadas)
+        } else {
+            Estado::EmAndamento
+        }
+    }
+}
+
+#[test]
+fn testar_estado() {
+    use super::resultado::Resultado;
+
+    let mut partida = Partida::default();
+    assert_eq!(partida.estado(), Estado::EmAndamento);
+    partida.jogar(partida.encontrar_jogada("22-18").unwrap());
+    assert_eq!(partida.desistir(Cor::Preto), Ok(()));
+    assert_eq!(partida.estado(), Estado::Vitoria(Cor::Branco, MotivoVitoria::Desistencia));
+    assert!(matches!(partida.jogar(0), Resultado::Falha));
+    assert!(partida.todas_jogadas_possiveis().is_empty());
+    assert_eq!(partida.empatar_por_acordo(), Err(PartidaEncerrada(partida.estado())));
+    assert_eq!(partida.historico().len(), 1);
+    // Desfazer volta a partida para antes do último lance, em andamento
+    assert!(partida.desfazer());
+    assert_eq!(partida.estado(), Estado::EmAndamento);
+    assert_eq!(partida.todas_jogadas_possiveis().len(), 7);
+    assert_eq!(partida.empatar_por_acordo(), Ok(()));
+    assert_eq!(partida.estado().to_string(), "empate: acordo entre os jogadores");
+    let mut partida = Partida::default();
+    assert_eq!(partida.perder_por_tempo(Cor::Branco), Ok(()));
+    assert_eq!(partida.estado().to_string(), "vitória das pretas: o tempo do adversário acabou");
+
+    // Tomando a última pedra: o vencedor fica com a vez e não joga de novo
+    let mut partida = Partida::from_fen("W:W22,K30:B18").unwrap();
+    assert!(matches!(partida.jogar(0), Resultado::FimDoJogo(Some(Cor::Branco))));
+    assert_eq!(partida.estado(), Estado::Vitoria(Cor::Branco, MotivoVitoria::SemPedras));
+    assert!(partida.encerrada());
+    assert!(matches!(partida.jogar(0), Resultado::Falha));
+
+    // Pretas bloqueadas depois do lance das brancas
+    let mut partida = Partida::from_fen("W:W9,14,30:B5").unwrap();
+    assert!(matches!(partida.jogar(partida.encontrar_jogada("30-26").unwrap()), Resultado::FimDoJogo(Some(Cor::Branco))));
+    assert_eq!(partida.estado(), Estado::Vitoria(Cor::Branco, MotivoVitoria::SemJogadas));
+    assert_eq!(Partida::from_fen("B:W9,14:B5").unwrap().estado(), Estado::Vitoria(Cor::Branco, MotivoVitoria::SemJogadas));
+    assert_eq!(Partida::from_fen("W:W28:B").unwrap().estado().vencedor(), Some(Cor::Branco));
+}
@@ -0,0 +1,125 @@
+use std::fmt::Display;
+
+use super::coord::Coord;
+use super::cor::Cor;
+use super::notacao::ler_casa;
+use super::pedra::Pedra;
+use super::posicao::{ErroPosicao, PosicaoBuilder};
+use super::Partida;
+
+// FEN no formato do PDN: "W:W21,22,K30:B1-12". O primeiro campo diz de quem é a vez,
+// os outros listam as pedras de cada cor, com K na frente das damas. A posição
+// passa pela validação de `PosicaoBuilder`, mas pode ter um lado sem pedras,
+// como no fim de uma partida.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErroFen {
+    VezInvalida(String),
+    CorInvalida(String),
+    CasaInvalida(String),
+    Posicao(ErroPosicao),
+}
+
+impl Display for ErroFen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErroFen::VezInvalida(vez) => write!(f, "'{vez}' não indica de quem é a vez (use W ou B)"),
+            ErroFen::CorInvalida(campo) => write!(f, "campo '{campo}' não começa com W ou B"),
+            ErroFen::CasaInvalida(casa) => write!(f, "'{casa}' não é uma casa válida"),
+            ErroFen::Posicao(erro) => write!(f, "{erro}"),
+        }
+    }
+}
+
+impl std::error::Error for ErroFen {}
+
+impl Partida {
+    pub fn fen(&self) -> String {
+        let vez = match self.vez {
+            Cor::Branco => "W",
+            Cor::Preto => "B",
+        };
+        format!("{vez}:W{}:B{}", self.casas_fen(true), self.casas_fen(false))
+    }
+
+    fn casas_fen(&self, brancas: bool) -> String {
+        (1..=32)
+            .filter_map(|numero| {
+                let pedra = self.pedra(Coord::de_numero(numero).unwrap())?;
+                if pedra.é_branca() != brancas {
+                    return None;
+                }
+                Some(if pedra.é_dama() { format!("K{numero}") } else { numero.to_string() })
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    pub fn from_fen(fen: &str) -> Result<Partida, ErroFen> {
+        let fen = fen.trim().trim_end_matches('.');
+        let mut campos = fen.split(':');
+        let vez = match campos.next().map(str::trim) {
+            Some("W") | Some("w") => Cor::Branco,
+            Some("B") | Some("b") => Cor::Preto,
+            outro => return Err(ErroFen::VezInvalida(outro.unwrap_or("").to_string())),
+        };
+
+        let mut builder = PosicaoBuilder::new().vez(vez).posicao_final();
+        for campo in campos {
+            let campo = campo.trim();
+            let (peão, dama) = match campo.chars().next() {
+                Some('W') | Some('w') => (Pedra::Branca, Pedra::DamaBranca),
+                Some('B') | Some('b') => (Pedra::Preta, Pedra::DamaPreta),
+                _ => return Err(ErroFen::CorInvalida(campo.to_string())),
+            };
+            for item in campo[1..].split(',').map(str::trim).filter(|item| !item.is_empty()) {
+                let (pedra, casas) = match item.strip_prefix(['K', 'k']) {
+                    Some(resto) => (dama, resto),
+                    None => (peão, item),
+                };
+                for casa in ler_intervalo(casas)? {
+                    builder = builder.pedra_em(casa, pedra);
+                }
+            }
+        }
+        builder.construir().map_err(ErroFen::Posicao)
+    }
+}
+
+// Aceita uma casa ("22", "c3") ou um intervalo numérico ("1-12").
+fn ler_intervalo(texto: &str) -> Result<Vec<Coord>, ErroFen> {
+    let invalida = || ErroFen::CasaInvalida(texto.to_string());
+    if let Some((inicio, fim)) = texto.split_once('-') {
+        let inicio: u8 = inicio.trim().parse().map_err(|_| invalida())?;
+        let fim: u8 = fim.trim().parse().map_err(|_| invalida())?;
+        if inicio > fim {
+            return Err(invalida());
+        }
+        return (inicio..=fim).map(|n| Coord::de_numero(n).ok_or_else(invalida)).collect();
+    }
+    ler_casa(texto).map(|casa| vec![casa]).ok_or_else(invalida)
+}
+
+#[test]
+fn testar_fen() {
+    let partida = Partida::default();
+    let fen = partida.fen();
+    assert_eq!(
+        fen,
+        "W:W21,22,23,24,25,26,27,28,29,30,31,32:B1,2,3,4,5,6,7,8,9,10,11,12"
+    );
+    let lida = Partida::from_fen("W:W21-32:B1-12").unwrap();
+    assert_eq!(lida.get_tabuleiro(), partida.get_tabuleiro());
+
+    let lida = Partida::from_fen("B:WK5,c3:B9").unwrap();
+    assert_eq!(lida.fen(), "B:WK5,22:B9");
+    assert_eq!(lida.get_vez(), &Cor::Preto);
+
+    assert_eq!(Partida::from_fen("X:W1").err(), Some(ErroFen::VezInvalida("X".to_string())));
+    assert_eq!(Partida::from_fen("W:W30,30:B1").err(), Some(ErroFen::Posicao(ErroPosicao::CasaRepetida(30))));
+    // Posições impossíveis são recusadas, mas não a de uma partida terminada
+    assert_eq!(Partida::from_fen("W:W1-32:B").err(), Some(ErroFen::Posicao(ErroPosicao::PeaoNaCoroacao(1))));
+    assert_eq!(Partida::from_fen("W:W13-25:B1").err(), Some(ErroFen::Posicao(ErroPosicao::PedrasDemais(Cor::Branco, 13))));
+    assert_eq!(Partida::from_fen("W:W:B").err(), Some(ErroFen::Posicao(ErroPosicao::SemPedras(Cor::Branco))));
+    assert!(Partida::from_fen("W:W28:B").unwrap().encerrada());
+}
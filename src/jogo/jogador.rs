@@ -1,5 +0,0 @@
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum Jogador {
-    Branco,
-    Preto,
-}
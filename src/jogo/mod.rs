@@ -2,18 +2,27 @@ use itertools::Itertools;
 use std::fmt::Display;
 
 pub mod coord;
-mod jogador;
+pub mod cor;
 pub mod casa;
 pub mod pedra;
 pub mod jogada;
 pub mod resultado;
+pub mod notacao;
+pub mod fen;
+pub mod chave;
+pub mod posicao;
+pub mod estado;
+#[cfg(feature = "serde")]
+pub mod esquema;
 
 use self::coord::{c, Coord};
 use self::jogada::Jogada;
-use self::jogador::Jogador;
+use self::cor::Cor;
 use self::resultado::Resultado;
 use self::casa::Casa;
 use self::pedra::Pedra;
+use self::estado::{Estado, MotivoEmpate, MotivoVitoria};
+use crate::render::Renderizador;
 
 const TABULEIRO_INICIAL_CHARS: [[char; 8]; 8] = [
     ['.', 'p', '.', 'p', '.', 'p', '.', 'p'],
@@ -29,64 +38,35 @@ const TABULEIRO_INICIAL_CHARS: [[char; 8]; 8] = [
 #[derive(Debug, Clone)]
 pub struct Partida {
     tabuleiro: [[Casa; 8]; 8],
-    vez: Jogador,
+    vez: Cor,
     jogadas: Vec<Vec<Jogada>>,
     contador_empate: f32,
+    historico: Vec<Vec<Jogada>>,
+    tabuleiro_inicial: [[Casa; 8]; 8],
+    vez_inicial: Cor,
+    contador_inicial: f32,
+    estado: Estado,
 }
 
 impl Default for Partida {
     fn default() -> Self {
-        // Construir tabuleiro inicial
-        let mut tabuleiro = [[Casa::Vazia; 8]; 8];
-        for y in 0..tabuleiro.len() {
-            for x in 0..tabuleiro.len() {
-                match TABULEIRO_INICIAL_CHARS[y][x] {
-                    'p' => tabuleiro[y][x] = Casa::Ocupada(Pedra::Preta),
-                    'b' => tabuleiro[y][x] = Casa::Ocupada(Pedra::Branca),
-                    'P' => tabuleiro[y][x] = Casa::Ocupada(Pedra::DamaPreta),
-                    'B' => tabuleiro[y][x] = Casa::Ocupada(Pedra::DamaBranca),
-                    '.' => (),
-                    c => panic!("{c} não é uma peça válida!"),
-                }
-            }
-        }
-        // Começar o jogo com a peça branca
-        let mut p = Partida {
-            tabuleiro,
-            vez: Jogador::Branco,
-            jogadas: vec![],
-            contador_empate: 0.0,
-        };
-        p.jogadas = p._todas_jogadas_possiveis();
-        p
+        Partida::new(TABULEIRO_INICIAL_CHARS)
     }
 }
 
 impl Display for Partida {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut buffer = String::new();
-        buffer.push_str("   0  1  2  3  4  5  6  7\n");
-        for y in 0..8 {
-            buffer.push_str(&format!("{y} "));
-            for x in 0..8 {
-                match self.tabuleiro[y][x] {
-                    Casa::Ocupada(peça) => match peça {
-                        Pedra::Branca => buffer.push_str(" x "),
-                        Pedra::Preta => buffer.push_str(" o "),
-                        Pedra::DamaBranca => buffer.push_str(" X "),
-                        Pedra::DamaPreta => buffer.push_str(" O "),
-                    },
-                    Casa::Vazia => buffer.push_str(" . "),
-                }
-            }
-            buffer.push('\n');
-        }
-        write!(f, "{}", &buffer)
+        write!(f, "{}", Renderizador::default().renderizar(self))
     }
 }
 
 impl Partida {
+    /// Joga a sequência de índice `jogada`. Depois do fim da partida toda
+    /// jogada é recusada com `Resultado::Falha`.
     pub fn jogar(&mut self, jogada: usize) -> Resultado {
+        if self.encerrada() {
+            return Resultado::Falha;
+        }
         // Checar se a jogada escolhida é válida
         let todas_jogadas = self._todas_jogadas_possiveis();
         let jogada = todas_jogadas.get(jogada);
@@ -96,6 +76,7 @@ impl Partida {
 
         // Executar  a jogada
         let jogada = jogada.unwrap();
+        self.historico.push(jogada.clone());
         let pedra_usada = self.pedra(jogada[0].origem()).unwrap();
         for jogada in jogada {
             self.executar_jogada(*jogada);
@@ -119,13 +100,22 @@ impl Partida {
 
         // Checar se acabou o jogo
         if self.ganhou() {
+            self.finalizar(Estado::Vitoria(self.vez, MotivoVitoria::SemPedras));
             return Resultado::FimDoJogo(Some(self.vez));
         }
         if self.empatou() {
+            let motivo = if self.duas_damas() { MotivoEmpate::DuasDamas } else { MotivoEmpate::LancesDeDama };
+            self.finalizar(Estado::Empate(motivo));
             return Resultado::FimDoJogo(None);
         }
 
+        // Quem fica sem jogadas perde, mas fica com a vez
         self.passar_turno();
+        if self.jogadas.is_empty() {
+            let vencedor = self.vez.adversario();
+            self.finalizar(Estado::Vitoria(vencedor, MotivoVitoria::SemJogadas));
+            return Resultado::FimDoJogo(Some(vencedor));
+        }
         Resultado::Sucesso
     }
 
@@ -142,7 +132,7 @@ impl Partida {
         }
     }
 
-    pub fn get_vez(&self) -> &Jogador {
+    pub fn get_vez(&self) -> &Cor {
         &self.vez
     }
 
@@ -150,6 +140,8 @@ impl Partida {
         &self.tabuleiro
     }
 
+    /// Partida com as peças do tabuleiro e as brancas na vez. Não valida a
+    /// posição; para isso use `PosicaoBuilder`.
     pub fn new(tabuleiro: [[char; 8]; 8]) -> Self {
         // Construir tabuleiro inicial
         let mut tab = [[Casa::Vazia; 8]; 8];
@@ -166,16 +158,61 @@ impl Partida {
             }
         }
         // Começar o jogo com a peça branca
+        Partida::com_tabuleiro(tab, Cor::Branco)
+    }
+
+    pub(crate) fn com_tabuleiro(tabuleiro: [[Casa; 8]; 8], vez: Cor) -> Self {
         let mut p = Partida {
-            tabuleiro: tab,
-            vez: Jogador::Branco,
+            tabuleiro,
+            vez,
             jogadas: vec![],
             contador_empate: 0.0,
+            historico: vec![],
+            tabuleiro_inicial: tabuleiro,
+            vez_inicial: vez,
+            contador_inicial: 0.0,
+            estado: Estado::EmAndamento,
         };
         p.jogadas = p._todas_jogadas_possiveis();
+        p.finalizar(p.estado_inicial());
         p
     }
 
+    /// Sequências já jogadas desde a posição inicial, na ordem em que foram feitas.
+    pub fn historico(&self) -> &[Vec<Jogada>] {
+        &self.historico
+    }
+
+    /// Partida nova, na posição em que esta começou.
+    pub fn posicao_inicial(&self) -> Partida {
+        let mut partida = Partida::com_tabuleiro(self.tabuleiro_inicial, self.vez_inicial);
+        partida.com_contador_inicial(self.contador_inicial);
+        partida
+    }
+
+    // Contador de empate de uma posição montada sem histórico, que `desfazer`
+    // e `posicao_inicial` preservam
+    pub(crate) fn com_contador_inicial(&mut self, contador: f32) {
+        debug_assert!(self.historico.is_empty());
+        self.contador_inicial = contador;
+        self.contador_empate = contador;
+    }
+
+    /// Desfaz a última sequência jogada. Retorna false se não há o que desfazer.
+    pub fn desfazer(&mut self) -> bool {
+        let mut historico = self.historico.clone();
+        if historico.pop().is_none() {
+            return false;
+        }
+        let mut partida = self.posicao_inicial();
+        for sequencia in historico {
+            let indice = partida.jogadas.iter().position(|x| *x == sequencia).unwrap();
+            partida.jogar(indice);
+        }
+        *self = partida;
+        true
+    }
+
     fn executar_jogada(&mut self, jogada: Jogada) {
         match jogada {
             Jogada::Mover(origem, destino) => self.mover_sem_checar(origem, destino),
@@ -320,20 +357,17 @@ impl Partida {
     }
 
     fn é_a_vez_de(&self, peça: Pedra) -> bool {
-        if peça.é_branca() && self.vez == Jogador::Preto {
+        if peça.é_branca() && self.vez == Cor::Preto {
             return false;
         }
-        if peça.é_preta() && self.vez == Jogador::Branco {
+        if peça.é_preta() && self.vez == Cor::Branco {
             return false;
         }
         true
     }
 
     fn passar_turno(&mut self) {
-        self.vez = match self.vez {
-            Jogador::Branco => Jogador::Preto,
-            Jogador::Preto => Jogador::Branco,
-        };
+        self.vez = self.vez.adversario();
         self.jogadas = self._todas_jogadas_possiveis();
     }
 
@@ -413,7 +447,7 @@ impl Partida {
     }
 
     pub fn é_a_vez_do_branco(&self) -> bool {
-        self.vez == Jogador::Branco
+        self.vez == Cor::Branco
     }
 
     pub fn get_contador_empate(&self) -> f32 {
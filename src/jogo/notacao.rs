@@ -0,0 +1,197 @@
+use std::fmt::Display;
+
+use super::coord::{c, Coord};
+use super::jogada::Jogada;
+use super::Partida;
+
+// As casas escuras são numeradas de 1 a 32, da esquerda para a direita,
+// começando pela fileira do preto (y = 0). O branco começa em 21..=32.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErroNotacao {
+    Vazia,
+    CasaInvalida(String),
+    Ilegal(String),
+    Ambigua(String),
+}
+
+impl Display for ErroNotacao {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErroNotacao::Vazia => write!(f, "nenhuma jogada informada"),
+            ErroNotacao::CasaInvalida(casa) => write!(f, "'{casa}' não é uma casa válida"),
+            ErroNotacao::Ilegal(jogada) => write!(f, "'{jogada}' não é uma jogada possível"),
+            ErroNotacao::Ambigua(jogada) => write!(f, "'{jogada}' é ambígua, informe as casas intermediárias"),
+        }
+    }
+}
+
+impl std::error::Error for ErroNotacao {}
+
+impl Coord {
+    /// Número (1..=32) da casa escura, ou None para casas claras e fora do tabuleiro.
+    pub fn numero(self) -> Option<u8> {
+        if !self.é_valida() || (self.x + self.y) % 2 == 0 {
+            return None;
+        }
+        Some((self.y * 4 + self.x / 2 + 1) as u8)
+    }
+
+    pub fn de_numero(numero: u8) -> Option<Coord> {
+        if !(1..=32).contains(&numero) {
+            return None;
+        }
+        let i = (numero - 1) as i32;
+        let y = i / 4;
+        let x = (i % 4) * 2 + if y % 2 == 0 { 1 } else { 0 };
+        Some(c(x, y))
+    }
+
+    /// Nome algébrico da casa, com a1 no canto inferior esquerdo do branco.
+    pub fn algebrica(self) -> String {
+        format!("{}{}", (b'a' + self.x as u8) as char, 8 - self.y)
+    }
+
+    pub fn de_algebrica(texto: &str) -> Option<Coord> {
+        let bytes = texto.as_bytes();
+        if bytes.len() != 2 {
+            return None;
+        }
+        let coluna = bytes[0].to_ascii_lowercase();
+        if !(b'a'..=b'h').contains(&coluna) || !(b'1'..=b'8').contains(&bytes[1]) {
+            return None;
+        }
+        Some(c((coluna - b'a') as i32, 8 - (bytes[1] - b'0') as i32))
+    }
+}
+
+/// Lê uma casa escrita como número (1..=32) ou em notação algébrica (a1..h8).
+pub fn ler_casa(texto: &str) -> Option<Coord> {
+    let texto = texto.trim();
+    if let Ok(numero) = texto.parse::<u8>() {
+        return Coord::de_numero(numero);
+    }
+    Coord::de_algebrica(texto).filter(|coord| coord.numero().is_some())
+}
+
+/// Casas por onde a pedra passa: a origem seguida de cada destino.
+pub fn caminho(sequencia: &[Jogada]) -> Vec<Coord> {
+    let mut casas = vec![];
+    if let Some(primeira) = sequencia.first() {
+        casas.push(primeira.origem());
+    }
+    casas.extend(sequencia.iter().map(|jogada| jogada.destino()));
+    casas
+}
+
+fn é_captura(sequencia: &[Jogada]) -> bool {
+    matches!(sequencia.first(), Some(Jogada::Capturar(..)))
+}
+
+/// Notação numérica da sequência: "22-18" para movimentos e "22x13x6" para capturas.
+pub fn notacao(sequencia: &[Jogada]) -> String {
+    let separador = if é_captura(sequencia) { "x" } else { "-" };
+    caminho(sequencia)
+        .into_iter()
+        .map(|casa| casa.numero().unwrap().to_string())
+        .collect::<Vec<_>>()
+        .join(separador)
+}
+
+/// Notação algébrica da sequência: "c3-d4" para movimentos e "c3xe5xc7" para capturas.
+pub fn notacao_algebrica(sequencia: &[Jogada]) -> String {
+    let separador = if é_captura(sequencia) { "x" } else { "-" };
+    caminho(sequencia)
+        .into_iter()
+        .map(Coord::algebrica)
+        .collect::<Vec<_>>()
+        .join(separador)
+}
+
+impl Partida {
+    /// Índice da jogada possível descrita por `texto`, para ser passado a `jogar`.
+    ///
+    /// Aceita casas numéricas ou algébricas separadas por '-', 'x' ou ':'. Numa captura
+    /// basta informar origem e destino final, a não ser que haja mais de um caminho.
+    pub fn encontrar_jogada(&self, texto: &str) -> Result<usize, ErroNotacao> {
+        let texto = texto.trim();
+        let partes: Vec<&str> = texto
+            .split(['-', 'x', 'X', ':'])
+            .filter(|parte| !parte.is_empty())
+            .collect();
+        if partes.is_empty() {
+            return Err(ErroNotacao::Vazia);
+        }
+        let mut casas = vec![];
+        for parte in partes {
+            match ler_casa(parte) {
+                Some(casa) => casas.push(casa),
+                None => return Err(ErroNotacao::CasaInvalida(parte.to_string())),
+            }
+        }
+        if casas.len() < 2 {
+            return Err(ErroNotacao::Ilegal(texto.to_string()));
+        }
+
+        let candidatas: Vec<usize> = self
+            .todas_jogadas_possiveis()
+            .iter()
+            .enumerate()
+            .filter(|(_, sequencia)| {
+                corresponde(sequencia, &casas) && sequencia.last().map(Jogada::destino) == casas.last().copied()
+            })
+            .map(|(i, _)| i)
+            .collect();
+        match candidatas[..] {
+            [] => Err(ErroNotacao::Ilegal(texto.to_string())),
+            [indice] => Ok(indice),
+            _ => Err(ErroNotacao::Ambigua(texto.to_string())),
+        }
+    }
+}
+
+/// Se as casas são a origem da sequência seguida de alguns dos seus pousos,
+/// na ordem. A notação exige ainda que a última casa seja o destino final; a
+/// seleção de casas na interface vai casando enquanto as casas são escolhidas.
+pub fn corresponde(sequencia: &[Jogada], casas: &[Coord]) -> bool {
+    let caminho = caminho(sequencia);
+    if casas.is_empty() || caminho.first() != casas.first() {
+        return false;
+    }
+    let mut pousos = caminho[1..].iter();
+    casas[1..].iter().all(|casa| pousos.any(|pouso| pouso == casa))
+}
+
+#[test]
+fn testar_numeracao() {
+    assert_eq!(c(1, 0).numero(), Some(1));
+    assert_eq!(c(7, 0).numero(), Some(4));
+    assert_eq!(c(0, 1).numero(), Some(5));
+    assert_eq!(c(6, 7).numero(), Some(32));
+    assert_eq!(c(0, 0).numero(), None);
+    for numero in 1..=32 {
+        assert_eq!(Coord::de_numero(numero).unwrap().numero(), Some(numero));
+    }
+    assert_eq!(c(0, 7).algebrica(), "a1");
+    assert_eq!(ler_casa("c3"), Some(c(2, 5)));
+    assert_eq!(ler_casa("a2"), None);
+}
+
+#[test]
+fn testar_encontrar_jogada() {
+    let partida = Partida::default();
+    let indice = partida.encontrar_jogada("22-18").unwrap();
+    assert_eq!(notacao(&partida.todas_jogadas_possiveis()[indice]), "22-18");
+    assert_eq!(partida.encontrar_jogada("c3-d4"), Ok(indice));
+    assert_eq!(notacao_algebrica(&partida.todas_jogadas_possiveis()[indice]), "c3-d4");
+    assert_eq!(partida.encontrar_jogada("22-15"), Err(ErroNotacao::Ilegal("22-15".to_string())));
+    assert_eq!(partida.encontrar_jogada("40-18"), Err(ErroNotacao::CasaInvalida("40".to_string())));
+
+    // A seleção pela interface casa com o começo do caminho
+    let posicao = Partida::from_fen("W:W27:B24,16").unwrap();
+    let captura = &posicao.todas_jogadas_possiveis()[0];
+    assert_eq!(notacao(captura), "27x20x11");
+    let casas = |numeros: &[u8]| numeros.iter().map(|&numero| Coord::de_numero(numero).unwrap()).collect::<Vec<_>>();
+    assert!(corresponde(captura, &casas(&[27])) && corresponde(captura, &casas(&[27, 20])));
+    assert!(corresponde(captura, &casas(&[27, 11])) && !corresponde(captura, &casas(&[27, 11, 20])));
+}
@@ -0,0 +1,246 @@
+use std::fmt::Display;
+
+use super::casa::Casa;
+use super::coord::Coord;
+use super::cor::Cor;
+use super::pedra::Pedra;
+use super::Partida;
+
+// Montagem de posições com validação, para editores e importadores. Ao
+// contrário de `Partida::new`, nada de pedras em casas claras, peões na linha
+// onde já seriam damas ou mais de 12 pedras de uma cor.
+
+const MAXIMO_DE_PEDRAS: usize = 12;
+// Com o contador neste valor a partida já estaria empatada
+const LIMITE_DO_CONTADOR: f32 = 20.0;
+const LIMITE_DAS_DAMAS: f32 = 5.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErroPosicao {
+    /// Número fora de 1 a 32.
+    CasaInexistente(u8),
+    /// Coordenada fora do tabuleiro ou numa casa clara.
+    CasaClara(Coord),
+    CasaRepetida(u8),
+    /// Peão na última linha do adversário, onde já teria virado dama.
+    PeaoNaCoroacao(u8),
+    PedrasDemais(Cor, usize),
+    SemPedras(Cor),
+    /// O contador de empate anda de meio em meio e vai de 0 a 20.
+    ContadorInvalido(f32),
+    /// Com só uma dama de cada lado a partida empata quando o contador chega a 5.
+    ContadorDeDamas(f32),
+    /// Caractere do tabuleiro que não é b, p, B, P ou '.'.
+    CaractereInvalido(char),
+}
+
+impl Display for ErroPosicao {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cor = |jogador: &Cor| if *jogador == Cor::Branco { "brancas" } else { "pretas" };
+        match self {
+            ErroPosicao::CasaInexistente(numero) => write!(f, "não existe a casa {numero}; use de 1 a 32"),
+            ErroPosicao::CasaClara(coord) => write!(f, "{coord:?} não é uma casa escura do tabuleiro"),
+            ErroPosicao::CasaRepetida(numero) => write!(f, "a casa {numero} recebeu mais de uma pedra"),
+            ErroPosicao::PeaoNaCoroacao(numero) => write!(f, "o peão na casa {numero} já teria virado dama"),
+            ErroPosicao::PedrasDemais(jogador, total) => {
+                write!(f, "as {} têm {total} pedras; o máximo é {MAXIMO_DE_PEDRAS}", cor(jogador))
+            }
+            ErroPosicao::SemPedras(jogador) => write!(f, "as {} não têm pedras", cor(jogador)),
+            ErroPosicao::ContadorInvalido(valor) => {
+                write!(f, "contador de empate {valor} inválido; use múltiplos de 0,5 menores que {LIMITE_DO_CONTADOR}")
+            }
+            ErroPosicao::ContadorDeDamas(valor) => {
+                write!(f, "contador de empate {valor} inválido com dama contra dama; use menos que {LIMITE_DAS_DAMAS}")
+            }
+            ErroPosicao::CaractereInvalido(ch) => write!(f, "'{ch}' não é uma peça válida"),
+        }
+    }
+}
+
+impl std::error::Error for ErroPosicao {}
+
+/// Monta uma posição peça por peça e só entrega a `Partida` se ela for válida.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PosicaoBuilder {
+    pedras: Vec<(Coord, Pedra)>,
+    vez: Cor,
+    contador_empate: f32,
+    // Aceita um lado sem pedras, como na posição final de uma partida
+    posicao_final: bool,
+    // Erro encontrado ao montar, entregue por `construir`
+    erro: Option<ErroPosicao>,
+}
+
+impl Default for PosicaoBuilder {
+    fn default() -> Self {
+        PosicaoBuilder { pedras: vec![], vez: Cor::Branco, contador_empate: 0.0, posicao_final: false, erro: None }
+    }
+}
+
+impl PosicaoBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Começa com as peças de um tabuleiro no formato de `Partida::new`.
+    pub fn de_tabuleiro(tabuleiro: [[char; 8]; 8]) -> Self {
+        let mut builder = Self::default();
+        for (y, linha) in tabuleiro.iter().enumerate() {
+            for (x, &ch) in linha.iter().enumerate() {
+                let pedra = match ch {
+                    'b' => Pedra::Branca,
+                    'p' => Pedra::Preta,
+                    'B' => Pedra::DamaBranca,
+                    'P' => Pedra::DamaPreta,
+                    '.' => continue,
+                    ch => {
+                        builder.erro.get_or_insert(ErroPosicao::CaractereInvalido(ch));
+                        continue;
+                    }
+                };
+                builder = builder.pedra_em(Coord { x: x as i32, y: y as i32 }, pedra);
+            }
+        }
+        builder
+    }
+
+    /// Começa com as peças de um tabuleiro de `Casa`, como o dos protocolos.
+    pub fn de_casas(tabuleiro: &[[Casa; 8]; 8]) -> Self {
+        let mut builder = Self::default();
+        for (y, linha) in tabuleiro.iter().enumerate() {
+            for (x, casa) in linha.iter().enumerate() {
+                if let Some(pedra) = casa.pedra() {
+                    builder = builder.pedra_em(Coord { x: x as i32, y: y as i32 }, pedra);
+                }
+            }
+        }
+        builder
+    }
+
+    /// Põe uma pedra na casa de número 1 a 32.
+    pub fn pedra(mut self, numero: u8, pedra: Pedra) -> Self {
+        match Coord::de_numero(numero) {
+            Some(coord) => self.pedras.push((coord, pedra)),
+            None => {
+                self.erro.get_or_insert(ErroPosicao::CasaInexistente(numero));
+            }
+        }
+        self
+    }
+
+    pub fn pedras(self, numeros: &[u8], pedra: Pedra) -> Self {
+        numeros.iter().fold(self, |builder, &numero| builder.pedra(numero, pedra))
+    }
+
+    /// Põe uma pedra pela coordenada do tabuleiro, como fazem os editores.
+    pub fn pedra_em(mut self, coord: Coord, pedra: Pedra) -> Self {
+        match coord.numero() {
+            Some(_) => self.pedras.push((coord, pedra)),
+            None => {
+                self.erro.get_or_insert(ErroPosicao::CasaClara(coord));
+            }
+        }
+        self
+    }
+
+    pub fn vez(mut self, vez: Cor) -> Self {
+        self.vez = vez;
+        self
+    }
+
+    /// Lances seguidos só com damas e sem captura, contados como em `Partida`.
+    pub fn contador_empate(mut self, valor: f32) -> Self {
+        self.contador_empate = valor;
+        self
+    }
+
+    // Para o FEN, que também registra partidas já terminadas
+    pub(super) fn posicao_final(mut self) -> Self {
+        self.posicao_final = true;
+        self
+    }
+
+    pub fn construir(self) -> Result<Partida, ErroPosicao> {
+        if let Some(erro) = self.erro {
+            return Err(erro);
+        }
+        let contador = self.contador_empate;
+        if !(0.0..LIMITE_DO_CONTADOR).contains(&contador) || (contador * 2.0).fract() != 0.0 {
+            return Err(ErroPosicao::ContadorInvalido(contador));
+        }
+
+        let mut tabuleiro = [[Casa::Vazia; 8]; 8];
+        for &(coord, pedra) in &self.pedras {
+            let numero = coord.numero().unwrap();
+            let casa = &mut tabuleiro[coord.y as usize][coord.x as usize];
+            if !casa.é_vazia() {
+                return Err(ErroPosicao::CasaRepetida(numero));
+            }
+            // As brancas coroam na linha de cima (y = 0), as pretas na de baixo
+            let coroacao = if pedra.é_branca() { 0 } else { 7 };
+            if !pedra.é_dama() && coord.y == coroacao {
+                return Err(ErroPosicao::PeaoNaCoroacao(numero));
+            }
+            *casa = Casa::Ocupada(pedra);
+        }
+        for jogador in [Cor::Branco, Cor::Preto] {
+            let total = self.pedras.iter().filter(|(_, pedra)| pedra.é_branca() == (jogador == Cor::Branco)).count();
+            if total == 0 && (!self.posicao_final || self.pedras.is_empty()) {
+                return Err(ErroPosicao::SemPedras(jogador));
+            }
+            if total > MAXIMO_DE_PEDRAS {
+                return Err(ErroPosicao::PedrasDemais(jogador, total));
+            }
+        }
+
+        let mut partida = Partida::com_tabuleiro(tabuleiro, self.vez);
+        if partida.duas_damas() && contador >= LIMITE_DAS_DAMAS {
+            return Err(ErroPosicao::ContadorDeDamas(contador));
+        }
+        partida.com_contador_inicial(contador);
+        Ok(partida)
+    }
+}
+
+#[test]
+fn testar_posicao_builder() {
+    let partida = PosicaoBuilder::new()
+        .pedras(&[21, 22], Pedra::Branca)
+        .pedra(1, Pedra::DamaBranca)
+        .pedra(9, Pedra::Preta)
+        .vez(Cor::Preto)
+        .contador_empate(1.5)
+        .construir()
+        .unwrap();
+    assert_eq!(partida.fen(), "B:WK1,21,22:B9");
+    assert_eq!(partida.get_contador_empate(), 1.5);
+
+    // O contador da posição montada sobrevive a desfazer
+    let mut damas = PosicaoBuilder::new().pedras(&[1, 2], Pedra::DamaBranca).pedra(32, Pedra::DamaPreta).contador_empate(3.0).construir().unwrap();
+    damas.jogar(damas.encontrar_jogada("1-5").unwrap());
+    assert_eq!(damas.get_contador_empate(), 3.5);
+    assert!(damas.desfazer());
+    assert_eq!(damas.get_contador_empate(), 3.0);
+    assert_eq!(damas.posicao_inicial().get_contador_empate(), 3.0);
+
+    let inicial = PosicaoBuilder::de_tabuleiro(super::TABULEIRO_INICIAL_CHARS).construir().unwrap();
+    assert_eq!(inicial.fen(), Partida::default().fen());
+
+    let erro = |builder: PosicaoBuilder| builder.pedra(30, Pedra::Branca).pedra(3, Pedra::Preta).construir().unwrap_err();
+    assert_eq!(erro(PosicaoBuilder::new().pedra(33, Pedra::Preta)), ErroPosicao::CasaInexistente(33));
+    assert_eq!(erro(PosicaoBuilder::new().pedra_em(Coord { x: 0, y: 0 }, Pedra::Preta)), ErroPosicao::CasaClara(Coord { x: 0, y: 0 }));
+    assert_eq!(erro(PosicaoBuilder::new().pedra(30, Pedra::DamaPreta)), ErroPosicao::CasaRepetida(30));
+    assert_eq!(erro(PosicaoBuilder::new().pedra(2, Pedra::Branca)), ErroPosicao::PeaoNaCoroacao(2));
+    assert_eq!(erro(PosicaoBuilder::new().pedra(31, Pedra::Preta)), ErroPosicao::PeaoNaCoroacao(31));
+    assert_eq!(erro(PosicaoBuilder::new().pedras(&(5..18).collect::<Vec<_>>(), Pedra::DamaPreta)), ErroPosicao::PedrasDemais(Cor::Preto, 14));
+    assert_eq!(erro(PosicaoBuilder::new().contador_empate(0.3)), ErroPosicao::ContadorInvalido(0.3));
+    assert_eq!(erro(PosicaoBuilder::new().contador_empate(20.0)), ErroPosicao::ContadorInvalido(20.0));
+    assert_eq!(PosicaoBuilder::new().pedra(30, Pedra::Branca).construir().unwrap_err(), ErroPosicao::SemPedras(Cor::Preto));
+    let dama_contra_dama = PosicaoBuilder::new().pedra(1, Pedra::DamaBranca).pedra(32, Pedra::DamaPreta);
+    assert_eq!(dama_contra_dama.clone().contador_empate(10.0).construir().unwrap_err(), ErroPosicao::ContadorDeDamas(10.0));
+    assert!(dama_contra_dama.contador_empate(4.5).construir().is_ok());
+
+    let mut tabuleiro = [['.'; 8]; 8];
+    tabuleiro[0][1] = 'x';
+    assert_eq!(PosicaoBuilder::de_tabuleiro(tabuleiro).construir().unwrap_err(), ErroPosicao::CaractereInvalido('x'));
+}
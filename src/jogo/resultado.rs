@@ -1,8 +1,10 @@
-use super::jogador::Jogador;
+use super::cor::Cor;
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Resultado {
     Falha,          // Jogada invalida. Não passa o turno nem mexe no tabuleiro
     Sucesso,        // Jogada válida e passa o turno. Não tem mais possiveis captura
-    FimDoJogo(Option<Jogador>), // Jogada válida e fim do jogo. Retorna o ganhador
+    FimDoJogo(Option<Cor>), // Jogada válida e fim do jogo. Retorna o ganhador
 }
\ No newline at end of file
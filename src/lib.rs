@@ -5,4 +5,32 @@ pub use jogo::jogada::Jogada;
 pub use jogo::resultado::Resultado;
 pub use jogo::casa::Casa;
 pub use jogo::pedra::Pedra;
-pub use jogo::coord::Coord;
\ No newline at end of file
+pub use jogo::coord::Coord;
+pub use jogo::cor::Cor;
+pub use sessao::Jogador;
+pub use jogo::notacao;
+pub use jogo::fen::ErroFen;
+pub use jogo::chave::Material;
+pub use jogo::posicao::{ErroPosicao, PosicaoBuilder};
+pub use jogo::estado::{Estado, MotivoEmpate, MotivoVitoria, PartidaEncerrada};
+#[cfg(feature = "serde")]
+pub use jogo::esquema::VERSAO_ESQUEMA;
+
+pub mod pdn;
+pub mod bot;
+pub mod render;
+pub mod diagrama;
+pub mod animacao;
+pub mod rede;
+pub mod hub;
+pub mod confronto;
+pub mod torneio;
+pub mod ranking;
+pub mod acervo;
+pub mod explorador;
+pub mod aberturas;
+pub mod golpes;
+pub mod exercicios;
+pub mod problemas;
+pub mod anotador;
+pub mod sessao;
@@ -1,44 +1,443 @@
-#![allow(unused)]
+mod cmd;
+
+use damas::bot::{Analise, Bot};
+use damas::notacao::notacao;
+use damas::pdn::{self, JogoPdn};
 use damas::Partida;
-use damas::Jogada;
-use damas::Resultado;
+use damas::sessao::Sessao;
+use damas::ranking::Ranking;
+use damas::render::{Coordenadas, Estilo, Renderizador};
+use damas::{Cor, Estado, Jogada, Jogador, MotivoVitoria};
 
+use std::env;
+use std::fs;
+use std::iter::{Peekable, Skip};
 use std::io::Write;
 use std::io;
+use std::mem;
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+const USO: &str = "\
+Uso: damas_cmd [opções]
+     damas_cmd diagrama <fen> [opções]
+     damas_cmd animacao <arquivo.pdn> -o <saida> [opções]
+     damas_cmd conectar <endereço> [nome]
+     damas_cmd dxp iniciar|seguir <endereço> [opções]
+     damas_cmd ranking [comando] [opções]
+     damas_cmd acervo importar|buscar|exportar|mostrar [opções]
+     damas_cmd explorar [opções]
+     damas_cmd puzzles <arquivo.pdn>... [opções]
+     damas_cmd problema <fen> [opções]
+     damas_cmd anotar <arquivo.pdn> [-o <saida.pdn>] [opções]
+  --branco humano|bot   quem joga com as brancas (padrão: humano)
+  --preto humano|bot    quem joga com as pretas (padrão: humano)
+  --profundidade <n>    profundidade máxima da busca do bot (padrão: 6)
+  --tempo <ms>          tempo máximo por jogada do bot
+  --atraso <ms>         pausa antes de cada jogada quando dois bots se enfrentam (padrão: 1000)
+  --estilo <estilo>     desenho do tabuleiro: ascii, unicode ou ansi (padrão: ascii)
+  --casas <casas>       rótulos das casas: algebrica, numeros ou indices (padrão: algebrica)
+  --ranking <arquivo>   registra o resultado no ranking ao fim de cada partida
+  --nomes <b>,<p>       nomes dos jogadores no ranking";
+
+const AJUDA: &str = "\
+Jogadas: digite a notação (22-18, 22x13, c3-d4) ou o número da lista.
+Comandos:
+  novo               começa uma partida nova
+  desfazer           volta a última jogada
+  salvar <arquivo>   grava a partida em PDN
+  carregar <arquivo> lê uma partida em PDN
+  virar              inverte o tabuleiro
+  fen [<fen>]        mostra o FEN da posição ou carrega uma posição
+  historico          lista os comandos digitados (repita com !n ou !!)
+  ajuda              mostra esta mensagem
+  sair               encerra o programa";
 
 fn clear_terminal() {
     print!("{esc}[2J{esc}[1;1H", esc = 27 as char);
 }
 
-fn input(msg: &str) -> usize {
+fn input(msg: &str) -> Option<String> {
     print!("{msg} ");
-    io::stdout().flush();
+    io::stdout().flush().ok();
     let mut buffer = String::new();
-    io::stdin().read_line(&mut buffer).expect("failed to read stdin");
-    buffer.trim().parse().unwrap()
+    match io::stdin().read_line(&mut buffer) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(buffer.trim().to_string()),
+    }
 }
 
-fn print_lista_de_jogadas(jogadas: Vec<Vec<Jogada>>) {
+fn print_lista_de_jogadas(jogadas: &[Vec<Jogada>]) {
     for (i, jogada) in jogadas.iter().enumerate() {
-        print!("{i}: {:?}   ", jogada);
-        if (i+1) % 3 == 0 { println!(); }
+        print!("{i}: {:<12}", notacao(jogada));
+        if (i+1) % 4 == 0 { println!(); }
     }
     println!();
 }
 
+struct Opcoes {
+    branco: Option<Bot>,
+    preto: Option<Bot>,
+    atraso: Duration,
+    renderizador: Renderizador,
+    ranking: Option<(String, String, String)>,
+}
+
+fn ler_opcoes(mut args: impl Iterator<Item = String>) -> Result<Opcoes, String> {
+    let mut bot = Bot::default();
+    let (mut branco_bot, mut preto_bot) = (false, false);
+    let mut atraso = Duration::from_millis(1000);
+    let mut renderizador = Renderizador::new()
+        .coordenadas(Coordenadas::Algebrica)
+        .ultima_jogada(true)
+        .capturas(true);
+    let mut ranking = None;
+    let mut nomes = None;
+    while let Some(arg) = args.next() {
+        if arg == "--ajuda" || arg == "-h" || arg == "--help" {
+            println!("{USO}");
+            process::exit(0);
+        }
+        let valor = args.next().ok_or(format!("{arg} precisa de um valor"))?;
+        let numero = || valor.parse::<u64>().map_err(|_| format!("{arg}: '{valor}' não é um número"));
+        match arg.as_str() {
+            "--branco" | "--preto" => {
+                let é_bot = match valor.as_str() {
+                    "humano" => false,
+                    "bot" => true,
+                    _ => return Err(format!("{arg}: use 'humano' ou 'bot'")),
+                };
+                if arg == "--branco" { branco_bot = é_bot } else { preto_bot = é_bot }
+            }
+            "--profundidade" => bot.profundidade = numero()? as u32,
+            "--tempo" => bot.tempo = Some(Duration::from_millis(numero()?)),
+            "--atraso" => atraso = Duration::from_millis(numero()?),
+            "--estilo" => {
+                renderizador.estilo = match valor.as_str() {
+                    "ascii" => Estilo::Ascii,
+                    "unicode" => Estilo::Unicode,
+                    "ansi" => Estilo::Ansi,
+                    _ => return Err(format!("{arg}: use 'ascii', 'unicode' ou 'ansi'")),
+                }
+            }
+            "--casas" => {
+                renderizador.coordenadas = match valor.as_str() {
+                    "algebrica" => Coordenadas::Algebrica,
+                    "numeros" => Coordenadas::Numeros,
+                    "indices" => Coordenadas::Indices,
+                    _ => return Err(format!("{arg}: use 'algebrica', 'numeros' ou 'indices'")),
+                }
+            }
+            "--ranking" => ranking = Some(valor),
+            "--nomes" => {
+                let (branco, preto) = valor.split_once(',').ok_or(format!("{arg}: use <branco>,<preto>"))?;
+                nomes = Some((branco.trim().to_string(), preto.trim().to_string()));
+            }
+            _ => return Err(format!("opção desconhecida: {arg}")),
+        }
+    }
+    let ranking = match (ranking, nomes) {
+        (Some(arquivo), Some((branco, preto))) => Some((arquivo, branco, preto)),
+        (None, None) => None,
+        _ => return Err("--ranking e --nomes devem ser usados juntos".to_string()),
+    };
+    Ok(Opcoes {
+        branco: branco_bot.then_some(bot),
+        preto: preto_bot.then_some(bot),
+        atraso,
+        renderizador,
+        ranking,
+    })
+}
+
+// Um lado da partida no REPL: o bot escolhe sozinho, o humano joga o lance
+// que foi lido da linha de comando.
+struct Lado {
+    bot: Option<Bot>,
+    lance: Vec<Jogada>,
+    analise: Option<Analise>,
+}
+
+impl Lado {
+    fn new(bot: Option<Bot>) -> Self {
+        Lado { bot, lance: vec![], analise: None }
+    }
+}
+
+impl Jogador for Lado {
+    fn escolher(&mut self, partida: &Partida) -> Vec<Jogada> {
+        let Some(bot) = self.bot else { return mem::take(&mut self.lance) };
+        self.analise = bot.analisar(partida);
+        self.analise.as_ref().map_or(vec![], |analise| partida.todas_jogadas_possiveis()[analise.indice].clone())
+    }
+}
+
+struct Repl {
+    partida: Partida,
+    branco: Lado,
+    preto: Lado,
+    opcoes: Opcoes,
+    virado: bool,
+    comandos: Vec<String>,
+    fim: Option<String>,
+    // Desfazer e chegar de novo ao fim não registra a mesma partida outra vez
+    registrada: bool,
+    mensagem: String,
+}
+
+impl Repl {
+    fn new(opcoes: Opcoes) -> Self {
+        Repl {
+            partida: Partida::default(),
+            // Quem joga de pretas contra o bot vê o tabuleiro do seu lado
+            virado: opcoes.preto.is_none() && opcoes.branco.is_some(),
+            branco: Lado::new(opcoes.branco),
+            preto: Lado::new(opcoes.preto),
+            opcoes,
+            comandos: vec![],
+            fim: None,
+            registrada: false,
+            mensagem: "Digite 'ajuda' para ver os comandos.".to_string(),
+        }
+    }
+
+    fn mostrar(&self) {
+        clear_terminal();
+        print!("{}", self.opcoes.renderizador.virado(self.virado).renderizar(&self.partida));
+        match &self.fim {
+            Some(fim) => println!("{fim}"),
+            None => print_lista_de_jogadas(self.partida.todas_jogadas_possiveis()),
+        }
+        if !self.mensagem.is_empty() {
+            println!("{}", self.mensagem);
+        }
+    }
+
+    fn trocar_partida(&mut self, partida: Partida) {
+        self.partida = partida;
+        self.registrada = false;
+        self.checar_fim();
+    }
+
+    fn checar_fim(&mut self) {
+        self.fim = match self.partida.estado() {
+            Estado::EmAndamento => None,
+            Estado::Vitoria(ganhador, MotivoVitoria::SemJogadas) => {
+                Some(format!("{:?} não tem jogadas e perdeu!", ganhador.adversario()))
+            }
+            Estado::Vitoria(ganhador, _) => Some(format!("{:?} ganhou!!!", ganhador)),
+            Estado::Empate(_) => Some("Empate!".to_string()),
+        };
+    }
+
+    // Retorna Ok(false) quando o programa deve terminar
+    fn executar(&mut self, linha: &str) -> Result<bool, String> {
+        let linha = match linha {
+            "!!" => self.comandos.last().cloned().ok_or("o histórico está vazio")?,
+            _ => match linha.strip_prefix('!') {
+                Some(n) => {
+                    let n: usize = n.parse().map_err(|_| "use !n com o número do comando")?;
+                    self.comandos
+                        .get(n.wrapping_sub(1))
+                        .cloned()
+                        .ok_or(format!("não há comando {n} no histórico"))?
+                }
+                None => linha.to_string(),
+            },
+        };
+        if linha.is_empty() {
+            return Ok(true);
+        }
+        self.comandos.push(linha.clone());
+        self.mensagem.clear();
+
+        let (comando, argumento) = match linha.split_once(char::is_whitespace) {
+            Some((comando, argumento)) => (comando, argumento.trim()),
+            None => (linha.as_str(), ""),
+        };
+        match comando {
+            "sair" => return Ok(false),
+            "ajuda" => self.mensagem = AJUDA.to_string(),
+            "novo" => self.trocar_partida(Partida::default()),
+            "virar" => self.virado = !self.virado,
+            "desfazer" => {
+                if !self.partida.desfazer() {
+                    return Err("não há jogadas para desfazer".to_string());
+                }
+                // Contra o bot, volta até a vez do humano
+                if self.tem_humano() {
+                    while self.bot_da_vez().is_some() && self.partida.desfazer() {}
+                }
+                self.checar_fim();
+            }
+            "historico" => {
+                self.mensagem = self
+                    .comandos
+                    .iter()
+                    .enumerate()
+                    .map(|(i, comando)| format!("{:>3}  {comando}", i + 1))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+            }
+            "fen" if argumento.is_empty() => self.mensagem = self.partida.fen(),
+            "fen" => {
+                let partida = Partida::from_fen(argumento).map_err(|erro| erro.to_string())?;
+                self.trocar_partida(partida);
+            }
+            "salvar" | "carregar" if argumento.is_empty() => {
+                return Err(format!("uso: {comando} <arquivo>"));
+            }
+            "salvar" => {
+                let texto = JogoPdn::de_partida(&self.partida).to_string();
+                fs::write(argumento, texto).map_err(|erro| format!("não foi possível salvar: {erro}"))?;
+                self.mensagem = format!("Partida salva em {argumento}");
+            }
+            "carregar" => {
+                let partida = carregar(argumento)?;
+                self.trocar_partida(partida);
+                self.mensagem = format!("Partida carregada de {argumento}");
+            }
+            _ => self.jogar(&linha)?,
+        }
+        Ok(true)
+    }
+
+    fn jogar(&mut self, texto: &str) -> Result<(), String> {
+        if self.fim.is_some() {
+            return Err("o jogo acabou, use 'novo' ou 'desfazer'".to_string());
+        }
+        let indice = match texto.parse::<usize>() {
+            Ok(indice) if indice < self.partida.todas_jogadas_possiveis().len() => indice,
+            Ok(indice) => return Err(format!("não há jogada {indice} na lista")),
+            Err(_) => self
+                .partida
+                .encontrar_jogada(texto)
+                .map_err(|erro| format!("{erro} (digite 'ajuda' para ver os comandos)"))?,
+        };
+        let lance = self.partida.todas_jogadas_possiveis()[indice].clone();
+        self.jogar_vez(lance)
+    }
+
+    // Joga a vez pela sessão: o lado do bot escolhe sozinho, o humano joga `lance`
+    fn jogar_vez(&mut self, lance: Vec<Jogada>) -> Result<(), String> {
+        match self.partida.get_vez() {
+            Cor::Branco => self.branco.lance = lance,
+            Cor::Preto => self.preto.lance = lance,
+        }
+        let mut sessao = Sessao::new(&mut self.branco, &mut self.preto).com_partida(mem::take(&mut self.partida));
+        let estado = sessao.jogar_lance();
+        self.partida = sessao.into_partida();
+        if estado.map_err(|erro| erro.to_string())?.encerrada() {
+            self.checar_fim();
+            self.registrar_no_ranking(self.partida.estado().vencedor())?;
+        }
+        Ok(())
+    }
+
+    fn registrar_no_ranking(&mut self, ganhador: Option<Cor>) -> Result<(), String> {
+        let Some((arquivo, branco, preto)) = &self.opcoes.ranking else { return Ok(()) };
+        if self.registrada {
+            return Ok(());
+        }
+        let mut ranking = Ranking::abrir(arquivo).map_err(|erro| format!("{arquivo}: {erro}"))?;
+        let registro = ranking.registrar(branco, preto, ganhador).map_err(|erro| erro.to_string())?;
+        let (a, b) = registro.variacao_elo;
+        let texto = format!("Ranking: {branco} {a:+.1}, {preto} {b:+.1}");
+        ranking.salvar(arquivo).map_err(|erro| format!("{arquivo}: {erro}"))?;
+        self.registrada = true;
+        if let Some(fim) = &mut self.fim {
+            fim.push('\n');
+            fim.push_str(&texto);
+        }
+        Ok(())
+    }
+
+    fn tem_humano(&self) -> bool {
+        self.opcoes.branco.is_none() || self.opcoes.preto.is_none()
+    }
+
+    fn bot_da_vez(&self) -> Option<Bot> {
+        match self.partida.get_vez() {
+            Cor::Branco => self.opcoes.branco,
+            Cor::Preto => self.opcoes.preto,
+        }
+    }
+
+    fn jogar_bot(&mut self) -> Result<(), String> {
+        let vez = *self.partida.get_vez();
+        self.jogar_vez(vec![])?;
+        let lado = if vez == Cor::Branco { &mut self.branco } else { &mut self.preto };
+        let analise = lado.analise.take().ok_or("o bot não tem jogadas")?;
+        let jogada = self.partida.historico().last().map(|sequencia| notacao(sequencia)).unwrap_or_default();
+        self.mensagem = format!(
+            "Bot ({:?}) jogou {jogada}  [avaliação {:+}, profundidade {}, {} nós]",
+            vez, analise.avaliacao, analise.profundidade, analise.nos
+        );
+        Ok(())
+    }
+}
+
+fn carregar(arquivo: &str) -> Result<Partida, String> {
+    let texto = fs::read_to_string(arquivo).map_err(|erro| format!("não foi possível ler {arquivo}: {erro}"))?;
+    let jogos = pdn::ler(&texto).map_err(|erro| erro.to_string())?;
+    let jogo = jogos.first().ok_or_else(|| format!("{arquivo} não contém nenhuma partida"))?;
+    jogo.partida().map_err(|erro| erro.to_string())
+}
+
+type Subcomando = fn(Peekable<Skip<env::Args>>) -> Result<(), String>;
+
 fn main() {
-    let mut jogo = Partida::default();
+    let mut args = env::args().skip(1).peekable();
+    let subcomando: Option<(Subcomando, &str)> = match args.peek().map(String::as_str) {
+        Some("diagrama") => Some((cmd::diagrama::executar, cmd::diagrama::USO)),
+        Some("animacao") => Some((cmd::animacao::executar, cmd::animacao::USO)),
+        Some("conectar") => Some((cmd::cliente::executar, cmd::cliente::USO)),
+        Some("dxp") => Some((cmd::dxp::executar, cmd::dxp::USO)),
+        Some("ranking") => Some((cmd::ranking::executar, cmd::ranking::USO)),
+        Some("acervo") => Some((cmd::acervo::executar, cmd::acervo::USO)),
+        Some("explorar") => Some((cmd::explorar::executar, cmd::explorar::USO)),
+        Some("puzzles") => Some((cmd::puzzles::executar, cmd::puzzles::USO)),
+        Some("problema") => Some((cmd::problema::executar, cmd::problema::USO)),
+        Some("anotar") => Some((cmd::anotar::executar, cmd::anotar::USO)),
+        _ => None,
+    };
+    if let Some((executar, uso)) = subcomando {
+        args.next();
+        if let Err(erro) = executar(args) {
+            eprintln!("Erro: {erro}\n{uso}");
+            process::exit(2);
+        }
+        return;
+    }
 
+    let opcoes = match ler_opcoes(args) {
+        Ok(opcoes) => opcoes,
+        Err(erro) => {
+            eprintln!("Erro: {erro}\n{USO}");
+            process::exit(2);
+        }
+    };
+    let mut repl = Repl::new(opcoes);
     loop {
-        println!("{}", jogo);
-        print_lista_de_jogadas(jogo.todas_jogadas_possiveis().to_vec());
-        let jogada = input(&format!("Vez do {:?}: ", jogo.get_vez()));
-        if let Resultado::FimDoJogo(ganhador) = jogo.jogar(jogada) {
-            clear_terminal();
-            println!("{}", jogo);
-            println!("{:?} ganhou!!!", ganhador);
-            std::process::exit(0);
+        repl.mostrar();
+        if repl.fim.is_none() && repl.bot_da_vez().is_some() {
+            if !repl.tem_humano() {
+                thread::sleep(repl.opcoes.atraso);
+            }
+            if let Err(erro) = repl.jogar_bot() {
+                repl.mensagem = format!("Erro: {erro}");
+            }
+            continue;
+        }
+        let linha = match input(&format!("Vez do {:?}>", repl.partida.get_vez())) {
+            Some(linha) => linha,
+            None => break,
+        };
+        match repl.executar(&linha) {
+            Ok(true) => (),
+            Ok(false) => break,
+            Err(erro) => repl.mensagem = format!("Erro: {erro}"),
         }
-        clear_terminal();
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,398 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use crate::aberturas::Catalogo;
+use crate::notacao::{notacao, ErroNotacao};
+use crate::{Cor, ErroFen, Partida};
+
+// Leitura e escrita de partidas em PDN (Portable Draughts Notation), com as
+// jogadas em notação numérica e a posição inicial na tag FEN quando necessário.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErroPdn {
+    Malformado(String),
+    Fen(ErroFen),
+    Jogada { lance: usize, erro: ErroNotacao },
+}
+
+impl Display for ErroPdn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErroPdn::Malformado(motivo) => write!(f, "PDN malformado: {motivo}"),
+            ErroPdn::Fen(erro) => write!(f, "FEN inválido: {erro}"),
+            ErroPdn::Jogada { lance, erro } => write!(f, "lance {lance}: {erro}"),
+        }
+    }
+}
+
+impl std::error::Error for ErroPdn {}
+
+/// O que acompanha um lance: NAG, comentário e variantes.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Anotacao {
+    /// Símbolo de avaliação do lance: !, ?, !!, ??, !? ou ?!.
+    pub nag: Option<String>,
+    pub comentario: Option<String>,
+    /// Sequências que poderiam ter sido jogadas no lugar do lance.
+    pub variantes: Vec<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct JogoPdn {
+    pub tags: Vec<(String, String)>,
+    pub lances: Vec<String>,
+    pub resultado: String,
+    /// Anotações pelo índice do lance em `lances`.
+    pub anotacoes: BTreeMap<usize, Anotacao>,
+}
+
+impl Default for JogoPdn {
+    fn default() -> Self {
+        JogoPdn {
+            tags: vec![("GameType".to_string(), "26".to_string())],
+            lances: vec![],
+            resultado: "*".to_string(),
+            anotacoes: BTreeMap::new(),
+        }
+    }
+}
+
+impl JogoPdn {
+    /// Jogo com os lances da partida, o resultado se ela terminou e, quando
+    /// ela começa na posição inicial, as tags da abertura do catálogo padrão.
+    pub fn de_partida(partida: &Partida) -> Self {
+        let mut jogo = JogoPdn::default();
+        let inicial = partida.posicao_inicial();
+        if inicial.fen() != Partida::default().fen() {
+            jogo.set_tag("FEN", &inicial.fen());
+        }
+        jogo.lances = partida.historico().iter().map(|sequencia| notacao(sequencia)).collect();
+        Catalogo::padrao().marcar(&mut jogo, partida);
+        if partida.encerrada() {
+            jogo.set_resultado(partida.estado().vencedor());
+        }
+        jogo
+    }
+
+    /// Marca o jogo como terminado, no resultado e na tag Result.
+    pub fn set_resultado(&mut self, vencedor: Option<Cor>) {
+        self.resultado = resultado(vencedor).to_string();
+        self.set_tag("Result", &self.resultado.clone());
+    }
+
+    pub fn tag(&self, nome: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(chave, _)| chave == nome)
+            .map(|(_, valor)| valor.as_str())
+    }
+
+    pub fn set_tag(&mut self, nome: &str, valor: &str) {
+        match self.tags.iter_mut().find(|(chave, _)| chave == nome) {
+            Some((_, atual)) => *atual = valor.to_string(),
+            None => self.tags.push((nome.to_string(), valor.to_string())),
+        }
+    }
+
+    pub fn anotacao(&self, lance: usize) -> Option<&Anotacao> {
+        self.anotacoes.get(&lance)
+    }
+
+    /// Anotação do lance, criada vazia se ainda não existir.
+    pub fn anotacao_mut(&mut self, lance: usize) -> &mut Anotacao {
+        self.anotacoes.entry(lance).or_default()
+    }
+
+    /// Partida na posição inicial do jogo, antes de qualquer lance.
+    pub fn posicao_inicial(&self) -> Result<Partida, ErroPdn> {
+        match self.tag("FEN") {
+            Some(fen) => Partida::from_fen(fen).map_err(ErroPdn::Fen),
+            None => Ok(Partida::default()),
+        }
+    }
+
+    /// Reproduz todos os lances e devolve a partida no fim do jogo.
+    pub fn partida(&self) -> Result<Partida, ErroPdn> {
+        self.partida_ate(self.lances.len())
+    }
+
+    /// Reproduz os primeiros `lances` lances do jogo.
+    pub fn partida_ate(&self, lances: usize) -> Result<Partida, ErroPdn> {
+        let mut partida = self.posicao_inicial()?;
+        for (i, lance) in self.lances.iter().take(lances).enumerate() {
+            let indice = partida
+                .encontrar_jogada(lance)
+                .map_err(|erro| ErroPdn::Jogada { lance: i + 1, erro })?;
+            partida.jogar(indice);
+        }
+        Ok(partida)
+    }
+}
+
+impl Display for JogoPdn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (chave, valor) in &self.tags {
+            writeln!(f, "[{chave} \"{}\"]", valor.replace('"', "'"))?;
+        }
+        writeln!(f)?;
+
+        let preto_começa = self
+            .posicao_inicial()
+            .map(|p| *p.get_vez() == Cor::Preto)
+            .unwrap_or(false);
+        let deslocamento = if preto_começa { 1 } else { 0 };
+        let mut palavras = vec![];
+        // Depois de um comentário ou variante, o lance das pretas volta a ter número
+        let mut retomar = false;
+        for (i, lance) in self.lances.iter().enumerate() {
+            let meio_lance = i + deslocamento;
+            if meio_lance % 2 == 0 {
+                palavras.push(format!("{}.", meio_lance / 2 + 1));
+            } else if i == 0 || retomar {
+                palavras.push(format!("{}...", meio_lance / 2 + 1));
+            }
+            let Some(anotacao) = self.anotacao(i) else {
+                palavras.push(lance.clone());
+                retomar = false;
+                continue;
+            };
+            palavras.push(format!("{lance}{}", anotacao.nag.as_deref().unwrap_or("")));
+            if let Some(comentario) = &anotacao.comentario {
+                palavras.push(format!("{{{comentario}}}"));
+            }
+            for variante in &anotacao.variantes {
+                palavras.push(format!("({})", numerar(variante, meio_lance).join(" ")));
+            }
+            retomar = anotacao.comentario.is_some() || !anotacao.variantes.is_empty();
+        }
+        palavras.push(self.resultado.clone());
+
+        let mut linha = String::new();
+        for palavra in palavras {
+            if !linha.is_empty() && linha.len() + palavra.len() + 1 > 79 {
+                writeln!(f, "{linha}")?;
+                linha.clear();
+            }
+            if !linha.is_empty() {
+                linha.push(' ');
+            }
+            linha.push_str(&palavra);
+        }
+        writeln!(f, "{linha}")
+    }
+}
+
+// Lances de uma variante com os números, começando no meio-lance dado.
+fn numerar(lances: &[String], inicio: usize) -> Vec<String> {
+    let mut palavras = vec![];
+    for (i, lance) in lances.iter().enumerate() {
+        let meio_lance = inicio + i;
+        if meio_lance.is_multiple_of(2) {
+            palavras.push(format!("{}.", meio_lance / 2 + 1));
+        } else if i == 0 {
+            palavras.push(format!("{}...", meio_lance / 2 + 1));
+        }
+        palavras.push(lance.clone());
+    }
+    palavras
+}
+
+// NAGs numéricos de $1 a $6 e os símbolos correspondentes
+const NAGS: [&str; 6] = ["!", "?", "!!", "??", "!?", "?!"];
+
+// Tira o número do lance ("12." ou "12...") e separa o NAG grudado na jogada.
+fn separar(palavra: &str) -> (&str, &str) {
+    let lance = palavra.trim_start_matches(|ch: char| ch.is_ascii_digit()).trim_start_matches('.');
+    let lance = if palavra.contains('.') { lance } else { palavra };
+    let jogada = lance.trim_end_matches(['!', '?', '+']);
+    (jogada, &lance[jogada.len()..])
+}
+
+// Os lances do nível de cima de uma variante, sem comentários nem subvariantes.
+fn lances_da_variante(texto: &str) -> Vec<String> {
+    let mut lances = vec![];
+    let mut nivel = 0;
+    let mut comentario = false;
+    let mut palavra = String::new();
+    for ch in texto.chars().chain([' ']) {
+        match ch {
+            '{' => comentario = true,
+            '}' => comentario = false,
+            _ if comentario => (),
+            '(' => nivel += 1,
+            ')' => nivel -= 1,
+            _ if nivel > 0 => (),
+            ch if ch.is_whitespace() => {
+                let (jogada, _) = separar(&palavra);
+                if !jogada.is_empty() && !jogada.starts_with('$') && !é_resultado(jogada) {
+                    lances.push(jogada.to_string());
+                }
+                palavra.clear();
+            }
+            ch => palavra.push(ch),
+        }
+    }
+    lances
+}
+
+// Resultados de jogo terminado, primeiro os do formato brasileiro, que são
+// os escritos; os internacionais são aceitos na leitura
+const RESULTADOS: [(&str, Option<Cor>); 7] = [
+    ("2-0", Some(Cor::Branco)),
+    ("0-2", Some(Cor::Preto)),
+    ("1-1", None),
+    ("1-0", Some(Cor::Branco)),
+    ("0-1", Some(Cor::Preto)),
+    ("1/2-1/2", None),
+    ("½-½", None),
+];
+
+/// Resultado no formato do PDN brasileiro: 2-0, 0-2 ou 1-1 no empate.
+pub fn resultado(vencedor: Option<Cor>) -> &'static str {
+    RESULTADOS.iter().find(|(_, cor)| *cor == vencedor).map_or("*", |(texto, _)| texto)
+}
+
+/// Vencedor de um resultado em qualquer formato, ou None se o texto não é o
+/// de um jogo terminado.
+pub fn vencedor(resultado: &str) -> Option<Option<Cor>> {
+    RESULTADOS.iter().find(|(texto, _)| *texto == resultado).map(|(_, cor)| *cor)
+}
+
+fn é_resultado(palavra: &str) -> bool {
+    palavra == "*" || vencedor(palavra).is_some()
+}
+
+/// Lê todos os jogos de um texto PDN.
+pub fn ler(texto: &str) -> Result<Vec<JogoPdn>, ErroPdn> {
+    let mut jogos = vec![];
+    let mut jogo: Option<JogoPdn> = None;
+    let mut chars = texto.chars().peekable();
+
+    while let Some(&ch) = chars.peek() {
+        match ch {
+            '[' => {
+                chars.next();
+                let conteudo: String = chars.by_ref().take_while(|&ch| ch != ']').collect();
+                let (chave, valor) = conteudo
+                    .trim()
+                    .split_once(char::is_whitespace)
+                    .ok_or_else(|| ErroPdn::Malformado(format!("tag [{conteudo}]")))?;
+                // Tags depois de lances começam um novo jogo sem resultado
+                if jogo.as_ref().is_some_and(|atual| !atual.lances.is_empty()) {
+                    jogos.push(jogo.take().unwrap());
+                }
+                let atual = jogo.get_or_insert_with(|| JogoPdn { tags: vec![], ..Default::default() });
+                atual.set_tag(chave, valor.trim().trim_matches('"'));
+            }
+            '{' => {
+                chars.next();
+                let comentario: String = chars.by_ref().take_while(|&ch| ch != '}').collect();
+                let comentario = comentario.trim();
+                // Comentários antes do primeiro lance não têm onde ficar
+                if let Some(atual) = jogo.as_mut().filter(|atual| !atual.lances.is_empty() && !comentario.is_empty()) {
+                    let anotacao = atual.anotacao_mut(atual.lances.len() - 1);
+                    anotacao.comentario = Some(match anotacao.comentario.take() {
+                        Some(anterior) => format!("{anterior} {comentario}"),
+                        None => comentario.to_string(),
+                    });
+                }
+            }
+            '(' => {
+                chars.next();
+                let mut nivel = 1;
+                let mut conteudo = String::new();
+                for ch in chars.by_ref() {
+                    match ch {
+                        '(' => nivel += 1,
+                        ')' => nivel -= 1,
+                        _ => (),
+                    }
+                    if nivel == 0 {
+                        break;
+                    }
+                    conteudo.push(ch);
+                }
+                // A variante substitui o último lance lido
+                let variante = lances_da_variante(&conteudo);
+                if let Some(atual) = jogo.as_mut().filter(|atual| !atual.lances.is_empty() && !variante.is_empty()) {
+                    atual.anotacao_mut(atual.lances.len() - 1).variantes.push(variante);
+                }
+            }
+            ch if ch.is_whitespace() => {
+                chars.next();
+            }
+            _ => {
+                let mut palavra = String::new();
+                while let Some(&ch) = chars.peek() {
+                    if ch.is_whitespace() || "[{(".contains(ch) {
+                        break;
+                    }
+                    palavra.push(ch);
+                    chars.next();
+                }
+                let atual = jogo.get_or_insert_with(|| JogoPdn { tags: vec![], ..Default::default() });
+                if é_resultado(&palavra) {
+                    atual.resultado = palavra;
+                    jogos.push(jogo.take().unwrap());
+                    continue;
+                }
+                // Números de lance ("12." ou "12...") e NAGs podem vir grudados na jogada
+                let (lance, sufixo) = separar(&palavra);
+                let nag = match lance.strip_prefix('$').and_then(|numero| numero.parse::<usize>().ok()) {
+                    Some(numero) => NAGS.get(numero.wrapping_sub(1)).copied(),
+                    None => NAGS.iter().copied().find(|&nag| nag == sufixo),
+                };
+                if !lance.is_empty() && !lance.starts_with('$') {
+                    atual.lances.push(lance.to_string());
+                }
+                if let Some(nag) = nag.filter(|_| !atual.lances.is_empty()) {
+                    atual.anotacao_mut(atual.lances.len() - 1).nag = Some(nag.to_string());
+                }
+            }
+        }
+    }
+    if let Some(jogo) = jogo {
+        if !jogo.lances.is_empty() || !jogo.tags.is_empty() {
+            jogos.push(jogo);
+        }
+    }
+    Ok(jogos)
+}
+
+#[test]
+fn testar_pdn() {
+    let mut partida = Partida::default();
+    for lance in ["22-18", "11-15", "18x11", "8x15"] {
+        let indice = partida.encontrar_jogada(lance).unwrap();
+        partida.jogar(indice);
+    }
+    let texto = JogoPdn::de_partida(&partida).to_string();
+    assert!(texto.contains("1. 22-18 11-15 2. 18x11 8x15 *"));
+    let mut desistencia = partida.clone();
+    desistencia.desistir(Cor::Branco).unwrap();
+    let jogo = JogoPdn::de_partida(&desistencia);
+    assert_eq!((jogo.resultado.as_str(), jogo.tag("Result")), ("0-2", Some("0-2")));
+
+    let jogos = ler(&format!("{texto}\n[Event \"b\"]\n1.22-17 {{bom}} 9-13 (1... 10-14) 1-0")).unwrap();
+    assert_eq!(jogos.len(), 2);
+    assert_eq!(jogos[0].partida().unwrap().fen(), partida.fen());
+    assert_eq!(jogos[1].tag("Event"), Some("b"));
+    assert_eq!(jogos[1].lances, vec!["22-17", "9-13"]);
+    assert_eq!(jogos[1].resultado, "1-0");
+    assert_eq!(vencedor(&jogos[1].resultado), Some(Some(Cor::Branco)));
+    assert_eq!(resultado(vencedor("½-½").unwrap()), "1-1");
+    assert_eq!(vencedor("*"), None);
+    assert_eq!(jogos[1].anotacao(0).unwrap().comentario.as_deref(), Some("bom"));
+    assert_eq!(jogos[1].anotacao(1).unwrap().variantes, [vec!["10-14".to_string()]]);
+
+    // Anotações sobrevivem à escrita e à leitura
+    let texto = "1. 22-18?! {fraco} (1. 21-17 $1 {melhor} (1. 23-19) 9-13) 1... 11-15 $4 2. 18x11 8x15 *";
+    let jogo = &ler(texto).unwrap()[0];
+    assert_eq!(jogo.lances, ["22-18", "11-15", "18x11", "8x15"]);
+    assert_eq!(jogo.anotacao(0).unwrap().nag.as_deref(), Some("?!"));
+    assert_eq!(jogo.anotacao(0).unwrap().variantes, [vec!["21-17".to_string(), "9-13".to_string()]]);
+    assert_eq!(jogo.anotacao(1).unwrap().nag.as_deref(), Some("??"));
+    let escrito = jogo.to_string();
+    assert!(escrito.contains("1. 22-18?! {fraco} (1. 21-17 9-13) 1... 11-15?? 2. 18x11 8x15 *"));
+    assert_eq!(&ler(&escrito).unwrap()[0], jogo);
+}
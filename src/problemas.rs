@@ -0,0 +1,320 @@
+use crate::notacao::notacao;
+use crate::Partida;
+
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+
+// Conferência de problemas "brancas jogam e ganham" (ou pretas, conforme a vez
+// no FEN). A busca é E/OU exaustiva com aprofundamento iterativo: nos lances
+// de quem ataca basta um ganhar, nos do adversário todas as defesas precisam
+// perder. Ganhar é deixar o adversário sem pedras ou sem jogadas; empate pelas
+// regras de Partida conta como defesa bem-sucedida. As jogadas vêm de
+// `todas_jogadas_possiveis`, com a captura obrigatória pela maioria.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErroProblema {
+    /// Nenhuma vitória forçada em até tantos lances (meios-lances).
+    SemVitoria(usize),
+    /// A busca passou do limite de posições antes de decidir.
+    LimiteDeNos(u64),
+}
+
+impl Display for ErroProblema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErroProblema::SemVitoria(lances) => write!(f, "não há vitória forçada em até {lances} lances"),
+            ErroProblema::LimiteDeNos(nos) => write!(f, "busca interrompida depois de {nos} posições"),
+        }
+    }
+}
+
+impl std::error::Error for ErroProblema {}
+
+/// Um lance da solução e o que vem depois dele. Depois de um lance de quem
+/// ataca vêm todas as defesas; depois de uma defesa, o lance que ganha.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ramo {
+    pub lance: String,
+    pub seguintes: Vec<Ramo>,
+}
+
+/// Outro lance que também ganha no mesmo ponto da solução.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dual {
+    /// Lances da posição do problema até o ponto do dual.
+    pub caminho: Vec<String>,
+    /// Os lances que ganham ali além do escolhido.
+    pub alternativas: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solucao {
+    /// Árvore a partir do lance-chave.
+    pub arvore: Ramo,
+    /// Meios-lances até a vitória contra a melhor defesa.
+    pub lances: usize,
+    /// Outros primeiros lances que também ganham dentro da profundidade.
+    pub cooks: Vec<String>,
+    /// Pontos depois do lance-chave em que mais de um lance ganha no mesmo prazo.
+    pub duais: Vec<Dual>,
+    pub nos: u64,
+}
+
+impl Solucao {
+    /// Se o problema tem solução única: sem cooks e sem duais.
+    pub fn correto(&self) -> bool {
+        self.cooks.is_empty() && self.duais.is_empty()
+    }
+}
+
+fn escrever_ramo(f: &mut fmt::Formatter<'_>, ramo: &Ramo, nivel: usize) -> fmt::Result {
+    writeln!(f, "{:1$}{2}", "", nivel * 2, ramo.lance)?;
+    for seguinte in &ramo.seguintes {
+        escrever_ramo(f, seguinte, nivel + 1)?;
+    }
+    Ok(())
+}
+
+impl Display for Solucao {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Vitória em {} lances", self.lances)?;
+        escrever_ramo(f, &self.arvore, 0)?;
+        if !self.cooks.is_empty() {
+            writeln!(f, "Cooks: {}", self.cooks.join(", "))?;
+        }
+        for dual in &self.duais {
+            writeln!(f, "Dual depois de {}: {}", dual.caminho.join(" "), dual.alternativas.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Solucionador {
+    /// Máximo de meios-lances até a vitória.
+    pub profundidade: usize,
+    /// Máximo de posições visitadas antes de desistir.
+    pub limite_nos: u64,
+}
+
+impl Default for Solucionador {
+    fn default() -> Self {
+        Solucionador { profundidade: 21, limite_nos: 20_000_000 }
+    }
+}
+
+// O que já se sabe de uma posição com quem ataca na vez: ganha em até
+// `provado` meios-lances, ou não ganha em até `refutado`.
+#[derive(Default, Clone, Copy)]
+struct Entrada {
+    provado: Option<usize>,
+    refutado: usize,
+}
+
+struct Busca {
+    limite_nos: u64,
+    nos: u64,
+    tabela: HashMap<u64, Entrada>,
+}
+
+// A chave da posição não inclui o contador de empate, que muda o resultado
+fn chave(partida: &Partida) -> u64 {
+    partida.chave() ^ ((partida.get_contador_empate() * 2.0) as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15)
+}
+
+impl Busca {
+    // Quem tem a vez ganha em até `restante` meios-lances?
+    fn ganha(&mut self, partida: &Partida, restante: usize) -> Result<bool, ErroProblema> {
+        if restante == 0 {
+            return Ok(false);
+        }
+        let chave = chave(partida);
+        let entrada = self.tabela.get(&chave).copied().unwrap_or_default();
+        if entrada.provado.is_some_and(|provado| provado <= restante) {
+            return Ok(true);
+        }
+        if entrada.refutado >= restante {
+            return Ok(false);
+        }
+        let mut ganhou = false;
+        for indice in 0..partida.todas_jogadas_possiveis().len() {
+            if self.vence_com(partida, indice, restante)? {
+                ganhou = true;
+                break;
+            }
+        }
+        let entrada = self.tabela.entry(chave).or_default();
+        if ganhou {
+            entrada.provado = Some(entrada.provado.map_or(restante, |provado| provado.min(restante)));
+        } else {
+            entrada.refutado = entrada.refutado.max(restante);
+        }
+        Ok(ganhou)
+    }
+
+    // A jogada `indice` de quem tem a vez ganha em até `restante` meios-lances?
+    fn vence_com(&mut self, partida: &Partida, indice: usize, restante: usize) -> Result<bool, ErroProblema> {
+        self.nos += 1;
+        if self.nos > self.limite_nos {
+            return Err(ErroProblema::LimiteDeNos(self.limite_nos));
+        }
+        let atacante = *partida.get_vez();
+        let mut filho = partida.clone();
+        filho.jogar(indice);
+        // O fim vem antes do prazo: deixar o defensor sem jogadas também ganha
+        if filho.encerrada() {
+            return Ok(filho.estado().vencedor() == Some(atacante));
+        }
+        if restante < 3 {
+            return Ok(false);
+        }
+        self.todas_perdem(&filho, restante - 1)
+    }
+
+    // Todas as defesas de quem tem a vez perdem em até `restante` meios-lances?
+    fn todas_perdem(&mut self, partida: &Partida, restante: usize) -> Result<bool, ErroProblema> {
+        for indice in 0..partida.todas_jogadas_possiveis().len() {
+            if !self.perde_com(partida, indice, restante)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn perde_com(&mut self, partida: &Partida, indice: usize, restante: usize) -> Result<bool, ErroProblema> {
+        self.nos += 1;
+        if self.nos > self.limite_nos {
+            return Err(ErroProblema::LimiteDeNos(self.limite_nos));
+        }
+        let defensor = *partida.get_vez();
+        let mut filho = partida.clone();
+        filho.jogar(indice);
+        if filho.encerrada() {
+            return Ok(filho.estado().vencedor().is_some_and(|vencedor| vencedor != defensor));
+        }
+        self.ganha(&filho, restante - 1)
+    }
+
+    // Jogadas de quem tem a vez que ganham em até `restante` meios-lances.
+    fn vencedoras(&mut self, partida: &Partida, restante: usize) -> Result<Vec<usize>, ErroProblema> {
+        let mut vencedoras = vec![];
+        for indice in 0..partida.todas_jogadas_possiveis().len() {
+            if self.vence_com(partida, indice, restante)? {
+                vencedoras.push(indice);
+            }
+        }
+        Ok(vencedoras)
+    }
+
+    // Monta a árvore a partir da jogada de quem ataca, anotando os duais.
+    fn arvore(
+        &mut self,
+        partida: &Partida,
+        indice: usize,
+        caminho: &mut Vec<String>,
+        duais: &mut Vec<Dual>,
+    ) -> Result<Ramo, ErroProblema> {
+        let lance = notacao(&partida.todas_jogadas_possiveis()[indice]);
+        let mut ramo = Ramo { lance: lance.clone(), seguintes: vec![] };
+        let mut filho = partida.clone();
+        filho.jogar(indice);
+        if filho.encerrada() {
+            return Ok(ramo);
+        }
+        caminho.push(lance);
+        for defesa in 0..filho.todas_jogadas_possiveis().len() {
+            let resposta = notacao(&filho.todas_jogadas_possiveis()[defesa]);
+            let mut neto = filho.clone();
+            let mut ramo_defesa = Ramo { lance: resposta.clone(), seguintes: vec![] };
+            neto.jogar(defesa);
+            if neto.encerrada() {
+                ramo.seguintes.push(ramo_defesa);
+                continue;
+            }
+            // O prazo mais curto contra esta defesa define o lance da solução
+            let mut prazo = 1;
+            while !self.ganha(&neto, prazo)? {
+                prazo += 2;
+            }
+            caminho.push(resposta);
+            let vencedoras = self.vencedoras(&neto, prazo)?;
+            let escolhida = vencedoras[0];
+            if vencedoras.len() > 1 {
+                duais.push(Dual {
+                    caminho: caminho.clone(),
+                    alternativas: vencedoras[1..]
+                        .iter()
+                        .map(|&indice| notacao(&neto.todas_jogadas_possiveis()[indice]))
+                        .collect(),
+                });
+            }
+            ramo_defesa.seguintes.push(self.arvore(&neto, escolhida, caminho, duais)?);
+            caminho.pop();
+            ramo.seguintes.push(ramo_defesa);
+        }
+        caminho.pop();
+        Ok(ramo)
+    }
+}
+
+impl Solucionador {
+    pub fn com_profundidade(mut self, profundidade: usize) -> Self {
+        self.profundidade = profundidade;
+        self
+    }
+
+    /// Prova que quem tem a vez ganha contra qualquer defesa e devolve a
+    /// solução mais curta, com os cooks e duais encontrados.
+    pub fn resolver(&self, partida: &Partida) -> Result<Solucao, ErroProblema> {
+        let mut busca = Busca { limite_nos: self.limite_nos, nos: 0, tabela: HashMap::new() };
+        let mut lances = 1;
+        while !busca.ganha(partida, lances)? {
+            lances += 2;
+            if lances > self.profundidade {
+                return Err(ErroProblema::SemVitoria(self.profundidade));
+            }
+        }
+        let vencedoras = busca.vencedoras(partida, lances)?;
+        let chave = vencedoras[0];
+        // Um cook é outro primeiro lance que ganha, mesmo que mais devagar
+        let mut cooks = vec![];
+        for indice in (0..partida.todas_jogadas_possiveis().len()).filter(|&indice| indice != chave) {
+            if busca.vence_com(partida, indice, self.profundidade)? {
+                cooks.push(notacao(&partida.todas_jogadas_possiveis()[indice]));
+            }
+        }
+        let mut duais = vec![];
+        let arvore = busca.arvore(partida, chave, &mut vec![], &mut duais)?;
+        Ok(Solucao { arvore, lances, cooks, duais, nos: busca.nos })
+    }
+}
+
+#[test]
+fn testar_problemas() {
+    let solucionador = Solucionador::default().com_profundidade(9);
+    let resolver = |fen: &str| solucionador.resolver(&Partida::from_fen(fen).unwrap());
+
+    // Entrega em 9 obrigando a tomada dupla, e as brancas tomam de volta
+    let solucao = resolver("W:W25,18,13:B22,5,20").unwrap();
+    assert!(solucao.correto());
+    assert_eq!(solucao.lances, 5);
+    assert_eq!(solucao.arvore.lance, "13-9");
+    assert_eq!(solucao.arvore.seguintes[0].lance, "5x14x23");
+    assert_eq!(solucao.arvore.seguintes[0].seguintes[0].lance, "25x18x27");
+    assert!(solucao.to_string().starts_with("Vitória em 5 lances\n13-9\n  5x14x23\n"));
+
+    let cook = resolver("W:W20,23,14:B15").unwrap();
+    assert!(cook.cooks.contains(&"23-19".to_string()));
+    let dual = resolver("W:W28,26,16:B18,12").unwrap();
+    assert!(dual.cooks.is_empty());
+    assert_eq!(dual.duais.len(), 1);
+    assert!(!dual.correto());
+
+    // Deixar as pretas sem jogadas ganha na hora, com 30-25 ou 30-26
+    let bloqueio = resolver("W:W9,14,30:B5").unwrap();
+    assert_eq!((bloqueio.lances, bloqueio.arvore.lance.as_str()), (1, "30-25"));
+    assert!(bloqueio.arvore.seguintes.is_empty());
+    assert!(bloqueio.cooks.contains(&"30-26".to_string()));
+
+    assert_eq!(solucionador.com_profundidade(3).resolver(&Partida::default()), Err(ErroProblema::SemVitoria(3)));
+}
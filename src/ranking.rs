@@ -0,0 +1,460 @@
+use crate::pdn;
+use crate::{Cor, Resultado};
+
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+use std::fmt::{self, Display};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Ratings do clube. O arquivo guarda os parâmetros, os jogadores e as
+// partidas, uma por linha com os campos separados por tabulação:
+//
+//   parametros  <k do Elo>  <tau do Glicko-2>
+//   jogador  <nome>
+//   partida  <segundos desde 1970>  <branco>  <preto>  <2-0|1-1|0-2>
+//
+// Os parâmetros vêm antes das partidas; sem eles valem os padrões.
+// Os ratings são recalculados ao abrir, na ordem das partidas, por Elo e por
+// Glicko-2 ao mesmo tempo; cada partida é um período de avaliação do Glicko-2.
+
+pub const RATING_INICIAL: f64 = 1500.0;
+
+// Conversão entre a escala do Glicko e a do Glicko-2
+const ESCALA: f64 = 173.7178;
+
+#[derive(Debug)]
+pub enum ErroRanking {
+    Io(io::Error),
+    /// Linha do arquivo, a partir de 1, e o problema encontrado.
+    Linha(usize, String),
+    /// Nomes não podem ser vazios nem ter tabulações ou quebras de linha.
+    NomeInvalido(String),
+}
+
+impl Display for ErroRanking {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErroRanking::Io(erro) => write!(f, "{erro}"),
+            ErroRanking::Linha(linha, motivo) => write!(f, "linha {linha}: {motivo}"),
+            ErroRanking::NomeInvalido(nome) => write!(f, "nome inválido: {nome:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ErroRanking {}
+
+impl From<io::Error> for ErroRanking {
+    fn from(erro: io::Error) -> Self {
+        ErroRanking::Io(erro)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Metodo {
+    Elo,
+    Glicko2,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Glicko2 {
+    pub rating: f64,
+    /// Desvio do rating; cai à medida que o jogador joga.
+    pub desvio: f64,
+    pub volatilidade: f64,
+}
+
+impl Default for Glicko2 {
+    fn default() -> Self {
+        Glicko2 { rating: RATING_INICIAL, desvio: 350.0, volatilidade: 0.06 }
+    }
+}
+
+fn g(phi: f64) -> f64 {
+    1.0 / (1.0 + 3.0 * phi * phi / (PI * PI)).sqrt()
+}
+
+impl Glicko2 {
+    /// Novo rating depois de um período com os adversários e a pontuação
+    /// contra cada um (1, 0,5 ou 0). `tau` limita a mudança da volatilidade.
+    pub fn atualizar(&self, partidas: &[(Glicko2, f64)], tau: f64) -> Glicko2 {
+        let mu = (self.rating - RATING_INICIAL) / ESCALA;
+        let phi = self.desvio / ESCALA;
+        if partidas.is_empty() {
+            let phi = (phi * phi + self.volatilidade * self.volatilidade).sqrt();
+            return Glicko2 { desvio: phi * ESCALA, ..*self };
+        }
+
+        let (mut inverso_de_v, mut soma) = (0.0, 0.0);
+        for (adversario, pontos) in partidas {
+            let mu_j = (adversario.rating - RATING_INICIAL) / ESCALA;
+            let g_j = g(adversario.desvio / ESCALA);
+            let esperado = 1.0 / (1.0 + (-g_j * (mu - mu_j)).exp());
+            inverso_de_v += g_j * g_j * esperado * (1.0 - esperado);
+            soma += g_j * (pontos - esperado);
+        }
+        let v = 1.0 / inverso_de_v;
+        let delta = v * soma;
+
+        // Nova volatilidade pelo método de Illinois
+        let a = (self.volatilidade * self.volatilidade).ln();
+        let f = |x: f64| {
+            let ex = x.exp();
+            ex * (delta * delta - phi * phi - v - ex) / (2.0 * (phi * phi + v + ex).powi(2)) - (x - a) / (tau * tau)
+        };
+        let mut a_ = a;
+        let mut b = if delta * delta > phi * phi + v {
+            (delta * delta - phi * phi - v).ln()
+        } else {
+            let mut k = 1.0;
+            while f(a - k * tau) < 0.0 {
+                k += 1.0;
+            }
+            a - k * tau
+        };
+        let (mut fa, mut fb) = (f(a_), f(b));
+        while (b - a_).abs() > 1e-6 {
+            let c = a_ + (a_ - b) * fa / (fb - fa);
+            let fc = f(c);
+            if fc * fb <= 0.0 {
+                a_ = b;
+                fa = fb;
+            } else {
+                fa /= 2.0;
+            }
+            b = c;
+            fb = fc;
+        }
+        let volatilidade = (a_ / 2.0).exp();
+
+        let phi_estrela = (phi * phi + volatilidade * volatilidade).sqrt();
+        let novo_phi = 1.0 / (1.0 / (phi_estrela * phi_estrela) + 1.0 / v).sqrt();
+        let novo_mu = mu + novo_phi * novo_phi * soma;
+        Glicko2 { rating: novo_mu * ESCALA + RATING_INICIAL, desvio: novo_phi * ESCALA, volatilidade }
+    }
+}
+
+/// Ratings e números de um jogador.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Avaliacao {
+    pub elo: f64,
+    pub glicko: Glicko2,
+    pub vitorias: u32,
+    pub empates: u32,
+    pub derrotas: u32,
+}
+
+impl Default for Avaliacao {
+    fn default() -> Self {
+        Avaliacao { elo: RATING_INICIAL, glicko: Glicko2::default(), vitorias: 0, empates: 0, derrotas: 0 }
+    }
+}
+
+impl Avaliacao {
+    pub fn partidas(&self) -> u32 {
+        self.vitorias + self.empates + self.derrotas
+    }
+
+    pub fn rating(&self, metodo: Metodo) -> f64 {
+        match metodo {
+            Metodo::Elo => self.elo,
+            Metodo::Glicko2 => self.glicko.rating,
+        }
+    }
+}
+
+/// Uma partida do histórico e quanto ela mudou os ratings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Registro {
+    /// Segundos desde 1970.
+    pub quando: u64,
+    pub branco: String,
+    pub preto: String,
+    pub vencedor: Option<Cor>,
+    /// Variação do Elo e do Glicko-2 das brancas e das pretas.
+    pub variacao_elo: (f64, f64),
+    pub variacao_glicko: (f64, f64),
+}
+
+impl Registro {
+    /// Resultado no formato do PDN brasileiro.
+    pub fn resultado(&self) -> &'static str {
+        pdn::resultado(self.vencedor)
+    }
+
+    /// Data da partida como AAAA-MM-DD, em UTC.
+    pub fn data(&self) -> String {
+        // Conversão de dias desde 1970 para o calendário civil
+        let dias = (self.quando / 86400) as i64 + 719468;
+        let era = dias.div_euclid(146097);
+        let dia_da_era = dias.rem_euclid(146097);
+        let ano_da_era = (dia_da_era - dia_da_era / 1460 + dia_da_era / 36524 - dia_da_era / 146096) / 365;
+        let dia_do_ano = dia_da_era - (365 * ano_da_era + ano_da_era / 4 - ano_da_era / 100);
+        let mp = (5 * dia_do_ano + 2) / 153;
+        let dia = dia_do_ano - (153 * mp + 2) / 5 + 1;
+        let mes = if mp < 10 { mp + 3 } else { mp - 9 };
+        let ano = ano_da_era + era * 400 + i64::from(mes <= 2);
+        format!("{ano:04}-{mes:02}-{dia:02}")
+    }
+}
+
+/// Instante do começo do dia, em segundos desde 1970 (UTC), de uma data
+/// AAAA.MM.DD como na tag Date do PDN, ou AAAA-MM-DD. None para datas
+/// incompletas como "2024.??.??".
+pub fn instante_da_data(texto: &str) -> Option<u64> {
+    let partes: Vec<&str> = texto.trim().split(['.', '-']).collect();
+    let [ano, mes, dia] = partes[..] else { return None };
+    let (ano, mes, dia): (i64, i64, i64) = (ano.parse().ok()?, mes.parse().ok()?, dia.parse().ok()?);
+    if ano < 1970 || !(1..=12).contains(&mes) || !(1..=31).contains(&dia) {
+        return None;
+    }
+    // A conversão inversa da de `Registro::data`
+    let ano = if mes <= 2 { ano - 1 } else { ano };
+    let era = ano.div_euclid(400);
+    let ano_da_era = ano.rem_euclid(400);
+    let mp = if mes > 2 { mes - 3 } else { mes + 9 };
+    let dia_do_ano = (153 * mp + 2) / 5 + dia - 1;
+    let dia_da_era = ano_da_era * 365 + ano_da_era / 4 - ano_da_era / 100 + dia_do_ano;
+    Some((era * 146097 + dia_da_era - 719468) as u64 * 86400)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ranking {
+    /// Fator K do Elo.
+    pub k: f64,
+    /// Restrição da volatilidade do Glicko-2.
+    pub tau: f64,
+    jogadores: BTreeMap<String, Avaliacao>,
+    historico: Vec<Registro>,
+}
+
+impl Default for Ranking {
+    fn default() -> Self {
+        Ranking { k: 32.0, tau: 0.5, jogadores: BTreeMap::new(), historico: vec![] }
+    }
+}
+
+fn validar(nome: &str) -> Result<(), ErroRanking> {
+    if nome.trim().is_empty() || nome.contains(['\t', '\n', '\r']) {
+        return Err(ErroRanking::NomeInvalido(nome.to_string()));
+    }
+    Ok(())
+}
+
+impl Ranking {
+    /// Lê o arquivo; se ele não existe, começa um ranking vazio.
+    pub fn abrir(caminho: impl AsRef<Path>) -> Result<Ranking, ErroRanking> {
+        match fs::read_to_string(caminho) {
+            Ok(texto) => Ranking::ler(&texto),
+            Err(erro) if erro.kind() == io::ErrorKind::NotFound => Ok(Ranking::default()),
+            Err(erro) => Err(erro.into()),
+        }
+    }
+
+    pub fn salvar(&self, caminho: impl AsRef<Path>) -> Result<(), ErroRanking> {
+        fs::write(caminho, self.to_string())?;
+        Ok(())
+    }
+
+    pub fn ler(texto: &str) -> Result<Ranking, ErroRanking> {
+        let mut ranking = Ranking::default();
+        for (i, linha) in texto.lines().enumerate() {
+            let erro = |motivo: &str| ErroRanking::Linha(i + 1, motivo.to_string());
+            let campos: Vec<&str> = linha.split('\t').collect();
+            match campos[..] {
+                [""] => {}
+                ["parametros", k, tau] => {
+                    if !ranking.historico.is_empty() {
+                        return Err(erro("parâmetros depois das partidas"));
+                    }
+                    let positivo = |texto: &str| texto.parse::<f64>().ok().filter(|valor| valor.is_finite() && *valor > 0.0);
+                    ranking.k = positivo(k).ok_or_else(|| erro("k inválido"))?;
+                    ranking.tau = positivo(tau).ok_or_else(|| erro("tau inválido"))?;
+                }
+                ["jogador", nome] => ranking.cadastrar(nome).map_err(|_| erro("nome inválido"))?,
+                ["partida", quando, branco, preto, resultado] => {
+                    let quando = quando.parse().map_err(|_| erro("data inválida"))?;
+                    let vencedor = pdn::vencedor(resultado).ok_or_else(|| erro("resultado inválido"))?;
+                    ranking.registrar_em(quando, branco, preto, vencedor).map_err(|_| erro("nome inválido"))?;
+                }
+                _ => return Err(erro("linha desconhecida")),
+            }
+        }
+        Ok(ranking)
+    }
+
+    /// Refaz os ratings desde o começo, na ordem em que as partidas foram
+    /// jogadas: depois de mudar `k` ou `tau`, ou de registrar partidas antigas.
+    pub fn recalcular(&mut self) {
+        let mut historico = std::mem::take(&mut self.historico);
+        historico.sort_by_key(|registro| registro.quando);
+        for avaliacao in self.jogadores.values_mut() {
+            *avaliacao = Avaliacao::default();
+        }
+        for registro in historico {
+            self.registrar_em(registro.quando, &registro.branco, &registro.preto, registro.vencedor).unwrap();
+        }
+    }
+
+    /// Inclui um jogador sem partidas; não faz nada se ele já existe.
+    pub fn cadastrar(&mut self, nome: &str) -> Result<(), ErroRanking> {
+        validar(nome)?;
+        self.jogadores.entry(nome.to_string()).or_default();
+        Ok(())
+    }
+
+    /// Registra uma partida agora, cadastrando os jogadores que faltarem.
+    pub fn registrar(&mut self, branco: &str, preto: &str, vencedor: Option<Cor>) -> Result<&Registro, ErroRanking> {
+        let agora = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |duracao| duracao.as_secs());
+        self.registrar_em(agora, branco, preto, vencedor)
+    }
+
+    /// Registra o resultado de `Partida::jogar`; devolve None se o jogo não acabou.
+    pub fn registrar_resultado(
+        &mut self,
+        branco: &str,
+        preto: &str,
+        resultado: Resultado,
+    ) -> Result<Option<&Registro>, ErroRanking> {
+        match resultado {
+            Resultado::FimDoJogo(vencedor) => self.registrar(branco, preto, vencedor).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Registra uma partida jogada em `quando`, em segundos desde 1970 (UTC).
+    /// Os ratings andam na ordem do registro; use `recalcular` depois de
+    /// registrar partidas mais antigas que as do histórico.
+    pub fn registrar_em(&mut self, quando: u64, branco: &str, preto: &str, vencedor: Option<Cor>) -> Result<&Registro, ErroRanking> {
+        validar(branco)?;
+        validar(preto)?;
+        if branco == preto {
+            return Err(ErroRanking::NomeInvalido(branco.to_string()));
+        }
+        let a = self.jogadores.get(branco).copied().unwrap_or_default();
+        let b = self.jogadores.get(preto).copied().unwrap_or_default();
+        let pontos = match vencedor {
+            Some(Cor::Branco) => 1.0,
+            Some(Cor::Preto) => 0.0,
+            None => 0.5,
+        };
+
+        let esperado = 1.0 / (1.0 + 10f64.powf((b.elo - a.elo) / 400.0));
+        let variacao = self.k * (pontos - esperado);
+        let glicko_a = a.glicko.atualizar(&[(b.glicko, pontos)], self.tau);
+        let glicko_b = b.glicko.atualizar(&[(a.glicko, 1.0 - pontos)], self.tau);
+
+        for (nome, avaliacao, elo, glicko, pontos) in
+            [(branco, a, a.elo + variacao, glicko_a, pontos), (preto, b, b.elo - variacao, glicko_b, 1.0 - pontos)]
+        {
+            let mut nova = Avaliacao { elo, glicko, ..avaliacao };
+            match pontos {
+                p if p > 0.5 => nova.vitorias += 1,
+                p if p < 0.5 => nova.derrotas += 1,
+                _ => nova.empates += 1,
+            }
+            self.jogadores.insert(nome.to_string(), nova);
+        }
+        self.historico.push(Registro {
+            quando,
+            branco: branco.to_string(),
+            preto: preto.to_string(),
+            vencedor,
+            variacao_elo: (variacao, -variacao),
+            variacao_glicko: (glicko_a.rating - a.glicko.rating, glicko_b.rating - b.glicko.rating),
+        });
+        Ok(self.historico.last().unwrap())
+    }
+
+    pub fn jogador(&self, nome: &str) -> Option<&Avaliacao> {
+        self.jogadores.get(nome)
+    }
+
+    /// Jogadores do maior para o menor rating no método escolhido.
+    pub fn classificacao(&self, metodo: Metodo) -> Vec<(&str, &Avaliacao)> {
+        let mut tabela: Vec<_> = self.jogadores.iter().map(|(nome, avaliacao)| (nome.as_str(), avaliacao)).collect();
+        tabela.sort_by(|a, b| b.1.rating(metodo).total_cmp(&a.1.rating(metodo)));
+        tabela
+    }
+
+    /// Partidas em ordem cronológica, só as do jogador se ele for dado.
+    pub fn historico<'a>(&'a self, nome: Option<&'a str>) -> impl Iterator<Item = &'a Registro> + 'a {
+        self.historico
+            .iter()
+            .filter(move |registro| nome.is_none_or(|nome| registro.branco == nome || registro.preto == nome))
+    }
+}
+
+impl Display for Ranking {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "parametros\t{}\t{}", self.k, self.tau)?;
+        for nome in self.jogadores.keys() {
+            writeln!(f, "jogador\t{nome}")?;
+        }
+        for registro in &self.historico {
+            writeln!(f, "partida\t{}\t{}\t{}\t{}", registro.quando, registro.branco, registro.preto, registro.resultado())?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn testar_glicko2() {
+    // Exemplo do artigo de Glickman
+    let jogador = Glicko2 { rating: 1500.0, desvio: 200.0, volatilidade: 0.06 };
+    let partidas = [
+        (Glicko2 { rating: 1400.0, desvio: 30.0, volatilidade: 0.06 }, 1.0),
+        (Glicko2 { rating: 1550.0, desvio: 100.0, volatilidade: 0.06 }, 0.0),
+        (Glicko2 { rating: 1700.0, desvio: 300.0, volatilidade: 0.06 }, 0.0),
+    ];
+    let novo = jogador.atualizar(&partidas, 0.5);
+    assert!((novo.rating - 1464.06).abs() < 0.01);
+    assert!((novo.desvio - 151.52).abs() < 0.01);
+    assert!((novo.volatilidade - 0.05999).abs() < 0.00001);
+}
+
+#[test]
+fn testar_ranking() {
+    let mut ranking = Ranking::default();
+    ranking.cadastrar("Caio").unwrap();
+    let registro = ranking.registrar("Ana", "Bia", Some(Cor::Branco)).unwrap();
+    assert_eq!(registro.variacao_elo, (16.0, -16.0));
+    ranking.registrar_resultado("Bia", "Ana", Resultado::FimDoJogo(None)).unwrap();
+    assert_eq!(ranking.registrar_resultado("Bia", "Ana", Resultado::Sucesso).unwrap(), None);
+    assert!(ranking.registrar("Ana", "Ana", None).is_err());
+
+    let ana = ranking.jogador("Ana").unwrap();
+    assert_eq!((ana.vitorias, ana.empates, ana.derrotas), (1, 1, 0));
+    assert!(ana.glicko.desvio < 350.0);
+    let nomes = |metodo| ranking.classificacao(metodo).iter().map(|(nome, _)| *nome).collect::<Vec<_>>();
+    assert_eq!(nomes(Metodo::Elo), ["Ana", "Caio", "Bia"]);
+    assert_eq!(nomes(Metodo::Glicko2), ["Ana", "Caio", "Bia"]);
+    assert_eq!(ranking.historico(Some("Caio")).count(), 0);
+
+    let relido = Ranking::ler(&ranking.to_string()).unwrap();
+    assert_eq!(relido, ranking);
+    // O k escolhido continua valendo depois de salvar e abrir de novo
+    let mut outro_k = ranking.clone();
+    outro_k.k = 16.0;
+    outro_k.recalcular();
+    assert_eq!(Ranking::ler(&outro_k.to_string()).unwrap(), outro_k);
+    assert_ne!(outro_k, ranking);
+    assert!(matches!(Ranking::ler("partida\t1\tA\tB\t1-1\nparametros\t16\t0.5"), Err(ErroRanking::Linha(2, _))));
+    assert_eq!(Registro { quando: 1_700_000_000, ..ranking.historico[0].clone() }.data(), "2023-11-14");
+    assert!(matches!(Ranking::ler("partida\tx"), Err(ErroRanking::Linha(1, _))));
+
+    // Partidas importadas fora de ordem são recalculadas na ordem em que foram jogadas
+    assert_eq!(instante_da_data("2023.11.14"), Some(1_699_920_000));
+    assert_eq!(instante_da_data("2024-02-29").map(|quando| Registro { quando, ..ranking.historico[0].clone() }.data()).as_deref(), Some("2024-02-29"));
+    assert_eq!(instante_da_data("2024.??.??"), None);
+    let mut importado = Ranking::default();
+    importado.registrar_em(instante_da_data("2024.03.02").unwrap(), "Ana", "Bia", None).unwrap();
+    importado.registrar_em(instante_da_data("2024.03.01").unwrap(), "Ana", "Bia", Some(Cor::Branco)).unwrap();
+    importado.recalcular();
+    let datas: Vec<String> = importado.historico(None).map(Registro::data).collect();
+    assert_eq!(datas, ["2024-03-01", "2024-03-02"]);
+    assert_eq!(importado.historico[0].variacao_elo, (16.0, -16.0));
+}
+
@@ -0,0 +1,62 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+
+use super::protocolo::{Comando, Mensagem};
+
+pub struct Remetente(TcpStream);
+
+pub struct Receptor(BufReader<TcpStream>);
+
+/// Conexão com um `damas_server`. Pode ser dividida para ler e escrever em threads diferentes.
+pub struct Cliente {
+    remetente: Remetente,
+    receptor: Receptor,
+}
+
+impl Cliente {
+    /// Conecta e já entra na fila de espera com o nome dado.
+    pub fn conectar(endereco: impl ToSocketAddrs, nome: &str) -> io::Result<Cliente> {
+        let conexao = TcpStream::connect(endereco)?;
+        let mut cliente = Cliente {
+            remetente: Remetente(conexao.try_clone()?),
+            receptor: Receptor(BufReader::new(conexao)),
+        };
+        cliente.enviar(&Comando::Entrar(nome.to_string()))?;
+        Ok(cliente)
+    }
+
+    pub fn enviar(&mut self, comando: &Comando) -> io::Result<()> {
+        self.remetente.enviar(comando)
+    }
+
+    pub fn receber(&mut self) -> io::Result<Mensagem> {
+        self.receptor.receber()
+    }
+
+    pub fn dividir(self) -> (Remetente, Receptor) {
+        (self.remetente, self.receptor)
+    }
+}
+
+impl Remetente {
+    pub fn enviar(&mut self, comando: &Comando) -> io::Result<()> {
+        writeln!(self.0, "{comando}")
+    }
+}
+
+impl Receptor {
+    /// Próxima mensagem do servidor. Fim da conexão vira `UnexpectedEof`.
+    pub fn receber(&mut self) -> io::Result<Mensagem> {
+        let mut linha = String::new();
+        loop {
+            if self.0.read_line(&mut linha)? == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            if !linha.trim().is_empty() {
+                break;
+            }
+            linha.clear();
+        }
+        Mensagem::ler(&linha).map_err(|erro| io::Error::new(io::ErrorKind::InvalidData, erro))
+    }
+}
@@ -0,0 +1,581 @@
+use std::fmt::Display;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+
+use crate::bot::Bot;
+use crate::notacao::{caminho, notacao};
+use crate::{Casa, Coord, Cor, Jogada, Partida, Pedra, PosicaoBuilder, Resultado};
+
+// DXP (DamExchange Protocol), o protocolo com que os programas de damas jogam
+// entre si por TCP. Cada mensagem é uma linha de campos de largura fixa,
+// terminada por um byte zero, e começa com uma letra que diz o tipo:
+//
+//   C  CHAT      texto livre
+//   R  GAMEREQ   versão(2) nome(32) cor do seguidor(W/Z) minutos(3) lances(3) posição(A, ou B + vez + casas)
+//   A  GAMEACC   nome(32) aceite(1)
+//   M  MOVE      segundos(4) origem(2) destino(2) capturas(2) casas capturadas(2 cada)
+//   E  GAMEEND   motivo(1) parar(1)
+//   B  BACKREQ   lance(3) vez(W/Z)
+//   K  BACKACC   aceite(1)
+//
+// O protocolo nasceu para o tabuleiro 10x10; aqui as casas vão de 1 a 32 e a
+// posição do GAMEREQ tem 32 casas com e (vazia), w/z (pedras) e W/Z (damas).
+
+const VERSAO: u32 = 1;
+const LARGURA_DO_NOME: usize = 32;
+
+#[derive(Debug)]
+pub enum ErroDxp {
+    Io(io::Error),
+    Malformado(String),
+    /// O seguidor recusou o convite com o código de aceite dado.
+    Recusado(u8),
+    /// O adversário mandou um lance que não está entre as jogadas possíveis.
+    LanceIlegal(String),
+    /// O bot não devolveu lance numa posição em que há jogadas.
+    SemLance(String),
+}
+
+impl Display for ErroDxp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErroDxp::Io(erro) => write!(f, "{erro}"),
+            ErroDxp::Malformado(motivo) => write!(f, "mensagem DXP malformada: {motivo}"),
+            ErroDxp::Recusado(codigo) => write!(f, "convite recusado (código {codigo})"),
+            ErroDxp::LanceIlegal(lance) => write!(f, "lance ilegal do adversário: {lance}"),
+            ErroDxp::SemLance(fen) => write!(f, "o bot não escolheu um lance em {fen}"),
+        }
+    }
+}
+
+impl std::error::Error for ErroDxp {}
+
+impl From<io::Error> for ErroDxp {
+    fn from(erro: io::Error) -> Self {
+        ErroDxp::Io(erro)
+    }
+}
+
+/// Condições da partida propostas pelo iniciador.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Convite {
+    pub cor_do_seguidor: Cor,
+    /// Tempo de cada jogador, em minutos.
+    pub minutos: u32,
+    /// Lances que cada jogador deve fazer dentro do tempo.
+    pub lances: u32,
+    /// Vez e tabuleiro iniciais; `None` para a posição inicial padrão.
+    pub posicao: Option<(Cor, [[Casa; 8]; 8])>,
+}
+
+impl Default for Convite {
+    fn default() -> Self {
+        Convite { cor_do_seguidor: Cor::Preto, minutos: 5, lances: 75, posicao: None }
+    }
+}
+
+impl Convite {
+    pub fn com_partida(mut self, partida: &Partida) -> Self {
+        self.posicao = Some((*partida.get_vez(), *partida.get_tabuleiro()));
+        self
+    }
+
+    /// A posição já foi validada em `MensagemDxp::ler` ou veio de uma `Partida`.
+    pub fn partida(&self) -> Partida {
+        match self.posicao {
+            Some((vez, tabuleiro)) => Partida::com_tabuleiro(tabuleiro, vez),
+            None => Partida::default(),
+        }
+    }
+}
+
+/// Resultado anunciado no GAMEEND, do ponto de vista de quem manda.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FimDxp {
+    Desconhecido,
+    Perdi,
+    Empate,
+    Ganhei,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MensagemDxp {
+    Chat(String),
+    GameReq { nome: String, convite: Convite },
+    /// Aceite 0 aceita; 1 recusa a cor, 2 recusa o tempo e 3 recusa por outro motivo.
+    GameAcc { nome: String, aceite: u8 },
+    Move { segundos: u32, origem: u8, destino: u8, capturadas: Vec<u8> },
+    /// Com `parar` quem manda não quer jogar outra partida na mesma conexão.
+    GameEnd { fim: FimDxp, parar: bool },
+    /// Pede para voltar a partida até o lance `lance`, com `vez` para jogar.
+    BackReq { lance: u32, vez: Cor },
+    /// Aceite 0 aceita; 1 não suporta e 2 recusa.
+    BackAcc { aceite: u8 },
+}
+
+fn letra_da_cor(jogador: Cor) -> char {
+    match jogador {
+        Cor::Branco => 'W',
+        Cor::Preto => 'Z',
+    }
+}
+
+// Lê os campos de largura fixa de uma mensagem, da esquerda para a direita
+struct Campos<'a> {
+    resto: &'a str,
+}
+
+impl<'a> Campos<'a> {
+    fn tomar(&mut self, largura: usize, campo: &str) -> Result<&'a str, ErroDxp> {
+        let fim = match self.resto.char_indices().nth(largura) {
+            Some((fim, _)) => fim,
+            None if self.resto.chars().count() == largura => self.resto.len(),
+            None => return Err(ErroDxp::Malformado(format!("{campo} incompleto"))),
+        };
+        let (campo, resto) = self.resto.split_at(fim);
+        self.resto = resto;
+        Ok(campo)
+    }
+
+    fn numero(&mut self, largura: usize, campo: &str) -> Result<u32, ErroDxp> {
+        let texto = self.tomar(largura, campo)?;
+        texto.trim().parse().map_err(|_| ErroDxp::Malformado(format!("{campo} não é um número: {texto}")))
+    }
+
+    fn cor(&mut self, campo: &str) -> Result<Cor, ErroDxp> {
+        match self.tomar(1, campo)? {
+            "W" => Ok(Cor::Branco),
+            "Z" => Ok(Cor::Preto),
+            outra => Err(ErroDxp::Malformado(format!("{campo} desconhecida: {outra}"))),
+        }
+    }
+}
+
+impl MensagemDxp {
+    pub fn ler(texto: &str) -> Result<MensagemDxp, ErroDxp> {
+        let texto = texto.trim_end_matches(['\0', '\r', '\n']);
+        let mut campos = Campos { resto: texto.get(1..).unwrap_or("") };
+        match texto.chars().next() {
+            Some('C') => Ok(MensagemDxp::Chat(campos.resto.to_string())),
+            Some('R') => {
+                campos.numero(2, "versão")?;
+                let nome = campos.tomar(LARGURA_DO_NOME, "nome")?.trim_end().to_string();
+                let cor_do_seguidor = campos.cor("cor do seguidor")?;
+                let minutos = campos.numero(3, "tempo")?;
+                let lances = campos.numero(3, "número de lances")?;
+                let posicao = match campos.tomar(1, "posição")? {
+                    "A" => None,
+                    "B" => {
+                        let vez = campos.cor("vez")?;
+                        let tabuleiro = ler_tabuleiro(campos.tomar(32, "tabuleiro")?)?;
+                        PosicaoBuilder::de_casas(&tabuleiro)
+                            .vez(vez)
+                            .construir()
+                            .map_err(|erro| ErroDxp::Malformado(format!("posição inválida: {erro}")))?;
+                        Some((vez, tabuleiro))
+                    }
+                    outra => return Err(ErroDxp::Malformado(format!("posição desconhecida: {outra}"))),
+                };
+                Ok(MensagemDxp::GameReq { nome, convite: Convite { cor_do_seguidor, minutos, lances, posicao } })
+            }
+            Some('A') => {
+                let nome = campos.tomar(LARGURA_DO_NOME, "nome")?.trim_end().to_string();
+                Ok(MensagemDxp::GameAcc { nome, aceite: campos.numero(1, "aceite")? as u8 })
+            }
+            Some('M') => {
+                let segundos = campos.numero(4, "tempo")?;
+                let origem = campos.numero(2, "origem")? as u8;
+                let destino = campos.numero(2, "destino")? as u8;
+                let quantas = campos.numero(2, "número de capturas")?;
+                let capturadas = (0..quantas)
+                    .map(|_| campos.numero(2, "casa capturada").map(|casa| casa as u8))
+                    .collect::<Result<_, _>>()?;
+                Ok(MensagemDxp::Move { segundos, origem, destino, capturadas })
+            }
+            Some('E') => {
+                let fim = match campos.numero(1, "motivo")? {
+                    1 => FimDxp::Perdi,
+                    2 => FimDxp::Empate,
+                    3 => FimDxp::Ganhei,
+                    _ => FimDxp::Desconhecido,
+                };
+                Ok(MensagemDxp::GameEnd { fim, parar: campos.numero(1, "código de parada")? == 1 })
+            }
+            Some('B') => Ok(MensagemDxp::BackReq { lance: campos.numero(3, "lance")?, vez: campos.cor("vez")? }),
+            Some('K') => Ok(MensagemDxp::BackAcc { aceite: campos.numero(1, "aceite")? as u8 }),
+            Some(outro) => Err(ErroDxp::Malformado(format!("tipo de mensagem desconhecido: {outro}"))),
+            None => Err(ErroDxp::Malformado("mensagem vazia".to_string())),
+        }
+    }
+
+    /// MOVE com a sequência dada.
+    pub fn de_jogada(sequencia: &[Jogada], segundos: u32) -> MensagemDxp {
+        let (origem, destino, capturadas) = casas_do_lance(sequencia);
+        MensagemDxp::Move { segundos, origem, destino, capturadas }
+    }
+
+    /// Índice em `todas_jogadas_possiveis` da sequência descrita por um MOVE. As
+    /// casas capturadas desfazem a ambiguidade entre caminhos com a mesma origem e destino.
+    pub fn jogada(&self, partida: &Partida) -> Option<usize> {
+        let MensagemDxp::Move { origem, destino, capturadas, .. } = self else {
+            return None;
+        };
+        let mut capturadas = capturadas.clone();
+        capturadas.sort_unstable();
+        partida.todas_jogadas_possiveis().iter().position(|sequencia| {
+            let (o, d, mut c) = casas_do_lance(sequencia);
+            c.sort_unstable();
+            o == *origem && d == *destino && c == capturadas
+        })
+    }
+}
+
+// Origem, destino e casas capturadas de uma sequência, na ordem das capturas
+fn casas_do_lance(sequencia: &[Jogada]) -> (u8, u8, Vec<u8>) {
+    let casas = caminho(sequencia);
+    let numero = |casa: Coord| casa.numero().expect("jogadas só passam por casas escuras");
+    let capturadas = sequencia
+        .iter()
+        .filter_map(|jogada| match jogada {
+            Jogada::Capturar(_, capturada, _) => Some(numero(*capturada)),
+            Jogada::Mover(..) => None,
+        })
+        .collect();
+    (numero(casas[0]), numero(casas[casas.len() - 1]), capturadas)
+}
+
+fn ler_tabuleiro(casas: &str) -> Result<[[Casa; 8]; 8], ErroDxp> {
+    let mut tabuleiro = [[Casa::Vazia; 8]; 8];
+    for (i, letra) in casas.chars().enumerate() {
+        let casa = Coord::de_numero(i as u8 + 1).expect("32 casas");
+        tabuleiro[casa.y as usize][casa.x as usize] = match letra {
+            'e' => Casa::Vazia,
+            'w' => Casa::Ocupada(Pedra::Branca),
+            'z' => Casa::Ocupada(Pedra::Preta),
+            'W' => Casa::Ocupada(Pedra::DamaBranca),
+            'Z' => Casa::Ocupada(Pedra::DamaPreta),
+            outra => return Err(ErroDxp::Malformado(format!("casa desconhecida: {outra}"))),
+        };
+    }
+    Ok(tabuleiro)
+}
+
+fn escrever_tabuleiro(tabuleiro: &[[Casa; 8]; 8]) -> String {
+    (1..=32)
+        .map(|numero| {
+            let casa = Coord::de_numero(numero).expect("32 casas");
+            match tabuleiro[casa.y as usize][casa.x as usize] {
+                Casa::Vazia => 'e',
+                Casa::Ocupada(Pedra::Branca) => 'w',
+                Casa::Ocupada(Pedra::Preta) => 'z',
+                Casa::Ocupada(Pedra::DamaBranca) => 'W',
+                Casa::Ocupada(Pedra::DamaPreta) => 'Z',
+            }
+        })
+        .collect()
+}
+
+fn nome_de_largura_fixa(nome: &str) -> String {
+    let nome: String = nome.chars().take(LARGURA_DO_NOME).collect();
+    format!("{nome:<LARGURA_DO_NOME$}")
+}
+
+impl Display for MensagemDxp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MensagemDxp::Chat(texto) => write!(f, "C{texto}"),
+            MensagemDxp::GameReq { nome, convite } => {
+                write!(
+                    f,
+                    "R{VERSAO:02}{}{}{:03}{:03}",
+                    nome_de_largura_fixa(nome),
+                    letra_da_cor(convite.cor_do_seguidor),
+                    convite.minutos,
+                    convite.lances
+                )?;
+                match &convite.posicao {
+                    None => write!(f, "A"),
+                    Some((vez, tabuleiro)) => write!(f, "B{}{}", letra_da_cor(*vez), escrever_tabuleiro(tabuleiro)),
+                }
+            }
+            MensagemDxp::GameAcc { nome, aceite } => write!(f, "A{}{aceite}", nome_de_largura_fixa(nome)),
+            MensagemDxp::Move { segundos, origem, destino, capturadas } => {
+                write!(f, "M{segundos:04}{origem:02}{destino:02}{:02}", capturadas.len())?;
+                capturadas.iter().try_for_each(|casa| write!(f, "{casa:02}"))
+            }
+            MensagemDxp::GameEnd { fim, parar } => {
+                let motivo = match fim {
+                    FimDxp::Desconhecido => 0,
+                    FimDxp::Perdi => 1,
+                    FimDxp::Empate => 2,
+                    FimDxp::Ganhei => 3,
+                };
+                write!(f, "E{motivo}{}", *parar as u8)
+            }
+            MensagemDxp::BackReq { lance, vez } => write!(f, "B{lance:03}{}", letra_da_cor(*vez)),
+            MensagemDxp::BackAcc { aceite } => write!(f, "K{aceite}"),
+        }
+    }
+}
+
+/// Conexão DXP sobre TCP, já separando as mensagens pelo byte zero.
+pub struct ConexaoDxp {
+    escrita: TcpStream,
+    leitura: BufReader<TcpStream>,
+}
+
+impl ConexaoDxp {
+    pub fn new(conexao: TcpStream) -> io::Result<ConexaoDxp> {
+        Ok(ConexaoDxp { escrita: conexao.try_clone()?, leitura: BufReader::new(conexao) })
+    }
+
+    pub fn enviar(&mut self, mensagem: &MensagemDxp) -> io::Result<()> {
+        write!(self.escrita, "{mensagem}\0")?;
+        self.escrita.flush()
+    }
+
+    pub fn receber(&mut self) -> Result<MensagemDxp, ErroDxp> {
+        let mut bytes = vec![];
+        if self.leitura.read_until(0, &mut bytes)? == 0 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        let texto = String::from_utf8_lossy(&bytes);
+        MensagemDxp::ler(&texto)
+    }
+}
+
+/// O que aconteceu durante a partida, para quem quiser mostrar ao usuário.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AvisoDxp {
+    Lance(Cor, String),
+    Chat(String),
+    /// O adversário voltou a partida e estes lances foram desfeitos.
+    Voltou(usize),
+}
+
+/// Uma partida DXP em andamento, do ponto de vista de um dos dois programas.
+pub struct SessaoDxp {
+    conexao: ConexaoDxp,
+    pub partida: Partida,
+    /// Cor com que este lado joga.
+    pub cor: Cor,
+    pub adversario: String,
+    convite: Convite,
+    restante: Duration,
+    // Vencedor quando as regras encerram a partida; `Some(None)` é empate
+    fim: Option<Option<Cor>>,
+}
+
+impl SessaoDxp {
+    fn new(conexao: ConexaoDxp, cor: Cor, adversario: String, convite: Convite) -> Self {
+        SessaoDxp {
+            conexao,
+            partida: convite.partida(),
+            cor,
+            adversario,
+            restante: Duration::from_secs(convite.minutos as u64 * 60),
+            convite,
+            fim: None,
+        }
+    }
+
+    /// Papel de iniciador: convida e espera o GAMEACC do seguidor.
+    pub fn iniciar(conexao: TcpStream, nome: &str, convite: Convite) -> Result<SessaoDxp, ErroDxp> {
+        let mut conexao = ConexaoDxp::new(conexao)?;
+        conexao.enviar(&MensagemDxp::GameReq { nome: nome.to_string(), convite: convite.clone() })?;
+        loop {
+            match conexao.receber()? {
+                MensagemDxp::GameAcc { nome, aceite: 0 } => {
+                    let cor = convite.cor_do_seguidor.adversario();
+                    return Ok(SessaoDxp::new(conexao, cor, nome, convite));
+                }
+                MensagemDxp::GameAcc { aceite, .. } => return Err(ErroDxp::Recusado(aceite)),
+                MensagemDxp::Chat(_) => (),
+                outra => return Err(ErroDxp::Malformado(format!("esperava GAMEACC, veio {outra}"))),
+            }
+        }
+    }
+
+    /// Papel de seguidor: espera o GAMEREQ e aceita as condições propostas.
+    pub fn seguir(conexao: TcpStream, nome: &str) -> Result<SessaoDxp, ErroDxp> {
+        let mut conexao = ConexaoDxp::new(conexao)?;
+        loop {
+            match conexao.receber()? {
+                MensagemDxp::GameReq { nome: iniciador, convite } => {
+                    conexao.enviar(&MensagemDxp::GameAcc { nome: nome.to_string(), aceite: 0 })?;
+                    return Ok(SessaoDxp::new(conexao, convite.cor_do_seguidor, iniciador, convite));
+                }
+                MensagemDxp::Chat(_) => (),
+                outra => return Err(ErroDxp::Malformado(format!("esperava GAMEREQ, veio {outra}"))),
+            }
+        }
+    }
+
+    pub fn conversar(&mut self, texto: &str) -> io::Result<()> {
+        self.conexao.enviar(&MensagemDxp::Chat(texto.to_string()))
+    }
+
+    /// Joga a partida até o GAMEEND, escolhendo os lances com o bot dentro do
+    /// tempo do convite. Retorna o vencedor, ou None para empate ou resultado desconhecido.
+    pub fn jogar(&mut self, bot: &Bot, mut aviso: impl FnMut(AvisoDxp)) -> Result<Option<Cor>, ErroDxp> {
+        let mut fim_enviado = false;
+        loop {
+            if let Some(vencedor) = self.fim {
+                if !fim_enviado {
+                    let fim = match vencedor {
+                        None => FimDxp::Empate,
+                        Some(vencedor) if vencedor == self.cor => FimDxp::Ganhei,
+                        Some(_) => FimDxp::Perdi,
+                    };
+                    self.conexao.enviar(&MensagemDxp::GameEnd { fim, parar: false })?;
+                    fim_enviado = true;
+                }
+            } else if *self.partida.get_vez() == self.cor {
+                let lance = self.lance_do_bot(bot)?;
+                aviso(AvisoDxp::Lance(self.cor, lance));
+                continue;
+            }
+
+            match self.conexao.receber()? {
+                lance @ MensagemDxp::Move { .. } => {
+                    if self.fim.is_some() || *self.partida.get_vez() == self.cor {
+                        return Err(ErroDxp::LanceIlegal(format!("{lance} fora da vez")));
+                    }
+                    let indice = lance.jogada(&self.partida).ok_or_else(|| ErroDxp::LanceIlegal(lance.to_string()))?;
+                    let texto = notacao(&self.partida.todas_jogadas_possiveis()[indice]);
+                    self.aplicar(indice);
+                    aviso(AvisoDxp::Lance(self.cor.adversario(), texto));
+                }
+                MensagemDxp::GameEnd { fim, .. } => {
+                    if !fim_enviado {
+                        // Sem um fim pelas regras, quem encerrou desistiu ou reclamou o resultado
+                        let nosso = match fim {
+                            FimDxp::Perdi => FimDxp::Ganhei,
+                            FimDxp::Ganhei => FimDxp::Perdi,
+                            outro => outro,
+                        };
+                        self.conexao.enviar(&MensagemDxp::GameEnd { fim: nosso, parar: false })?;
+                    }
+                    return Ok(match (self.fim, fim) {
+                        (Some(vencedor), _) => vencedor,
+                        (None, FimDxp::Perdi) => Some(self.cor),
+                        (None, FimDxp::Ganhei) => Some(self.cor.adversario()),
+                        (None, _) => None,
+                    });
+                }
+                MensagemDxp::Chat(texto) => aviso(AvisoDxp::Chat(texto)),
+                MensagemDxp::BackReq { lance, vez } => {
+                    let resposta = match self.voltar(lance, vez) {
+                        Some(desfeitos) => {
+                            aviso(AvisoDxp::Voltou(desfeitos));
+                            0
+                        }
+                        None => 2,
+                    };
+                    self.conexao.enviar(&MensagemDxp::BackAcc { aceite: resposta })?;
+                }
+                // Este lado nunca pede para voltar lances
+                MensagemDxp::BackAcc { .. } => (),
+                outra => return Err(ErroDxp::Malformado(format!("mensagem inesperada durante a partida: {outra}"))),
+            }
+        }
+    }
+
+    fn aplicar(&mut self, indice: usize) {
+        if let Resultado::FimDoJogo(vencedor) = self.partida.jogar(indice) {
+            self.fim = Some(vencedor);
+        }
+    }
+
+    fn lance_do_bot(&mut self, bot: &Bot) -> Result<String, ErroDxp> {
+        // O tempo que sobra é dividido entre os lances que faltam no controle
+        let feitos = self.partida.historico().len() as u32 / 2;
+        let faltam = self.convite.lances.saturating_sub(feitos).max(1);
+        let orcamento = self.restante / faltam;
+        let bot = Bot { tempo: Some(bot.tempo.map_or(orcamento, |tempo| tempo.min(orcamento))), ..*bot };
+
+        let inicio = Instant::now();
+        let indice = bot.escolher(&self.partida).ok_or_else(|| ErroDxp::SemLance(self.partida.fen()))?;
+        let gasto = inicio.elapsed();
+        self.restante = self.restante.saturating_sub(gasto);
+
+        let sequencia = self.partida.todas_jogadas_possiveis()[indice].clone();
+        self.conexao.enviar(&MensagemDxp::de_jogada(&sequencia, gasto.as_secs() as u32))?;
+        self.aplicar(indice);
+        Ok(notacao(&sequencia))
+    }
+
+    // Volta ao lance `lance` com `vez` para jogar, contando os lances como no PDN.
+    // Retorna quantos lances foram desfeitos, ou None se o pedido não faz sentido.
+    fn voltar(&mut self, lance: u32, vez: Cor) -> Option<usize> {
+        let inicial = self.convite.partida();
+        let deslocamento = (*inicial.get_vez() == Cor::Preto) as usize;
+        let alvo = ((lance as usize).checked_sub(1)? * 2 + (vez == Cor::Preto) as usize).checked_sub(deslocamento)?;
+        let jogados = self.partida.historico().len();
+        if alvo > jogados {
+            return None;
+        }
+        for _ in alvo..jogados {
+            self.partida.desfazer();
+        }
+        self.fim = None;
+        Some(jogados - alvo)
+    }
+}
+
+#[test]
+fn testar_mensagens_dxp() {
+    let partida = Partida::from_fen("B:W22,K30:B18,11").unwrap();
+    let mensagens = [
+        MensagemDxp::Chat("olá".to_string()),
+        MensagemDxp::GameReq { nome: "damas".to_string(), convite: Convite::default() },
+        MensagemDxp::GameReq { nome: "damas".to_string(), convite: Convite::default().com_partida(&partida) },
+        MensagemDxp::GameAcc { nome: "outro".to_string(), aceite: 0 },
+        MensagemDxp::Move { segundos: 12, origem: 22, destino: 8, capturadas: vec![18, 11] },
+        MensagemDxp::GameEnd { fim: FimDxp::Ganhei, parar: true },
+        MensagemDxp::BackReq { lance: 3, vez: Cor::Preto },
+        MensagemDxp::BackAcc { aceite: 2 },
+    ];
+    for mensagem in mensagens {
+        assert_eq!(MensagemDxp::ler(&mensagem.to_string()).unwrap(), mensagem);
+    }
+    assert_eq!(
+        MensagemDxp::Move { segundos: 5, origem: 22, destino: 8, capturadas: vec![11, 18] }.to_string(),
+        "M00052208021118"
+    );
+    assert_eq!(MensagemDxp::ler("R01damas").err().map(|erro| erro.to_string()), Some("mensagem DXP malformada: nome incompleto".to_string()));
+    // Convite com um peão branco na casa 1, onde já seria dama
+    let convite = MensagemDxp::GameReq { nome: "damas".to_string(), convite: Convite::default().com_partida(&partida) }.to_string();
+    let convite = format!("{}w{}", &convite[..convite.len() - 32], &convite[convite.len() - 31..]);
+    assert!(matches!(MensagemDxp::ler(&convite), Err(ErroDxp::Malformado(motivo)) if motivo.contains("casa 1")));
+
+    // As capturas podem vir em qualquer ordem
+    let partida = Partida::from_fen("W:W22:B18,11").unwrap();
+    let lance = MensagemDxp::Move { segundos: 0, origem: 22, destino: 8, capturadas: vec![11, 18] };
+    assert_eq!(lance.jogada(&partida), Some(0));
+    assert_eq!(MensagemDxp::de_jogada(&partida.todas_jogadas_possiveis()[0], 0).to_string(), "M00002208021811");
+}
+
+#[test]
+fn testar_partida_dxp() {
+    use std::net::TcpListener;
+    use std::thread;
+
+    let escuta = TcpListener::bind("127.0.0.1:0").unwrap();
+    let endereco = escuta.local_addr().unwrap();
+    let seguidor = thread::spawn(move || {
+        let (conexao, _) = escuta.accept().unwrap();
+        let mut sessao = SessaoDxp::seguir(conexao, "seguidor").unwrap();
+        assert_eq!((sessao.cor, sessao.adversario.as_str()), (Cor::Preto, "iniciador"));
+        sessao.jogar(&Bot::new(2), |_| ()).unwrap()
+    });
+
+    let convite = Convite::default().com_partida(&Partida::from_fen("W:W22:B18,11").unwrap());
+    let mut sessao = SessaoDxp::iniciar(TcpStream::connect(endereco).unwrap(), "iniciador", convite).unwrap();
+    let mut lances = vec![];
+    let vencedor = sessao.jogar(&Bot::new(2), |aviso| lances.push(aviso)).unwrap();
+    assert_eq!(vencedor, Some(Cor::Branco));
+    assert_eq!(lances, vec![AvisoDxp::Lance(Cor::Branco, "22x15x8".to_string())]);
+    assert_eq!(seguidor.join().unwrap(), Some(Cor::Branco));
+}
@@ -0,0 +1,10 @@
+// Jogo em rede por TCP com um protocolo de linhas de texto. O servidor guarda
+// a `Partida` oficial e só aceita jogadas que estão na lista de jogadas possíveis.
+// Com a feature `ws` há também um servidor JSON sobre WebSocket para o frontend web.
+
+pub mod protocolo;
+pub mod servidor;
+pub mod cliente;
+pub mod dxp;
+#[cfg(feature = "ws")]
+pub mod ws;
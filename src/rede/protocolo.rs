@@ -0,0 +1,223 @@
+use std::fmt::Display;
+
+use crate::{Cor, Estado, MotivoEmpate, MotivoVitoria};
+
+// Cada mensagem é uma linha: uma palavra-chave em maiúsculas seguida dos argumentos.
+//
+// Cliente -> servidor:
+//   ENTRAR <nome>        entra na fila e espera um adversário
+//   JOGAR <lance>        joga um lance em notação (22-18, 22x13, c3-d4)
+//   DESISTIR             abandona a partida
+//   EMPATE               oferece empate, ou aceita a oferta do adversário
+//   RECUSAR              recusa a oferta de empate do adversário
+//   SAIR                 encerra a conexão
+//
+// Servidor -> cliente:
+//   BEMVINDO <cor>       cor atribuída ao jogador
+//   AGUARDANDO           esperando o adversário
+//   INICIO <branco> <preto>
+//   LANCE <cor> <lance>  lance aceito pelo servidor
+//   TABULEIRO <fen>      posição atual
+//   VEZ <cor>
+//   EMPATE <cor>         a cor ofereceu empate
+//   RECUSADO <cor>       a cor recusou o empate
+//   FIM <resultado> <motivo>   resultado é 1-0, 0-1 ou 1/2-1/2
+//   ERRO <mensagem>
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Comando {
+    Entrar(String),
+    Jogar(String),
+    Desistir,
+    Empate,
+    Recusar,
+    Sair,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Motivo {
+    /// O adversário ficou sem pedras.
+    Vitoria,
+    /// O adversário ficou sem jogadas.
+    SemJogadas,
+    Desistencia,
+    Desconexao,
+    /// Empate pela regra das jogadas de dama.
+    Empate,
+    Acordo,
+    /// O relógio do adversário chegou a zero.
+    Tempo,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mensagem {
+    BemVindo(Cor),
+    Aguardando,
+    Inicio { branco: String, preto: String },
+    Lance(Cor, String),
+    Tabuleiro(String),
+    Vez(Cor),
+    Empate(Cor),
+    Recusado(Cor),
+    Fim { vencedor: Option<Cor>, motivo: Motivo },
+    Erro(String),
+}
+
+pub(crate) fn cor(jogador: Cor) -> &'static str {
+    match jogador {
+        Cor::Branco => "BRANCO",
+        Cor::Preto => "PRETO",
+    }
+}
+
+pub(crate) fn ler_cor(texto: &str) -> Result<Cor, String> {
+    match texto {
+        "BRANCO" => Ok(Cor::Branco),
+        "PRETO" => Ok(Cor::Preto),
+        _ => Err(format!("cor desconhecida: {texto}")),
+    }
+}
+
+impl Motivo {
+    /// Por que a partida acabou, se acabou.
+    pub(crate) fn do_estado(estado: Estado) -> Option<Motivo> {
+        match estado {
+            Estado::EmAndamento => None,
+            Estado::Vitoria(_, MotivoVitoria::SemPedras) => Some(Motivo::Vitoria),
+            Estado::Vitoria(_, MotivoVitoria::SemJogadas) => Some(Motivo::SemJogadas),
+            Estado::Vitoria(_, MotivoVitoria::Desistencia) => Some(Motivo::Desistencia),
+            Estado::Vitoria(_, MotivoVitoria::Tempo) => Some(Motivo::Tempo),
+            Estado::Empate(MotivoEmpate::Acordo) => Some(Motivo::Acordo),
+            Estado::Empate(_) => Some(Motivo::Empate),
+        }
+    }
+
+    pub(crate) fn nome(self) -> &'static str {
+        match self {
+            Motivo::Vitoria => "vitoria",
+            Motivo::SemJogadas => "sem-jogadas",
+            Motivo::Desistencia => "desistencia",
+            Motivo::Desconexao => "desconexao",
+            Motivo::Empate => "empate",
+            Motivo::Acordo => "acordo",
+            Motivo::Tempo => "tempo",
+        }
+    }
+
+    fn ler(texto: &str) -> Result<Motivo, String> {
+        [
+            Motivo::Vitoria,
+            Motivo::SemJogadas,
+            Motivo::Desistencia,
+            Motivo::Desconexao,
+            Motivo::Empate,
+            Motivo::Acordo,
+            Motivo::Tempo,
+        ]
+        .into_iter()
+        .find(|motivo| motivo.nome() == texto)
+        .ok_or_else(|| format!("motivo desconhecido: {texto}"))
+    }
+}
+
+fn separar(linha: &str) -> (&str, &str) {
+    let linha = linha.trim();
+    match linha.split_once(char::is_whitespace) {
+        Some((palavra, resto)) => (palavra, resto.trim()),
+        None => (linha, ""),
+    }
+}
+
+fn exigir<'a>(palavra: &str, argumento: &'a str) -> Result<&'a str, String> {
+    if argumento.is_empty() {
+        Err(format!("{palavra} precisa de um argumento"))
+    } else {
+        Ok(argumento)
+    }
+}
+
+impl Comando {
+    pub fn ler(linha: &str) -> Result<Comando, String> {
+        let (palavra, argumento) = separar(linha);
+        match palavra {
+            "ENTRAR" => Ok(Comando::Entrar(exigir(palavra, argumento)?.to_string())),
+            "JOGAR" => Ok(Comando::Jogar(exigir(palavra, argumento)?.to_string())),
+            "DESISTIR" => Ok(Comando::Desistir),
+            "EMPATE" => Ok(Comando::Empate),
+            "RECUSAR" => Ok(Comando::Recusar),
+            "SAIR" => Ok(Comando::Sair),
+            _ => Err(format!("comando desconhecido: {palavra}")),
+        }
+    }
+}
+
+impl Display for Comando {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Comando::Entrar(nome) => write!(f, "ENTRAR {nome}"),
+            Comando::Jogar(lance) => write!(f, "JOGAR {lance}"),
+            Comando::Desistir => write!(f, "DESISTIR"),
+            Comando::Empate => write!(f, "EMPATE"),
+            Comando::Recusar => write!(f, "RECUSAR"),
+            Comando::Sair => write!(f, "SAIR"),
+        }
+    }
+}
+
+impl Mensagem {
+    pub fn ler(linha: &str) -> Result<Mensagem, String> {
+        let (palavra, argumento) = separar(linha);
+        match palavra {
+            "BEMVINDO" => Ok(Mensagem::BemVindo(ler_cor(argumento)?)),
+            "AGUARDANDO" => Ok(Mensagem::Aguardando),
+            "INICIO" => {
+                let (branco, preto) = separar(argumento);
+                Ok(Mensagem::Inicio { branco: branco.to_string(), preto: preto.to_string() })
+            }
+            "LANCE" => {
+                let (jogador, lance) = separar(argumento);
+                Ok(Mensagem::Lance(ler_cor(jogador)?, exigir(palavra, lance)?.to_string()))
+            }
+            "TABULEIRO" => Ok(Mensagem::Tabuleiro(exigir(palavra, argumento)?.to_string())),
+            "VEZ" => Ok(Mensagem::Vez(ler_cor(argumento)?)),
+            "EMPATE" => Ok(Mensagem::Empate(ler_cor(argumento)?)),
+            "RECUSADO" => Ok(Mensagem::Recusado(ler_cor(argumento)?)),
+            "FIM" => {
+                let (resultado, motivo) = separar(argumento);
+                let vencedor = match resultado {
+                    "1-0" => Some(Cor::Branco),
+                    "0-1" => Some(Cor::Preto),
+                    "1/2-1/2" => None,
+                    _ => return Err(format!("resultado desconhecido: {resultado}")),
+                };
+                Ok(Mensagem::Fim { vencedor, motivo: Motivo::ler(motivo)? })
+            }
+            "ERRO" => Ok(Mensagem::Erro(argumento.to_string())),
+            _ => Err(format!("mensagem desconhecida: {palavra}")),
+        }
+    }
+}
+
+impl Display for Mensagem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Mensagem::BemVindo(jogador) => write!(f, "BEMVINDO {}", cor(*jogador)),
+            Mensagem::Aguardando => write!(f, "AGUARDANDO"),
+            Mensagem::Inicio { branco, preto } => write!(f, "INICIO {branco} {preto}"),
+            Mensagem::Lance(jogador, lance) => write!(f, "LANCE {} {lance}", cor(*jogador)),
+            Mensagem::Tabuleiro(fen) => write!(f, "TABULEIRO {fen}"),
+            Mensagem::Vez(jogador) => write!(f, "VEZ {}", cor(*jogador)),
+            Mensagem::Empate(jogador) => write!(f, "EMPATE {}", cor(*jogador)),
+            Mensagem::Recusado(jogador) => write!(f, "RECUSADO {}", cor(*jogador)),
+            Mensagem::Fim { vencedor, motivo } => {
+                let resultado = match vencedor {
+                    Some(Cor::Branco) => "1-0",
+                    Some(Cor::Preto) => "0-1",
+                    None => "1/2-1/2",
+                };
+                write!(f, "FIM {resultado} {}", motivo.nome())
+            }
+            Mensagem::Erro(mensagem) => write!(f, "ERRO {mensagem}"),
+        }
+    }
+}
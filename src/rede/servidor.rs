@@ -0,0 +1,301 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use super::protocolo::{Comando, Mensagem, Motivo};
+use crate::notacao::notacao;
+use crate::{Cor, Partida, Resultado};
+
+// Cada conexão que manda ENTRAR vai para a fila; a cada dois jogadores o
+// servidor abre uma partida numa thread própria. O primeiro da dupla joga de brancas.
+
+struct Conectado {
+    nome: String,
+    escrita: TcpStream,
+    leitura: BufReader<TcpStream>,
+}
+
+enum Evento {
+    Comando(Cor, Comando),
+    Invalido(Cor, String),
+    Desconectou(Cor),
+}
+
+pub struct Servidor {
+    listener: TcpListener,
+}
+
+impl Servidor {
+    pub fn escutar(endereco: impl ToSocketAddrs) -> io::Result<Servidor> {
+        Ok(Servidor { listener: TcpListener::bind(endereco)? })
+    }
+
+    /// Endereço em que o servidor está escutando, útil quando a porta é 0.
+    pub fn endereco(&self) -> io::Result<std::net::SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Aceita conexões para sempre, formando duplas e abrindo partidas.
+    pub fn servir(self) -> io::Result<()> {
+        let (fila, chegadas) = mpsc::channel::<Conectado>();
+        thread::spawn(move || formar_duplas(chegadas));
+        for conexao in self.listener.incoming() {
+            let conexao = match conexao {
+                Ok(conexao) => conexao,
+                Err(_) => continue,
+            };
+            let fila = fila.clone();
+            thread::spawn(move || {
+                if let Ok(conectado) = recepcionar(conexao) {
+                    fila.send(conectado).ok();
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+fn enviar(conexao: &mut TcpStream, mensagem: &Mensagem) {
+    // Um cliente que caiu é tratado pela thread de leitura
+    writeln!(conexao, "{mensagem}").ok();
+}
+
+// Espera o ENTRAR de uma conexão nova
+fn recepcionar(conexao: TcpStream) -> io::Result<Conectado> {
+    let mut escrita = conexao.try_clone()?;
+    let mut leitura = BufReader::new(conexao);
+    let mut linha = String::new();
+    loop {
+        linha.clear();
+        if leitura.read_line(&mut linha)? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        match Comando::ler(&linha) {
+            Ok(Comando::Entrar(nome)) => {
+                let nome = nome.split_whitespace().collect::<Vec<_>>().join("_");
+                return Ok(Conectado { nome, escrita, leitura });
+            }
+            Ok(Comando::Sair) => return Err(io::ErrorKind::ConnectionAborted.into()),
+            Ok(_) => enviar(&mut escrita, &Mensagem::Erro("mande ENTRAR <nome> primeiro".to_string())),
+            Err(erro) => enviar(&mut escrita, &Mensagem::Erro(erro)),
+        }
+    }
+}
+
+fn formar_duplas(chegadas: Receiver<Conectado>) {
+    let mut esperando: Option<Conectado> = None;
+    while let Ok(mut chegou) = chegadas.recv() {
+        // Quem caiu enquanto esperava não forma dupla; o recém-chegado toma o lugar
+        match esperando.take().filter(ainda_conectado) {
+            Some(branco) => {
+                enviar(&mut chegou.escrita, &Mensagem::BemVindo(Cor::Preto));
+                thread::spawn(move || SalaDeJogo::new(branco, chegou).jogar());
+            }
+            None => {
+                enviar(&mut chegou.escrita, &Mensagem::BemVindo(Cor::Branco));
+                enviar(&mut chegou.escrita, &Mensagem::Aguardando);
+                esperando = Some(chegou);
+            }
+        }
+    }
+}
+
+// Espia a conexão sem bloquear: fim de arquivo ou erro é cliente que já saiu
+fn ainda_conectado(conectado: &Conectado) -> bool {
+    if !conectado.leitura.buffer().is_empty() {
+        return true;
+    }
+    let socket = conectado.leitura.get_ref();
+    if socket.set_nonblocking(true).is_err() {
+        return false;
+    }
+    let espiado = socket.peek(&mut [0u8]);
+    socket.set_nonblocking(false).ok();
+    match espiado {
+        Ok(lidos) => lidos > 0,
+        Err(erro) => erro.kind() == io::ErrorKind::WouldBlock,
+    }
+}
+
+fn ler_comandos(jogador: Cor, mut leitura: BufReader<TcpStream>, eventos: Sender<Evento>) {
+    let mut linha = String::new();
+    loop {
+        linha.clear();
+        match leitura.read_line(&mut linha) {
+            Ok(0) | Err(_) => break,
+            Ok(_) if linha.trim().is_empty() => continue,
+            Ok(_) => {
+                let evento = match Comando::ler(&linha) {
+                    Ok(comando) => Evento::Comando(jogador, comando),
+                    Err(erro) => Evento::Invalido(jogador, erro),
+                };
+                if eventos.send(evento).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+    eventos.send(Evento::Desconectou(jogador)).ok();
+}
+
+struct SalaDeJogo {
+    partida: Partida,
+    conexoes: [TcpStream; 2],
+    eventos: Receiver<Evento>,
+    // Quem ofereceu empate e ainda espera resposta
+    oferta: Option<Cor>,
+}
+
+pub(super) fn indice(jogador: Cor) -> usize {
+    match jogador {
+        Cor::Branco => 0,
+        Cor::Preto => 1,
+    }
+}
+
+impl SalaDeJogo {
+    fn new(branco: Conectado, preto: Conectado) -> Self {
+        let (remetente, eventos) = mpsc::channel();
+        let inicio = Mensagem::Inicio { branco: branco.nome.clone(), preto: preto.nome.clone() };
+        let mut sala = SalaDeJogo {
+            partida: Partida::default(),
+            conexoes: [branco.escrita, preto.escrita],
+            eventos,
+            oferta: None,
+        };
+        for (jogador, leitura) in [(Cor::Branco, branco.leitura), (Cor::Preto, preto.leitura)] {
+            let remetente = remetente.clone();
+            thread::spawn(move || ler_comandos(jogador, leitura, remetente));
+        }
+        sala.todos(&inicio);
+        sala.todos(&Mensagem::Tabuleiro(sala.partida.fen()));
+        sala.todos(&Mensagem::Vez(*sala.partida.get_vez()));
+        sala
+    }
+
+    fn todos(&mut self, mensagem: &Mensagem) {
+        for conexao in &mut self.conexoes {
+            enviar(conexao, mensagem);
+        }
+    }
+
+    fn para(&mut self, jogador: Cor, mensagem: &Mensagem) {
+        enviar(&mut self.conexoes[indice(jogador)], mensagem);
+    }
+
+    fn jogar(mut self) {
+        while let Ok(evento) = self.eventos.recv() {
+            // A partida ainda está em andamento aqui, então desistir sempre é aceito
+            let mut desconexao = false;
+            match evento {
+                Evento::Invalido(jogador, erro) => self.para(jogador, &Mensagem::Erro(erro)),
+                Evento::Desconectou(jogador) => {
+                    self.partida.desistir(jogador).ok();
+                    desconexao = true;
+                }
+                Evento::Comando(jogador, comando) => self.tratar(jogador, comando),
+            }
+            let estado = self.partida.estado();
+            // Quem cai perde como quem desiste, mas o aviso diz o que houve
+            let motivo = if desconexao { Some(Motivo::Desconexao) } else { Motivo::do_estado(estado) };
+            if let Some(motivo) = motivo {
+                self.todos(&Mensagem::Fim { vencedor: estado.vencedor(), motivo });
+                for conexao in &self.conexoes {
+                    conexao.shutdown(std::net::Shutdown::Both).ok();
+                }
+                return;
+            }
+        }
+    }
+
+    fn tratar(&mut self, jogador: Cor, comando: Comando) {
+        match comando {
+            Comando::Entrar(_) => self.para(jogador, &Mensagem::Erro("você já está numa partida".to_string())),
+            Comando::Sair | Comando::Desistir => {
+                self.partida.desistir(jogador).ok();
+            }
+            Comando::Empate if self.oferta == Some(jogador.adversario()) => {
+                self.partida.empatar_por_acordo().ok();
+            }
+            Comando::Empate => {
+                self.oferta = Some(jogador);
+                self.todos(&Mensagem::Empate(jogador));
+            }
+            Comando::Recusar if self.oferta == Some(jogador.adversario()) => {
+                self.oferta = None;
+                self.todos(&Mensagem::Recusado(jogador));
+            }
+            Comando::Recusar => self.para(jogador, &Mensagem::Erro("não há oferta de empate".to_string())),
+            Comando::Jogar(lance) => self.jogar_lance(jogador, &lance),
+        }
+    }
+
+    fn jogar_lance(&mut self, jogador: Cor, lance: &str) {
+        if *self.partida.get_vez() != jogador {
+            self.para(jogador, &Mensagem::Erro("não é a sua vez".to_string()));
+            return;
+        }
+        let indice = match self.partida.encontrar_jogada(lance) {
+            Ok(indice) => indice,
+            Err(erro) => {
+                self.para(jogador, &Mensagem::Erro(erro.to_string()));
+                return;
+            }
+        };
+        let jogada = notacao(&self.partida.todas_jogadas_possiveis()[indice]);
+        // Jogar recusa implicitamente uma oferta de empate pendente
+        self.oferta = None;
+        let resultado = self.partida.jogar(indice);
+        self.todos(&Mensagem::Lance(jogador, jogada));
+        self.todos(&Mensagem::Tabuleiro(self.partida.fen()));
+        if let Resultado::Sucesso = resultado {
+            self.todos(&Mensagem::Vez(*self.partida.get_vez()));
+        }
+    }
+}
+
+#[test]
+fn testar_partida_em_rede() {
+    use super::cliente::Cliente;
+
+    let servidor = Servidor::escutar("127.0.0.1:0").unwrap();
+    let endereco = servidor.endereco().unwrap();
+    thread::spawn(move || servidor.servir());
+
+    let mut branco = Cliente::conectar(endereco, "ana").unwrap();
+    assert_eq!(branco.receber().unwrap(), Mensagem::BemVindo(Cor::Branco));
+    assert_eq!(branco.receber().unwrap(), Mensagem::Aguardando);
+    let mut preto = Cliente::conectar(endereco, "bia").unwrap();
+    assert_eq!(preto.receber().unwrap(), Mensagem::BemVindo(Cor::Preto));
+    for cliente in [&mut branco, &mut preto] {
+        let inicio = Mensagem::Inicio { branco: "ana".to_string(), preto: "bia".to_string() };
+        assert_eq!(cliente.receber().unwrap(), inicio);
+        assert_eq!(cliente.receber().unwrap(), Mensagem::Tabuleiro(Partida::default().fen()));
+        assert_eq!(cliente.receber().unwrap(), Mensagem::Vez(Cor::Branco));
+    }
+
+    // Jogada ilegal e jogada fora da vez são recusadas
+    branco.enviar(&Comando::Jogar("22-15".to_string())).unwrap();
+    assert!(matches!(branco.receber().unwrap(), Mensagem::Erro(_)));
+    preto.enviar(&Comando::Jogar("11-15".to_string())).unwrap();
+    assert!(matches!(preto.receber().unwrap(), Mensagem::Erro(_)));
+
+    branco.enviar(&Comando::Jogar("c3-d4".to_string())).unwrap();
+    assert_eq!(preto.receber().unwrap(), Mensagem::Lance(Cor::Branco, "22-18".to_string()));
+    assert!(matches!(preto.receber().unwrap(), Mensagem::Tabuleiro(_)));
+    assert_eq!(preto.receber().unwrap(), Mensagem::Vez(Cor::Preto));
+
+    preto.enviar(&Comando::Desistir).unwrap();
+    let fim = Mensagem::Fim { vencedor: Some(Cor::Branco), motivo: Motivo::Desistencia };
+    assert_eq!(preto.receber().unwrap(), fim);
+
+    // Quem saiu da fila antes de ter adversário não é pareado
+    let mut desistente = Cliente::conectar(endereco, "caio").unwrap();
+    assert_eq!(desistente.receber().unwrap(), Mensagem::BemVindo(Cor::Branco));
+    assert_eq!(desistente.receber().unwrap(), Mensagem::Aguardando);
+    drop(desistente);
+    thread::sleep(std::time::Duration::from_millis(50));
+    let mut novo = Cliente::conectar(endereco, "davi").unwrap();
+    assert_eq!(novo.receber().unwrap(), Mensagem::BemVindo(Cor::Branco));
+}
@@ -0,0 +1,567 @@
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::io::{self, ErrorKind};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+
+use super::protocolo::{cor, Motivo};
+use super::servidor::indice;
+use crate::notacao::notacao;
+use crate::{Casa, Cor, Partida, Pedra, Resultado};
+
+// Servidor JSON sobre WebSocket para o frontend web. Cada sala tem uma `Partida`
+// oficial, dois assentos e quantos espectadores quiser; depois de cada lance
+// todos na sala recebem o estado completo. Cada mensagem é um objeto com o campo "tipo".
+//
+// Cliente -> servidor:
+//   entrar      {sala, nome, espectador?}   ocupa o primeiro assento livre da sala
+//   reconectar  {sala, token}               volta ao assento depois de perder a conexão
+//   jogar       {lance}
+//   desistir
+//
+// Servidor -> cliente:
+//   bem_vindo   {sala, cor, token}   cor e token são nulos para espectadores
+//   estado      {tabuleiro, fen, vez, jogadas, contador_empate, relogio, ...}
+//   fim         {vencedor, motivo}
+//   erro        {mensagem}
+
+/// Tempo de cada jogador quando o servidor não recebe outro.
+pub const TEMPO_PADRAO: Duration = Duration::from_secs(10 * 60);
+
+// Quanto a thread de uma conexão espera por dados antes de olhar a caixa de saída
+const ESPERA: Duration = Duration::from_millis(20);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "tipo", rename_all = "snake_case")]
+pub enum Pedido {
+    Entrar {
+        sala: String,
+        nome: String,
+        #[serde(default)]
+        espectador: bool,
+    },
+    Reconectar { sala: String, token: String },
+    Jogar { lance: String },
+    Desistir,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "tipo", rename_all = "snake_case")]
+pub enum Evento {
+    BemVindo { sala: String, cor: Option<String>, token: Option<String> },
+    Estado(Estado),
+    /// `vencedor` é nulo em caso de empate.
+    Fim { vencedor: Option<String>, motivo: String },
+    Erro { mensagem: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Estado {
+    /// Oito linhas de cima para baixo: `b`/`p` para pedras, `B`/`P` para damas e `.` para casas vazias.
+    pub tabuleiro: Vec<String>,
+    pub fen: String,
+    pub vez: String,
+    /// Jogadas possíveis do lado da vez em notação numérica; vazia depois do fim.
+    pub jogadas: Vec<String>,
+    pub contador_empate: f32,
+    pub ultimo_lance: Option<String>,
+    pub branco: Option<String>,
+    pub preto: Option<String>,
+    pub espectadores: usize,
+    pub relogio: Relogio,
+}
+
+/// Tempo restante de cada lado, em milissegundos.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Relogio {
+    pub branco: u64,
+    pub preto: u64,
+}
+
+fn nome_da_cor(jogador: Cor) -> String {
+    cor(jogador).to_lowercase()
+}
+
+fn jogador(assento: usize) -> Cor {
+    if assento == 0 { Cor::Branco } else { Cor::Preto }
+}
+
+// Não é criptográfico, mas as chaves de cada `RandomState` vêm do sistema operacional
+fn novo_token() -> String {
+    let estado = RandomState::new();
+    format!("{:016x}{:016x}", estado.hash_one(1u8), estado.hash_one(2u8))
+}
+
+type Saida = Sender<String>;
+
+fn enviar(saida: &Saida, evento: &Evento) {
+    // Uma conexão que caiu é tratada pela própria thread
+    saida.send(serde_json::to_string(evento).expect("eventos sempre viram JSON")).ok();
+}
+
+struct Assento {
+    nome: String,
+    token: String,
+    /// Conexão atual do jogador; `None` enquanto ele estiver desconectado.
+    conexao: Option<(u64, Saida)>,
+}
+
+struct Sala {
+    partida: Partida,
+    assentos: [Option<Assento>; 2],
+    espectadores: Vec<(u64, Saida)>,
+    ultimo_lance: Option<String>,
+    /// Tempo restante de cada lado no instante `desde`.
+    relogio: [Duration; 2],
+    /// Desde quando corre o relógio da vez; `None` antes dos dois jogadores chegarem e depois do fim.
+    desde: Option<Instant>,
+    fim: Option<Evento>,
+}
+
+impl Sala {
+    fn new(tempo: Duration) -> Self {
+        Sala {
+            partida: Partida::default(),
+            assentos: [None, None],
+            espectadores: vec![],
+            ultimo_lance: None,
+            relogio: [tempo; 2],
+            desde: None,
+            fim: None,
+        }
+    }
+
+    fn restante(&self, jogador: Cor) -> Duration {
+        let tempo = self.relogio[indice(jogador)];
+        match self.desde {
+            Some(desde) if *self.partida.get_vez() == jogador => tempo.saturating_sub(desde.elapsed()),
+            _ => tempo,
+        }
+    }
+
+    fn estado(&self) -> Estado {
+        let tabuleiro = self
+            .partida
+            .get_tabuleiro()
+            .iter()
+            .map(|linha| {
+                linha
+                    .iter()
+                    .map(|casa| match casa {
+                        Casa::Vazia => '.',
+                        Casa::Ocupada(Pedra::Branca) => 'b',
+                        Casa::Ocupada(Pedra::Preta) => 'p',
+                        Casa::Ocupada(Pedra::DamaBranca) => 'B',
+                        Casa::Ocupada(Pedra::DamaPreta) => 'P',
+                    })
+                    .collect()
+            })
+            .collect();
+        let jogadas = self.partida.todas_jogadas_possiveis().iter().map(|sequencia| notacao(sequencia)).collect();
+        let nome = |assento: usize| self.assentos[assento].as_ref().map(|assento| assento.nome.clone());
+        Estado {
+            tabuleiro,
+            fen: self.partida.fen(),
+            vez: nome_da_cor(*self.partida.get_vez()),
+            jogadas,
+            contador_empate: self.partida.get_contador_empate(),
+            ultimo_lance: self.ultimo_lance.clone(),
+            branco: nome(0),
+            preto: nome(1),
+            espectadores: self.espectadores.len(),
+            relogio: Relogio {
+                branco: self.restante(Cor::Branco).as_millis() as u64,
+                preto: self.restante(Cor::Preto).as_millis() as u64,
+            },
+        }
+    }
+
+    fn conexoes(&self) -> impl Iterator<Item = &(u64, Saida)> {
+        self.assentos
+            .iter()
+            .filter_map(|assento| assento.as_ref().and_then(|assento| assento.conexao.as_ref()))
+            .chain(&self.espectadores)
+    }
+
+    fn todos(&self, evento: &Evento) {
+        for (_, saida) in self.conexoes() {
+            enviar(saida, evento);
+        }
+    }
+
+    fn publicar_estado(&self) {
+        self.todos(&Evento::Estado(self.estado()));
+    }
+
+    // Para o relógio e avisa o fim, se a partida terminou
+    fn encerrar(&mut self) {
+        let estado = self.partida.estado();
+        let Some(motivo) = Motivo::do_estado(estado) else { return };
+        let vez = *self.partida.get_vez();
+        self.relogio[indice(vez)] = self.restante(vez);
+        self.desde = None;
+        self.fim = Some(Evento::Fim { vencedor: estado.vencedor().map(nome_da_cor), motivo: motivo.nome().to_string() });
+        self.publicar_estado();
+        if let Some(fim) = &self.fim {
+            self.todos(fim);
+        }
+    }
+
+    fn jogar(&mut self, jogador: Cor, lance: &str) -> Result<(), String> {
+        if self.fim.is_some() {
+            return Err("a partida já acabou".to_string());
+        }
+        if self.desde.is_none() {
+            return Err("esperando o adversário".to_string());
+        }
+        if *self.partida.get_vez() != jogador {
+            return Err("não é a sua vez".to_string());
+        }
+        let escolhida = self.partida.encontrar_jogada(lance).map_err(|erro| erro.to_string())?;
+        self.relogio[indice(jogador)] = self.restante(jogador);
+        if self.relogio[indice(jogador)].is_zero() {
+            self.partida.perder_por_tempo(jogador).map_err(|erro| erro.to_string())?;
+            self.encerrar();
+            return Ok(());
+        }
+        self.desde = Some(Instant::now());
+        self.ultimo_lance = Some(notacao(&self.partida.todas_jogadas_possiveis()[escolhida]));
+        match self.partida.jogar(escolhida) {
+            Resultado::FimDoJogo(_) => self.encerrar(),
+            _ => self.publicar_estado(),
+        }
+        Ok(())
+    }
+
+    fn verificar_relogio(&mut self) {
+        let vez = *self.partida.get_vez();
+        if self.desde.is_some() && self.restante(vez).is_zero() && self.partida.perder_por_tempo(vez).is_ok() {
+            self.encerrar();
+        }
+    }
+}
+
+/// Uma conexão aceita e o lugar que ela ocupa, se já entrou numa sala.
+struct Conexao {
+    id: u64,
+    saida: Saida,
+    lugar: Option<(String, Option<Cor>)>,
+}
+
+struct Salas {
+    salas: HashMap<String, Sala>,
+    tempo: Duration,
+}
+
+impl Salas {
+    fn tratar(&mut self, conexao: &mut Conexao, pedido: Pedido) -> Result<(), String> {
+        match pedido {
+            Pedido::Entrar { .. } | Pedido::Reconectar { .. } if conexao.lugar.is_some() => {
+                Err("você já está numa sala".to_string())
+            }
+            Pedido::Entrar { sala: nome_da_sala, nome, espectador } => {
+                let tempo = self.tempo;
+                let sala = self.salas.entry(nome_da_sala.clone()).or_insert_with(|| Sala::new(tempo));
+                let livre = if espectador { None } else { sala.assentos.iter().position(Option::is_none) };
+                let (cor, token) = match livre {
+                    Some(assento) => {
+                        let token = novo_token();
+                        let conectado = Some((conexao.id, conexao.saida.clone()));
+                        sala.assentos[assento] = Some(Assento { nome, token: token.clone(), conexao: conectado });
+                        if sala.assentos.iter().all(Option::is_some) && sala.fim.is_none() {
+                            sala.desde = Some(Instant::now());
+                        }
+                        (Some(jogador(assento)), Some(token))
+                    }
+                    // Sala cheia: quem queria jogar assiste
+                    None => {
+                        sala.espectadores.push((conexao.id, conexao.saida.clone()));
+                        (None, None)
+                    }
+                };
+                enviar(&conexao.saida, &Evento::BemVindo { sala: nome_da_sala.clone(), cor: cor.map(nome_da_cor), token });
+                sala.publicar_estado();
+                if let Some(fim) = &sala.fim {
+                    enviar(&conexao.saida, fim);
+                }
+                conexao.lugar = Some((nome_da_sala, cor));
+                Ok(())
+            }
+            Pedido::Reconectar { sala: nome_da_sala, token } => {
+                let sala = self.salas.get_mut(&nome_da_sala).ok_or("sala desconhecida")?;
+                let assento = sala
+                    .assentos
+                    .iter()
+                    .position(|assento| assento.as_ref().is_some_and(|assento| assento.token == token))
+                    .ok_or("token inválido")?;
+                if let Some(assento) = &mut sala.assentos[assento] {
+                    assento.conexao = Some((conexao.id, conexao.saida.clone()));
+                }
+                let cor = Some(nome_da_cor(jogador(assento)));
+                enviar(&conexao.saida, &Evento::BemVindo { sala: nome_da_sala.clone(), cor, token: Some(token) });
+                enviar(&conexao.saida, &Evento::Estado(sala.estado()));
+                if let Some(fim) = &sala.fim {
+                    enviar(&conexao.saida, fim);
+                }
+                conexao.lugar = Some((nome_da_sala, Some(jogador(assento))));
+                Ok(())
+            }
+            Pedido::Jogar { lance } => {
+                let (sala, jogador) = self.assento(conexao)?;
+                sala.jogar(jogador, &lance)
+            }
+            Pedido::Desistir => {
+                let (sala, jogador) = self.assento(conexao)?;
+                if sala.fim.is_some() {
+                    return Err("a partida já acabou".to_string());
+                }
+                sala.partida.desistir(jogador).map_err(|erro| erro.to_string())?;
+                sala.encerrar();
+                Ok(())
+            }
+        }
+    }
+
+    fn assento(&mut self, conexao: &Conexao) -> Result<(&mut Sala, Cor), String> {
+        match &conexao.lugar {
+            Some((nome, Some(jogador))) => {
+                let sala = self.salas.get_mut(nome).ok_or("sala desconhecida")?;
+                // Depois de uma reconexão com o token, a conexão antiga não joga mais
+                let dono = sala.assentos[indice(*jogador)].as_ref().and_then(|assento| assento.conexao.as_ref());
+                if dono.is_none_or(|(id, _)| *id != conexao.id) {
+                    return Err("o assento foi assumido por outra conexão".to_string());
+                }
+                Ok((sala, *jogador))
+            }
+            Some((_, None)) => Err("espectadores não jogam".to_string()),
+            None => Err("entre numa sala primeiro".to_string()),
+        }
+    }
+
+    fn sair(&mut self, conexao: &Conexao) {
+        let Some((nome, _)) = &conexao.lugar else { return };
+        let Some(sala) = self.salas.get_mut(nome) else { return };
+        sala.espectadores.retain(|(id, _)| *id != conexao.id);
+        // O assento continua reservado para quem tiver o token
+        for assento in sala.assentos.iter_mut().flatten() {
+            if assento.conexao.as_ref().is_some_and(|(id, _)| *id == conexao.id) {
+                assento.conexao = None;
+            }
+        }
+        if sala.fim.is_some() && sala.conexoes().next().is_none() {
+            self.salas.remove(nome);
+        }
+    }
+}
+
+pub struct ServidorWs {
+    listener: TcpListener,
+    tempo: Duration,
+}
+
+impl ServidorWs {
+    pub fn escutar(endereco: impl ToSocketAddrs) -> io::Result<ServidorWs> {
+        Ok(ServidorWs { listener: TcpListener::bind(endereco)?, tempo: TEMPO_PADRAO })
+    }
+
+    /// Tempo de cada jogador nas salas novas.
+    pub fn com_tempo(mut self, tempo: Duration) -> Self {
+        self.tempo = tempo;
+        self
+    }
+
+    /// Endereço em que o servidor está escutando, útil quando a porta é 0.
+    pub fn endereco(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Aceita conexões para sempre; cada uma é atendida numa thread própria.
+    pub fn servir(self) -> io::Result<()> {
+        let salas = Arc::new(Mutex::new(Salas { salas: HashMap::new(), tempo: self.tempo }));
+        let relogios = Arc::clone(&salas);
+        thread::spawn(move || loop {
+            thread::sleep(ESPERA);
+            for sala in relogios.lock().unwrap().salas.values_mut() {
+                sala.verificar_relogio();
+            }
+        });
+        for (id, conexao) in self.listener.incoming().enumerate() {
+            let conexao = match conexao {
+                Ok(conexao) => conexao,
+                Err(_) => continue,
+            };
+            let salas = Arc::clone(&salas);
+            thread::spawn(move || atender(conexao, salas, id as u64));
+        }
+        Ok(())
+    }
+}
+
+// O WebSocket síncrono não se divide em leitura e escrita, então a thread
+// alterna entre ler com timeout curto e esvaziar a caixa de saída.
+fn atender(conexao: TcpStream, salas: Arc<Mutex<Salas>>, id: u64) {
+    let mut ws = match tungstenite::accept(conexao) {
+        Ok(ws) => ws,
+        Err(_) => return,
+    };
+    if ws.get_ref().set_read_timeout(Some(ESPERA)).is_err() {
+        return;
+    }
+    let (saida, caixa) = mpsc::channel();
+    let mut conexao = Conexao { id, saida, lugar: None };
+    'conexao: loop {
+        for texto in caixa.try_iter() {
+            if ws.send(Message::text(texto)).is_err() {
+                break 'conexao;
+            }
+        }
+        match ws.read() {
+            Ok(Message::Text(texto)) => {
+                let resposta = match serde_json::from_str::<Pedido>(&texto) {
+                    Ok(pedido) => salas.lock().unwrap().tratar(&mut conexao, pedido),
+                    Err(erro) => Err(format!("pedido inválido: {erro}")),
+                };
+                if let Err(mensagem) = resposta {
+                    enviar(&conexao.saida, &Evento::Erro { mensagem });
+                }
+            }
+            Ok(_) => (),
+            Err(tungstenite::Error::Io(erro)) if matches!(erro.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => (),
+            Err(_) => break,
+        }
+    }
+    salas.lock().unwrap().sair(&conexao);
+}
+
+/// Cliente síncrono para o `ServidorWs`, usado nos testes e por bots.
+pub struct ClienteWs {
+    ws: WebSocket<MaybeTlsStream<TcpStream>>,
+}
+
+impl ClienteWs {
+    /// Conecta a uma URL como `ws://127.0.0.1:7879`.
+    pub fn conectar(url: &str) -> io::Result<ClienteWs> {
+        let (ws, _) = tungstenite::connect(url).map_err(io::Error::other)?;
+        Ok(ClienteWs { ws })
+    }
+
+    pub fn enviar(&mut self, pedido: &Pedido) -> io::Result<()> {
+        let texto = serde_json::to_string(pedido)?;
+        self.ws.send(Message::text(texto)).map_err(io::Error::other)
+    }
+
+    /// Bloqueia até o próximo evento do servidor.
+    pub fn receber(&mut self) -> io::Result<Evento> {
+        loop {
+            if let Message::Text(texto) = self.ws.read().map_err(io::Error::other)? {
+                return Ok(serde_json::from_str(&texto)?);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+fn iniciar(tempo: Duration) -> String {
+    let servidor = ServidorWs::escutar("127.0.0.1:0").unwrap().com_tempo(tempo);
+    let url = format!("ws://{}", servidor.endereco().unwrap());
+    thread::spawn(move || servidor.servir());
+    url
+}
+
+#[cfg(test)]
+fn entrar(url: &str, nome: &str, espectador: bool) -> (ClienteWs, Option<String>, Option<String>) {
+    let mut cliente = ClienteWs::conectar(url).unwrap();
+    let pedido = Pedido::Entrar { sala: "mesa".to_string(), nome: nome.to_string(), espectador };
+    cliente.enviar(&pedido).unwrap();
+    match cliente.receber().unwrap() {
+        Evento::BemVindo { cor, token, .. } => (cliente, cor, token),
+        evento => panic!("esperava bem_vindo, veio {evento:?}"),
+    }
+}
+
+#[cfg(test)]
+fn estado(cliente: &mut ClienteWs) -> Estado {
+    match cliente.receber().unwrap() {
+        Evento::Estado(estado) => estado,
+        evento => panic!("esperava estado, veio {evento:?}"),
+    }
+}
+
+#[test]
+fn testar_sala_com_espectador() {
+    let url = iniciar(TEMPO_PADRAO);
+    let (mut ana, cor, token) = entrar(&url, "ana", false);
+    assert_eq!(cor.as_deref(), Some("branco"));
+    estado(&mut ana);
+    let (mut bia, cor, _) = entrar(&url, "bia", false);
+    assert_eq!(cor.as_deref(), Some("preto"));
+    let (mut carla, cor, _) = entrar(&url, "carla", true);
+    assert_eq!(cor, None);
+    let inicial = estado(&mut carla);
+    assert_eq!((inicial.branco.as_deref(), inicial.preto.as_deref()), (Some("ana"), Some("bia")));
+    assert_eq!(inicial.espectadores, 1);
+    assert_eq!(inicial.tabuleiro[7], "b.b.b.b.");
+    for cliente in [&mut ana, &mut bia] {
+        estado(cliente);
+        estado(cliente);
+    }
+
+    ana.enviar(&Pedido::Jogar { lance: "22-15".to_string() }).unwrap();
+    assert!(matches!(ana.receber().unwrap(), Evento::Erro { .. }));
+    carla.enviar(&Pedido::Jogar { lance: "22-18".to_string() }).unwrap();
+    assert!(matches!(carla.receber().unwrap(), Evento::Erro { .. }));
+    ana.enviar(&Pedido::Jogar { lance: "c3-d4".to_string() }).unwrap();
+    let mut fen = String::new();
+    for cliente in [&mut ana, &mut bia, &mut carla] {
+        let estado = estado(cliente);
+        assert_eq!(estado.vez, "preto");
+        assert_eq!(estado.ultimo_lance.as_deref(), Some("22-18"));
+        assert!(estado.jogadas.contains(&"11-15".to_string()));
+        fen = estado.fen;
+    }
+
+    // Ana cai e volta ao assento com o token
+    drop(ana);
+    let mut ana = ClienteWs::conectar(&url).unwrap();
+    ana.enviar(&Pedido::Reconectar { sala: "mesa".to_string(), token: token.clone().unwrap() }).unwrap();
+    assert!(matches!(ana.receber().unwrap(), Evento::BemVindo { cor: Some(cor), .. } if cor == "branco"));
+    assert_eq!(estado(&mut ana).fen, fen);
+
+    // Outra conexão com o mesmo token assume o assento e a antiga não joga mais
+    let mut outra = ClienteWs::conectar(&url).unwrap();
+    outra.enviar(&Pedido::Reconectar { sala: "mesa".to_string(), token: token.unwrap() }).unwrap();
+    assert!(matches!(outra.receber().unwrap(), Evento::BemVindo { .. }));
+    estado(&mut outra);
+    ana.enviar(&Pedido::Desistir).unwrap();
+    assert!(matches!(ana.receber().unwrap(), Evento::Erro { mensagem } if mensagem.contains("outra conexão")));
+    let mut ana = outra;
+
+    bia.enviar(&Pedido::Desistir).unwrap();
+    let fim = Evento::Fim { vencedor: Some("branco".to_string()), motivo: "desistencia".to_string() };
+    for cliente in [&mut ana, &mut bia, &mut carla] {
+        assert!(estado(cliente).jogadas.is_empty());
+        assert_eq!(cliente.receber().unwrap(), fim);
+    }
+}
+
+#[test]
+fn testar_relogio() {
+    let url = iniciar(Duration::from_millis(200));
+    let (mut ana, _, _) = entrar(&url, "ana", false);
+    let (_bia, _, _) = entrar(&url, "bia", false);
+    estado(&mut ana);
+    estado(&mut ana);
+    let ultimo = estado(&mut ana);
+    assert_eq!(ultimo.relogio.branco, 0);
+    assert!(ultimo.jogadas.is_empty());
+    let fim = Evento::Fim { vencedor: Some("preto".to_string()), motivo: "tempo".to_string() };
+    assert_eq!(ana.receber().unwrap(), fim);
+}
@@ -0,0 +1,234 @@
+use crate::notacao::caminho;
+use crate::{Casa, Coord, Jogada, Partida, Pedra};
+
+// Desenho do tabuleiro em texto. O mesmo renderizador serve a linha de comando,
+// os logs e os testes; `Display for Partida` usa a configuração padrão.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Coordenadas {
+    Nenhuma,
+    /// Índices 0..=7 de coluna e linha, como em `Coord`.
+    Indices,
+    /// Número 1..=32 dentro de cada casa escura vazia.
+    Numeros,
+    /// Colunas a..h e linhas 1..8.
+    Algebrica,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Estilo {
+    /// x/o para as pedras, X/O para as damas.
+    Ascii,
+    Unicode,
+    /// Unicode com as casas coloridas por códigos ANSI.
+    Ansi,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Renderizador {
+    /// Desenha do ponto de vista do preto.
+    pub virado: bool,
+    pub coordenadas: Coordenadas,
+    pub estilo: Estilo,
+    /// Destaca a origem e o destino da última jogada.
+    pub ultima_jogada: bool,
+    /// Marca as casas das pedras capturadas na última jogada.
+    pub capturas: bool,
+}
+
+impl Default for Renderizador {
+    fn default() -> Self {
+        Renderizador {
+            virado: false,
+            coordenadas: Coordenadas::Indices,
+            estilo: Estilo::Ascii,
+            ultima_jogada: false,
+            capturas: false,
+        }
+    }
+}
+
+const ANSI_CLARA: &str = "\x1b[48;2;222;200;160m";
+const ANSI_ESCURA: &str = "\x1b[48;2;120;80;50m";
+const ANSI_ULTIMA: &str = "\x1b[48;2;190;170;40m";
+const ANSI_CAPTURA: &str = "\x1b[48;2;190;60;50m";
+const ANSI_BRANCA: &str = "\x1b[97m";
+const ANSI_PRETA: &str = "\x1b[30m";
+const ANSI_NUMERO: &str = "\x1b[37m";
+const ANSI_RESET: &str = "\x1b[0m";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Marca {
+    Nenhuma,
+    Ultima,
+    Captura,
+}
+
+impl Renderizador {
+    pub fn new() -> Self {
+        Renderizador::default()
+    }
+
+    pub fn virado(mut self, virado: bool) -> Self {
+        self.virado = virado;
+        self
+    }
+
+    pub fn coordenadas(mut self, coordenadas: Coordenadas) -> Self {
+        self.coordenadas = coordenadas;
+        self
+    }
+
+    pub fn estilo(mut self, estilo: Estilo) -> Self {
+        self.estilo = estilo;
+        self
+    }
+
+    pub fn ultima_jogada(mut self, marcar: bool) -> Self {
+        self.ultima_jogada = marcar;
+        self
+    }
+
+    pub fn capturas(mut self, marcar: bool) -> Self {
+        self.capturas = marcar;
+        self
+    }
+
+    pub fn renderizar(&self, partida: &Partida) -> String {
+        let ultima: &[Jogada] = partida.historico().last().map(Vec::as_slice).unwrap_or(&[]);
+        let marca = |casa: Coord| {
+            if self.capturas
+                && ultima.iter().any(|jogada| matches!(jogada, Jogada::Capturar(_, c, _) if *c == casa))
+            {
+                Marca::Captura
+            } else if self.ultima_jogada && caminho(ultima).contains(&casa) {
+                Marca::Ultima
+            } else {
+                Marca::Nenhuma
+            }
+        };
+
+        let mut ordem: Vec<i32> = (0..8).collect();
+        if self.virado {
+            ordem.reverse();
+        }
+        let mut buffer = String::new();
+        if let Some(rotulos) = self.rotulos_das_colunas(&ordem) {
+            buffer.push_str(&format!("  {}\n", rotulos.trim_end()));
+        }
+        for &y in &ordem {
+            match self.coordenadas {
+                Coordenadas::Indices => buffer.push_str(&format!("{y} ")),
+                Coordenadas::Algebrica => buffer.push_str(&format!("{} ", 8 - y)),
+                _ => (),
+            }
+            for &x in &ordem {
+                let casa = Coord { x, y };
+                buffer.push_str(&self.celula(partida.casa(casa), casa, marca(casa)));
+            }
+            buffer.push('\n');
+        }
+        buffer
+    }
+
+    fn rotulos_das_colunas(&self, ordem: &[i32]) -> Option<String> {
+        let rotulo = |x: i32| match self.coordenadas {
+            Coordenadas::Indices => Some(x.to_string()),
+            Coordenadas::Algebrica => Some(((b'a' + x as u8) as char).to_string()),
+            _ => None,
+        };
+        ordem.iter().map(|&x| rotulo(x).map(|r| format!(" {r} "))).collect()
+    }
+
+    fn celula(&self, casa: Casa, coord: Coord, marca: Marca) -> String {
+        let escura = coord.numero().is_some();
+        let numero = match (casa, self.coordenadas) {
+            (Casa::Vazia, Coordenadas::Numeros) => coord.numero(),
+            _ => None,
+        };
+        let simbolo = match (self.estilo, casa) {
+            (_, Casa::Vazia) if marca == Marca::Captura => {
+                if self.estilo == Estilo::Ascii { "*" } else { "✕" }
+            }
+            (Estilo::Ascii, Casa::Vazia) => ".",
+            (_, Casa::Vazia) => if escura { "·" } else { " " },
+            (Estilo::Ascii, Casa::Ocupada(pedra)) => match pedra {
+                Pedra::Branca => "x",
+                Pedra::DamaBranca => "X",
+                Pedra::Preta => "o",
+                Pedra::DamaPreta => "O",
+            },
+            (Estilo::Unicode, Casa::Ocupada(pedra)) => match pedra {
+                Pedra::Branca => "⛀",
+                Pedra::DamaBranca => "⛁",
+                Pedra::Preta => "⛂",
+                Pedra::DamaPreta => "⛃",
+            },
+            (Estilo::Ansi, Casa::Ocupada(pedra)) => if pedra.é_dama() { "♛" } else { "●" },
+        };
+        let conteudo = match numero {
+            Some(numero) if marca == Marca::Nenhuma => format!("{numero:^3}"),
+            _ => format!(" {simbolo} "),
+        };
+
+        if self.estilo != Estilo::Ansi {
+            return match marca {
+                Marca::Ultima => format!("[{}]", conteudo.trim()),
+                _ => conteudo,
+            };
+        }
+        let fundo = match marca {
+            Marca::Captura => ANSI_CAPTURA,
+            Marca::Ultima => ANSI_ULTIMA,
+            Marca::Nenhuma if escura => ANSI_ESCURA,
+            Marca::Nenhuma => ANSI_CLARA,
+        };
+        let frente = match casa {
+            Casa::Ocupada(pedra) if pedra.é_branca() => ANSI_BRANCA,
+            Casa::Ocupada(_) => ANSI_PRETA,
+            Casa::Vazia => ANSI_NUMERO,
+        };
+        format!("{fundo}{frente}{conteudo}{ANSI_RESET}")
+    }
+}
+
+#[test]
+fn testar_renderizacao_padrao() {
+    let partida = Partida::default();
+    assert_eq!(
+        partida.to_string(),
+        "   0  1  2  3  4  5  6  7\n\
+         0  .  o  .  o  .  o  .  o \n\
+         1  o  .  o  .  o  .  o  . \n\
+         2  .  o  .  o  .  o  .  o \n\
+         3  .  .  .  .  .  .  .  . \n\
+         4  .  .  .  .  .  .  .  . \n\
+         5  x  .  x  .  x  .  x  . \n\
+         6  .  x  .  x  .  x  .  x \n\
+         7  x  .  x  .  x  .  x  . \n"
+    );
+}
+
+#[test]
+fn testar_renderizacao_virada_com_marcas() {
+    let mut partida = Partida::from_fen("W:W22:B18").unwrap();
+    partida.jogar(0);
+    let desenho = Renderizador::new()
+        .virado(true)
+        .coordenadas(Coordenadas::Algebrica)
+        .ultima_jogada(true)
+        .capturas(true)
+        .renderizar(&partida);
+    assert_eq!(
+        desenho,
+        "   h  g  f  e  d  c  b  a\n\
+         1  .  .  .  .  .  .  .  . \n\
+         2  .  .  .  .  .  .  .  . \n\
+         3  .  .  .  .  . [.] .  . \n\
+         4  .  .  .  .  *  .  .  . \n\
+         5  .  .  . [x] .  .  .  . \n\
+         6  .  .  .  .  .  .  .  . \n\
+         7  .  .  .  .  .  .  .  . \n\
+         8  .  .  .  .  .  .  .  . \n"
+    );
+}
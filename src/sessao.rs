@@ -0,0 +1,327 @@
+use std::collections::hash_map::RandomState;
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::hash::BuildHasher;
+use std::io::{self, BufRead, StdinLock, Stdout, Write};
+
+use crate::bot::Bot;
+use crate::notacao::notacao;
+use crate::{Cor, Estado, Jogada, Partida, Resultado};
+
+// Laço de uma partida entre dois jogadores quaisquer: a sessão pede o lance a
+// quem tem a vez, confere se ele é uma das jogadas possíveis e joga até a
+// partida terminar. Uma sequência vazia é a forma de o jogador desistir.
+
+/// Quem escolhe os lances de um dos lados da partida.
+pub trait Jogador {
+    /// Uma das sequências de `todas_jogadas_possiveis`, ou nenhuma para
+    /// desistir. Só é chamado com a partida em andamento.
+    fn escolher(&mut self, partida: &Partida) -> Vec<Jogada>;
+
+    /// Quando `escolher` devolve nenhuma sequência por não ter conseguido
+    /// escolher, o motivo; None se foi desistência.
+    fn falha(&mut self) -> Option<String> {
+        None
+    }
+}
+
+impl<J: Jogador + ?Sized> Jogador for &mut J {
+    fn escolher(&mut self, partida: &Partida) -> Vec<Jogada> {
+        (**self).escolher(partida)
+    }
+
+    fn falha(&mut self) -> Option<String> {
+        (**self).falha()
+    }
+}
+
+impl<J: Jogador + ?Sized> Jogador for Box<J> {
+    fn escolher(&mut self, partida: &Partida) -> Vec<Jogada> {
+        (**self).escolher(partida)
+    }
+
+    fn falha(&mut self) -> Option<String> {
+        (**self).falha()
+    }
+}
+
+impl Jogador for Bot {
+    fn escolher(&mut self, partida: &Partida) -> Vec<Jogada> {
+        Bot::escolher(self, partida).map_or(vec![], |indice| partida.todas_jogadas_possiveis()[indice].clone())
+    }
+}
+
+/// Jogador que digita os lances, pela notação ou pelo número da lista.
+/// Desiste com "desistir" ou quando a entrada acaba.
+pub struct Humano<E, S> {
+    entrada: E,
+    saida: S,
+}
+
+impl Humano<StdinLock<'static>, Stdout> {
+    pub fn terminal() -> Self {
+        Humano::new(io::stdin().lock(), io::stdout())
+    }
+}
+
+impl<E: BufRead, S: Write> Humano<E, S> {
+    pub fn new(entrada: E, saida: S) -> Self {
+        Humano { entrada, saida }
+    }
+
+    fn pedir(&mut self, partida: &Partida) -> io::Result<Option<usize>> {
+        let jogadas = partida.todas_jogadas_possiveis();
+        for (i, sequencia) in jogadas.iter().enumerate() {
+            writeln!(self.saida, "{i}: {}", notacao(sequencia))?;
+        }
+        loop {
+            write!(self.saida, "Vez do {:?}> ", partida.get_vez())?;
+            self.saida.flush()?;
+            let mut linha = String::new();
+            if self.entrada.read_line(&mut linha)? == 0 {
+                return Ok(None);
+            }
+            let linha = linha.trim();
+            let indice = match linha.parse::<usize>() {
+                _ if linha == "desistir" => return Ok(None),
+                Ok(indice) if indice < jogadas.len() => Ok(indice),
+                Ok(indice) => Err(format!("não há jogada {indice} na lista")),
+                Err(_) => partida.encontrar_jogada(linha).map_err(|erro| erro.to_string()),
+            };
+            match indice {
+                Ok(indice) => return Ok(Some(indice)),
+                Err(erro) => writeln!(self.saida, "Erro: {erro}")?,
+            }
+        }
+    }
+}
+
+impl<E: BufRead, S: Write> Jogador for Humano<E, S> {
+    fn escolher(&mut self, partida: &Partida) -> Vec<Jogada> {
+        // Sem terminal não há como continuar jogando
+        match self.pedir(partida) {
+            Ok(Some(indice)) => partida.todas_jogadas_possiveis()[indice].clone(),
+            Ok(None) | Err(_) => vec![],
+        }
+    }
+}
+
+/// Joga qualquer uma das jogadas possíveis, sorteada.
+#[derive(Debug, Clone)]
+pub struct Aleatorio {
+    estado: u64,
+}
+
+impl Default for Aleatorio {
+    fn default() -> Self {
+        // As chaves de cada `RandomState` vêm do sistema operacional
+        Aleatorio::com_semente(RandomState::new().hash_one(0u8))
+    }
+}
+
+impl Aleatorio {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mesma semente, mesmos lances: para partidas que precisam ser repetidas.
+    pub fn com_semente(semente: u64) -> Self {
+        Aleatorio { estado: semente }
+    }
+
+    // Gerador congruencial linear; os bits altos são os de melhor qualidade
+    fn sortear(&mut self, total: usize) -> usize {
+        self.estado = self.estado.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((self.estado >> 33) % total as u64) as usize
+    }
+}
+
+impl Jogador for Aleatorio {
+    fn escolher(&mut self, partida: &Partida) -> Vec<Jogada> {
+        let jogadas = partida.todas_jogadas_possiveis();
+        if jogadas.is_empty() {
+            return vec![];
+        }
+        jogadas[self.sortear(jogadas.len())].clone()
+    }
+}
+
+/// Joga uma lista fixa de lances em notação e desiste quando ela acaba.
+/// Um lance que não existe na posição é uma falha, não uma desistência.
+#[derive(Debug, Clone, Default)]
+pub struct Roteiro {
+    lances: VecDeque<String>,
+    falha: Option<String>,
+}
+
+impl Roteiro {
+    pub fn new<T: Into<String>>(lances: impl IntoIterator<Item = T>) -> Self {
+        Roteiro { lances: lances.into_iter().map(Into::into).collect(), falha: None }
+    }
+
+    /// Lances que ainda não foram jogados.
+    pub fn restantes(&self) -> usize {
+        self.lances.len()
+    }
+}
+
+impl Jogador for Roteiro {
+    fn escolher(&mut self, partida: &Partida) -> Vec<Jogada> {
+        let Some(lance) = self.lances.pop_front() else {
+            return vec![];
+        };
+        match partida.encontrar_jogada(&lance) {
+            Ok(indice) => partida.todas_jogadas_possiveis()[indice].clone(),
+            Err(erro) => {
+                self.falha = Some(format!("roteiro: {erro} em {}", partida.fen()));
+                vec![]
+            }
+        }
+    }
+
+    fn falha(&mut self) -> Option<String> {
+        self.falha.take()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErroSessao {
+    /// O jogador devolveu uma sequência que não está entre as jogadas possíveis.
+    JogadaIlegal { vez: Cor, sequencia: Vec<Jogada> },
+    /// O jogador não conseguiu escolher um lance.
+    Falha { vez: Cor, erro: String },
+}
+
+impl Display for ErroSessao {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cor = |vez: &Cor| if *vez == Cor::Branco { "brancas" } else { "pretas" };
+        match self {
+            ErroSessao::JogadaIlegal { vez, sequencia } => {
+                write!(f, "as {} tentaram {}, que não é uma jogada possível", cor(vez), notacao(sequencia))
+            }
+            ErroSessao::Falha { vez, erro } => write!(f, "as {} não conseguiram jogar: {erro}", cor(vez)),
+        }
+    }
+}
+
+impl std::error::Error for ErroSessao {}
+
+/// Conduz uma partida entre dois jogadores, do lance atual até o fim.
+pub struct Sessao<B, P> {
+    partida: Partida,
+    branco: B,
+    preto: P,
+}
+
+impl<B: Jogador, P: Jogador> Sessao<B, P> {
+    /// Sessão a partir da posição inicial.
+    pub fn new(branco: B, preto: P) -> Self {
+        Sessao { partida: Partida::default(), branco, preto }
+    }
+
+    /// Continua a partida dada, com a posição e o histórico dela.
+    pub fn com_partida(mut self, partida: Partida) -> Self {
+        self.partida = partida;
+        self
+    }
+
+    pub fn partida(&self) -> &Partida {
+        &self.partida
+    }
+
+    pub fn into_partida(self) -> Partida {
+        self.partida
+    }
+
+    /// Pede e joga um lance de quem tem a vez. Com a partida já encerrada não faz nada.
+    pub fn jogar_lance(&mut self) -> Result<Estado, ErroSessao> {
+        if self.partida.encerrada() {
+            return Ok(self.partida.estado());
+        }
+        let vez = *self.partida.get_vez();
+        let jogador: &mut dyn Jogador = match vez {
+            Cor::Branco => &mut self.branco,
+            Cor::Preto => &mut self.preto,
+        };
+        let sequencia = jogador.escolher(&self.partida);
+        if sequencia.is_empty() {
+            if let Some(erro) = jogador.falha() {
+                return Err(ErroSessao::Falha { vez, erro });
+            }
+            // A partida está em andamento, então desistir sempre é aceito
+            self.partida.desistir(vez).ok();
+            return Ok(self.partida.estado());
+        }
+        let indice = self.partida.todas_jogadas_possiveis().iter().position(|possivel| *possivel == sequencia);
+        match indice.map(|indice| self.partida.jogar(indice)) {
+            None | Some(Resultado::Falha) => Err(ErroSessao::JogadaIlegal { vez, sequencia }),
+            Some(_) => Ok(self.partida.estado()),
+        }
+    }
+
+    /// Joga até o fim e devolve como a partida terminou.
+    pub fn jogar(&mut self) -> Result<Estado, ErroSessao> {
+        loop {
+            let estado = self.jogar_lance()?;
+            if estado.encerrada() {
+                return Ok(estado);
+            }
+        }
+    }
+}
+
+#[test]
+fn testar_sessao() {
+    use crate::{MotivoVitoria, Pedra};
+
+    // Roteiros até o bloqueio das pretas
+    let mut sessao = Sessao::new(Roteiro::new(["30-26"]), Roteiro::default()).com_partida(Partida::from_fen("W:W9,14,30:B5").unwrap());
+    assert_eq!(sessao.jogar(), Ok(Estado::Vitoria(Cor::Branco, MotivoVitoria::SemJogadas)));
+    assert_eq!(sessao.partida().historico().len(), 1);
+
+    // O roteiro das pretas acaba depois da troca e elas desistem
+    let mut brancas = Roteiro::new(["22-18", "18x11"]);
+    let mut sessao = Sessao::new(&mut brancas, Roteiro::new(["11-15"]));
+    assert_eq!(sessao.jogar(), Ok(Estado::Vitoria(Cor::Branco, MotivoVitoria::Desistencia)));
+    assert_eq!(sessao.partida().historico().len(), 3);
+    assert_eq!(brancas.restantes(), 0);
+
+    // Um lance do roteiro que não existe é erro da sessão, não desistência
+    let mut sessao = Sessao::new(Roteiro::new(["22-18"]), Roteiro::new(["21-17"]));
+    let erro = sessao.jogar().unwrap_err();
+    assert!(matches!(&erro, ErroSessao::Falha { vez: Cor::Preto, erro } if erro.contains("'21-17'")));
+    assert!(!sessao.partida().encerrada());
+
+    // Aleatórios jogam sempre lances legais até o fim, e a semente repete a partida
+    let partida = |semente| {
+        let mut sessao = Sessao::new(Aleatorio::com_semente(semente), Aleatorio::com_semente(semente + 1));
+        let estado = sessao.jogar().unwrap();
+        assert!(estado.encerrada() && !matches!(estado, Estado::Vitoria(_, MotivoVitoria::Desistencia)));
+        sessao.into_partida()
+    };
+    assert_eq!(partida(7).historico(), partida(7).historico());
+    let mut sessao = Sessao::new(Box::new(Bot::new(2)) as Box<dyn Jogador>, Aleatorio::new());
+    assert!(sessao.jogar().unwrap().encerrada());
+
+    // O humano erra, joga pelo número e desiste quando a entrada acaba
+    let mut saida = vec![];
+    let humano = Humano::new(io::Cursor::new("22-14\n22-18\n0\n"), &mut saida);
+    let mut sessao = Sessao::new(humano, Roteiro::new(["11-15", "8x15"]));
+    assert_eq!(sessao.jogar(), Ok(Estado::Vitoria(Cor::Preto, MotivoVitoria::Desistencia)));
+    assert_eq!(sessao.partida().historico().len(), 4);
+    assert!(String::from_utf8(saida).unwrap().contains("Erro: '22-14' não é uma jogada possível"));
+
+    // Quem devolve uma sequência fora da lista é recusado
+    struct Trapaceiro;
+    impl Jogador for Trapaceiro {
+        fn escolher(&mut self, partida: &Partida) -> Vec<Jogada> {
+            let origem = crate::Coord::de_numero(22).unwrap();
+            assert_eq!(partida.casa(origem).pedra(), Some(Pedra::Branca));
+            vec![Jogada::Mover(origem, crate::Coord::de_numero(14).unwrap())]
+        }
+    }
+    let mut sessao = Sessao::new(Trapaceiro, Aleatorio::new());
+    let erro = sessao.jogar().unwrap_err();
+    assert_eq!(erro.to_string(), "as brancas tentaram 22-14, que não é uma jogada possível");
+    assert!(sessao.partida().historico().is_empty());
+}
@@ -0,0 +1,529 @@
+use crate::bot::Bot;
+use crate::confronto;
+use crate::pdn;
+use crate::{Cor, Estado, Partida};
+
+use std::collections::HashSet;
+use std::fmt::{self, Display, Write};
+
+// Torneios do clube: inscrição, emparceiramento por rodada (todos contra todos
+// pelas tabelas de Berger, ou suíço no estilo do sistema holandês), registro
+// dos resultados e classificação com desempates.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sistema {
+    /// Todos contra todos, pelas tabelas de Berger.
+    TodosContraTodos,
+    /// Suíço com o número de rodadas dado.
+    Suico(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tipo {
+    Humano,
+    /// Bots jogam entre si sozinhos, com `Torneio::jogar_bots`.
+    Bot(Bot),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inscrito {
+    pub nome: String,
+    pub tipo: Tipo,
+    /// Rating usado para ordenar a primeira rodada do suíço.
+    pub rating: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Desfecho {
+    VitoriaBranco,
+    VitoriaPreto,
+    Empate,
+}
+
+impl Desfecho {
+    /// O resultado de uma partida terminada, ou None se ela ainda está em andamento.
+    pub fn da_partida(partida: &Partida) -> Option<Desfecho> {
+        match partida.estado() {
+            Estado::EmAndamento => None,
+            estado => Some(Desfecho::do_vencedor(estado.vencedor())),
+        }
+    }
+
+    pub fn do_vencedor(vencedor: Option<Cor>) -> Desfecho {
+        match vencedor {
+            Some(Cor::Branco) => Desfecho::VitoriaBranco,
+            Some(Cor::Preto) => Desfecho::VitoriaPreto,
+            None => Desfecho::Empate,
+        }
+    }
+
+    pub fn vencedor(self) -> Option<Cor> {
+        match self {
+            Desfecho::VitoriaBranco => Some(Cor::Branco),
+            Desfecho::VitoriaPreto => Some(Cor::Preto),
+            Desfecho::Empate => None,
+        }
+    }
+
+    /// Pontos das brancas e das pretas.
+    fn pontos(&self) -> (f32, f32) {
+        match self {
+            Desfecho::VitoriaBranco => (1.0, 0.0),
+            Desfecho::VitoriaPreto => (0.0, 1.0),
+            Desfecho::Empate => (0.5, 0.5),
+        }
+    }
+}
+
+impl Display for Desfecho {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", pdn::resultado(self.vencedor()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mesa {
+    /// Índices em `Torneio::inscritos`.
+    pub branco: usize,
+    pub preto: usize,
+    pub desfecho: Option<Desfecho>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Rodada {
+    pub mesas: Vec<Mesa>,
+    /// Quem fica de fora quando o número de inscritos é ímpar.
+    pub folga: Option<usize>,
+}
+
+impl Rodada {
+    pub fn completa(&self) -> bool {
+        self.mesas.iter().all(|mesa| mesa.desfecho.is_some())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErroTorneio {
+    PoucosInscritos,
+    /// Inscrições só antes da primeira rodada.
+    JaComecou,
+    RodadaIncompleta,
+    Encerrado,
+    /// Não há emparceiramento sem repetir confrontos.
+    SemEmparceiramento,
+    MesaInexistente { rodada: usize, mesa: usize },
+    PartidaEmAndamento,
+}
+
+impl Display for ErroTorneio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErroTorneio::PoucosInscritos => write!(f, "são precisos pelo menos dois inscritos"),
+            ErroTorneio::JaComecou => write!(f, "o torneio já começou"),
+            ErroTorneio::RodadaIncompleta => write!(f, "a rodada atual ainda tem partidas sem resultado"),
+            ErroTorneio::Encerrado => write!(f, "todas as rodadas já foram jogadas"),
+            ErroTorneio::SemEmparceiramento => write!(f, "não há emparceiramento sem repetir confrontos"),
+            ErroTorneio::MesaInexistente { rodada, mesa } => write!(f, "rodada {rodada} não tem a mesa {mesa}"),
+            ErroTorneio::PartidaEmAndamento => write!(f, "a partida ainda não terminou"),
+        }
+    }
+}
+
+impl std::error::Error for ErroTorneio {}
+
+/// Uma linha da classificação.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Colocacao {
+    pub inscrito: usize,
+    pub pontos: f32,
+    pub buchholz: f32,
+    pub sonneborn_berger: f32,
+    pub vitorias: u32,
+    pub empates: u32,
+    pub derrotas: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Torneio {
+    pub nome: String,
+    pub sistema: Sistema,
+    pub inscritos: Vec<Inscrito>,
+    pub rodadas: Vec<Rodada>,
+}
+
+impl Torneio {
+    pub fn new(nome: &str, sistema: Sistema) -> Self {
+        Torneio { nome: nome.to_string(), sistema, inscritos: vec![], rodadas: vec![] }
+    }
+
+    /// Inscreve um jogador e devolve o seu índice.
+    pub fn inscrever(&mut self, nome: &str, tipo: Tipo, rating: u32) -> Result<usize, ErroTorneio> {
+        if !self.rodadas.is_empty() {
+            return Err(ErroTorneio::JaComecou);
+        }
+        self.inscritos.push(Inscrito { nome: nome.to_string(), tipo, rating });
+        Ok(self.inscritos.len() - 1)
+    }
+
+    pub fn total_de_rodadas(&self) -> usize {
+        match self.sistema {
+            Sistema::TodosContraTodos => {
+                let n = self.inscritos.len();
+                n + n % 2 - 1
+            }
+            Sistema::Suico(rodadas) => rodadas,
+        }
+    }
+
+    /// Emparceira a próxima rodada; a anterior precisa estar completa.
+    pub fn proxima_rodada(&mut self) -> Result<&Rodada, ErroTorneio> {
+        if self.inscritos.len() < 2 {
+            return Err(ErroTorneio::PoucosInscritos);
+        }
+        if self.rodadas.last().is_some_and(|rodada| !rodada.completa()) {
+            return Err(ErroTorneio::RodadaIncompleta);
+        }
+        if self.rodadas.len() >= self.total_de_rodadas() {
+            return Err(ErroTorneio::Encerrado);
+        }
+        let rodada = match self.sistema {
+            Sistema::TodosContraTodos => berger(self.inscritos.len(), self.rodadas.len()),
+            Sistema::Suico(_) => self.suico()?,
+        };
+        self.rodadas.push(rodada);
+        Ok(self.rodadas.last().unwrap())
+    }
+
+    /// Registra o resultado de uma mesa; `rodada` e `mesa` começam em 1.
+    pub fn registrar(&mut self, rodada: usize, mesa: usize, desfecho: Desfecho) -> Result<(), ErroTorneio> {
+        let m = self
+            .rodadas
+            .get_mut(rodada.wrapping_sub(1))
+            .and_then(|r| r.mesas.get_mut(mesa.wrapping_sub(1)))
+            .ok_or(ErroTorneio::MesaInexistente { rodada, mesa })?;
+        m.desfecho = Some(desfecho);
+        Ok(())
+    }
+
+    /// Registra o resultado de uma partida terminada.
+    pub fn registrar_partida(&mut self, rodada: usize, mesa: usize, partida: &Partida) -> Result<(), ErroTorneio> {
+        let desfecho = Desfecho::da_partida(partida).ok_or(ErroTorneio::PartidaEmAndamento)?;
+        self.registrar(rodada, mesa, desfecho)
+    }
+
+    /// Joga as mesas sem resultado da rodada atual em que os dois lados são bots.
+    /// Devolve quantas partidas foram jogadas.
+    pub fn jogar_bots(&mut self) -> Result<usize, String> {
+        let Some(rodada) = self.rodadas.last_mut() else { return Ok(0) };
+        let mut jogadas = 0;
+        for mesa in rodada.mesas.iter_mut().filter(|mesa| mesa.desfecho.is_none()) {
+            if let (Tipo::Bot(mut branco), Tipo::Bot(mut preto)) =
+                (self.inscritos[mesa.branco].tipo, self.inscritos[mesa.preto].tipo)
+            {
+                let (_, vencedor) = confronto::jogar_partida(&Partida::default(), &mut branco, &mut preto)?;
+                mesa.desfecho = Some(Desfecho::do_vencedor(vencedor));
+                jogadas += 1;
+            }
+        }
+        Ok(jogadas)
+    }
+
+    /// Adversários e pontos de cada partida com resultado do inscrito.
+    fn partidas_de(&self, inscrito: usize) -> impl Iterator<Item = (usize, f32)> + '_ {
+        self.rodadas.iter().flat_map(|rodada| &rodada.mesas).filter_map(move |mesa| {
+            let (branco, preto) = mesa.desfecho?.pontos();
+            if mesa.branco == inscrito {
+                Some((mesa.preto, branco))
+            } else if mesa.preto == inscrito {
+                Some((mesa.branco, preto))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Pontos do inscrito; no suíço, a folga vale uma vitória.
+    pub fn pontos(&self, inscrito: usize) -> f32 {
+        let folgas = match self.sistema {
+            Sistema::Suico(_) => self.rodadas.iter().filter(|rodada| rodada.folga == Some(inscrito)).count(),
+            Sistema::TodosContraTodos => 0,
+        };
+        self.partidas_de(inscrito).map(|(_, pontos)| pontos).sum::<f32>() + folgas as f32
+    }
+
+    /// Soma dos pontos dos adversários.
+    pub fn buchholz(&self, inscrito: usize) -> f32 {
+        self.partidas_de(inscrito).map(|(adversario, _)| self.pontos(adversario)).sum()
+    }
+
+    /// Soma dos pontos dos adversários vencidos mais metade dos que empataram.
+    pub fn sonneborn_berger(&self, inscrito: usize) -> f32 {
+        self.partidas_de(inscrito).map(|(adversario, pontos)| pontos * self.pontos(adversario)).sum()
+    }
+
+    /// Classificação por pontos e depois pelos desempates: Sonneborn-Berger
+    /// antes de Buchholz no todos contra todos, o contrário no suíço.
+    pub fn classificacao(&self) -> Vec<Colocacao> {
+        let mut tabela = (0..self.inscritos.len())
+            .map(|inscrito| {
+                let mut colocacao = Colocacao {
+                    inscrito,
+                    pontos: self.pontos(inscrito),
+                    buchholz: self.buchholz(inscrito),
+                    sonneborn_berger: self.sonneborn_berger(inscrito),
+                    vitorias: 0,
+                    empates: 0,
+                    derrotas: 0,
+                };
+                for (_, pontos) in self.partidas_de(inscrito) {
+                    if pontos == 1.0 {
+                        colocacao.vitorias += 1;
+                    } else if pontos == 0.5 {
+                        colocacao.empates += 1;
+                    } else {
+                        colocacao.derrotas += 1;
+                    }
+                }
+                colocacao
+            })
+            .collect::<Vec<_>>();
+        let suico = matches!(self.sistema, Sistema::Suico(_));
+        tabela.sort_by(|a, b| {
+            let desempates = |c: &Colocacao| {
+                if suico { [c.pontos, c.buchholz, c.sonneborn_berger] } else { [c.pontos, c.sonneborn_berger, c.buchholz] }
+            };
+            desempates(b)
+                .partial_cmp(&desempates(a))
+                .unwrap()
+                .then_with(|| self.inscritos[a.inscrito].nome.cmp(&self.inscritos[b.inscrito].nome))
+        });
+        tabela
+    }
+
+    /// Classificação em CSV, com cabeçalho.
+    pub fn csv(&self) -> String {
+        let mut csv = String::from("posicao,nome,pontos,vitorias,empates,derrotas,sonneborn_berger,buchholz\n");
+        for (i, c) in self.classificacao().iter().enumerate() {
+            let nome = &self.inscritos[c.inscrito].nome;
+            let nome = if nome.contains([',', '"', '\n']) { format!("\"{}\"", nome.replace('"', "\"\"")) } else { nome.clone() };
+            writeln!(
+                csv,
+                "{},{nome},{},{},{},{},{},{}",
+                i + 1,
+                c.pontos,
+                c.vitorias,
+                c.empates,
+                c.derrotas,
+                c.sonneborn_berger,
+                c.buchholz
+            )
+            .unwrap();
+        }
+        csv
+    }
+
+    /// Página HTML com a classificação e os resultados de cada rodada.
+    pub fn html(&self) -> String {
+        let nome = |i: usize| escapar(&self.inscritos[i].nome);
+        let mut html = String::new();
+        writeln!(html, "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>{0}</title></head>\n<body>\n<h1>{0}</h1>", escapar(&self.nome)).unwrap();
+        html.push_str("<table>\n<tr><th>#</th><th>Nome</th><th>Pontos</th><th>V</th><th>E</th><th>D</th><th>SB</th><th>Buchholz</th></tr>\n");
+        for (i, c) in self.classificacao().iter().enumerate() {
+            writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                i + 1,
+                nome(c.inscrito),
+                c.pontos,
+                c.vitorias,
+                c.empates,
+                c.derrotas,
+                c.sonneborn_berger,
+                c.buchholz
+            )
+            .unwrap();
+        }
+        html.push_str("</table>\n");
+        for (i, rodada) in self.rodadas.iter().enumerate() {
+            writeln!(html, "<h2>Rodada {}</h2>\n<table>", i + 1).unwrap();
+            for mesa in &rodada.mesas {
+                let desfecho = mesa.desfecho.map_or("-".to_string(), |d| d.to_string());
+                writeln!(html, "<tr><td>{}</td><td>{desfecho}</td><td>{}</td></tr>", nome(mesa.branco), nome(mesa.preto)).unwrap();
+            }
+            if let Some(folga) = rodada.folga {
+                writeln!(html, "<tr><td colspan=\"3\">Folga: {}</td></tr>", nome(folga)).unwrap();
+            }
+            html.push_str("</table>\n");
+        }
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    // Saldo de cores (brancas menos pretas) e a cor da última partida
+    fn cores(&self, inscrito: usize) -> (i32, Option<Cor>) {
+        let mut saldo = 0;
+        let mut ultima = None;
+        for mesa in self.rodadas.iter().flat_map(|rodada| &rodada.mesas) {
+            if mesa.branco == inscrito {
+                saldo += 1;
+                ultima = Some(Cor::Branco);
+            } else if mesa.preto == inscrito {
+                saldo -= 1;
+                ultima = Some(Cor::Preto);
+            }
+        }
+        (saldo, ultima)
+    }
+
+    // Emparceiramento suíço: ordena por pontos e rating e, em cada grupo de
+    // pontos, a metade de cima enfrenta a de baixo na mesma ordem. Quem não
+    // acha adversário no grupo desce para o próximo.
+    fn suico(&self) -> Result<Rodada, ErroTorneio> {
+        let mut ordem: Vec<usize> = (0..self.inscritos.len()).collect();
+        ordem.sort_by(|&a, &b| {
+            self.pontos(b)
+                .partial_cmp(&self.pontos(a))
+                .unwrap()
+                .then(self.inscritos[b].rating.cmp(&self.inscritos[a].rating))
+                .then(a.cmp(&b))
+        });
+        let mut folga = None;
+        if ordem.len() % 2 == 1 {
+            let teve_folga = |i: usize| self.rodadas.iter().any(|rodada| rodada.folga == Some(i));
+            let posicao = ordem.iter().rposition(|&i| !teve_folga(i)).unwrap_or(ordem.len() - 1);
+            folga = Some(ordem.remove(posicao));
+        }
+        let jogados: HashSet<(usize, usize)> = self
+            .rodadas
+            .iter()
+            .flat_map(|rodada| &rodada.mesas)
+            .flat_map(|mesa| [(mesa.branco, mesa.preto), (mesa.preto, mesa.branco)])
+            .collect();
+        let pares = self.emparelhar(&ordem, &jogados).ok_or(ErroTorneio::SemEmparceiramento)?;
+        let mesas = pares
+            .into_iter()
+            .map(|(a, b)| {
+                let ((saldo_a, ultima_a), (saldo_b, ultima_b)) = (self.cores(a), self.cores(b));
+                let a_de_brancas = if saldo_a != saldo_b {
+                    saldo_a < saldo_b
+                } else if ultima_a != ultima_b {
+                    ultima_a != Some(Cor::Branco)
+                } else {
+                    self.rodadas.len().is_multiple_of(2)
+                };
+                let (branco, preto) = if a_de_brancas { (a, b) } else { (b, a) };
+                Mesa { branco, preto, desfecho: None }
+            })
+            .collect();
+        Ok(Rodada { mesas, folga })
+    }
+
+    fn emparelhar(&self, ordem: &[usize], jogados: &HashSet<(usize, usize)>) -> Option<Vec<(usize, usize)>> {
+        let Some((&primeiro, resto)) = ordem.split_first() else { return Some(vec![]) };
+        let pontos = self.pontos(primeiro);
+        let grupo = ordem.iter().take_while(|&&i| self.pontos(i) == pontos).count();
+        // No grupo, o primeiro da metade de cima prefere o primeiro da metade de baixo
+        let meio = (grupo / 2).max(1);
+        let candidatos = (meio..grupo).chain(1..meio).chain(grupo.max(1)..ordem.len());
+        for candidato in candidatos {
+            let adversario = ordem[candidato];
+            if jogados.contains(&(primeiro, adversario)) {
+                continue;
+            }
+            let restantes: Vec<usize> = resto.iter().copied().filter(|&i| i != adversario).collect();
+            if let Some(mut pares) = self.emparelhar(&restantes, jogados) {
+                pares.insert(0, (primeiro, adversario));
+                return Some(pares);
+            }
+        }
+        None
+    }
+}
+
+// Tabelas de Berger: o último fica fixo e os outros giram; ele alterna de cor a
+// cada rodada e, nas outras mesas, as cores se alternam com o giro.
+fn berger(inscritos: usize, rodada: usize) -> Rodada {
+    let n = inscritos + inscritos % 2;
+    let giro = n - 1;
+    let mut mesas = vec![];
+    let mut folga = None;
+    for mesa in 0..n / 2 {
+        let a = (rodada + mesa) % giro;
+        let b = if mesa == 0 { n - 1 } else { (rodada + giro - mesa) % giro };
+        let (branco, preto) = if mesa == 0 && !rodada.is_multiple_of(2) { (b, a) } else { (a, b) };
+        if branco >= inscritos {
+            folga = Some(preto);
+        } else if preto >= inscritos {
+            folga = Some(branco);
+        } else {
+            mesas.push(Mesa { branco, preto, desfecho: None });
+        }
+    }
+    Rodada { mesas, folga }
+}
+
+fn escapar(texto: &str) -> String {
+    texto.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[test]
+fn testar_todos_contra_todos() {
+    let mut torneio = Torneio::new("Clube", Sistema::TodosContraTodos);
+    for nome in ["Ana", "Bia", "Caio", "Davi", "Eva"] {
+        torneio.inscrever(nome, Tipo::Humano, 1500).unwrap();
+    }
+    assert_eq!(torneio.total_de_rodadas(), 5);
+    let mut confrontos = HashSet::new();
+    while torneio.proxima_rodada().is_ok() {
+        let rodada = torneio.rodadas.len();
+        for mesa in 1..=torneio.rodadas[rodada - 1].mesas.len() {
+            let Mesa { branco, preto, .. } = torneio.rodadas[rodada - 1].mesas[mesa - 1];
+            assert!(confrontos.insert((branco.min(preto), branco.max(preto))));
+            let desfecho = if branco < preto { Desfecho::VitoriaBranco } else { Desfecho::VitoriaPreto };
+            torneio.registrar(rodada, mesa, desfecho).unwrap();
+        }
+        assert!(torneio.rodadas[rodada - 1].folga.is_some());
+    }
+    assert_eq!(confrontos.len(), 10);
+    for inscrito in 0..5 {
+        assert!(torneio.cores(inscrito).0.abs() <= 1);
+    }
+    let tabela = torneio.classificacao();
+    assert_eq!(tabela.iter().map(|c| c.inscrito).collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+    assert_eq!(tabela[0].pontos, 4.0);
+    assert_eq!(tabela[1].sonneborn_berger, 3.0);
+    assert!(torneio.csv().starts_with("posicao,nome,pontos"));
+    assert!(torneio.html().contains("<td>Ana</td>"));
+    assert_eq!(torneio.proxima_rodada(), Err(ErroTorneio::Encerrado));
+}
+
+#[test]
+fn testar_suico() {
+    let mut torneio = Torneio::new("Aberto", Sistema::Suico(3));
+    for (nome, rating) in [("A", 1800), ("B", 1700), ("C", 1600), ("D", 1500), ("E", 1400)] {
+        torneio.inscrever(nome, Tipo::Bot(Bot::new(1)), rating).unwrap();
+    }
+    let rodada = torneio.proxima_rodada().unwrap();
+    assert_eq!(rodada.folga, Some(4));
+    assert_eq!(rodada.mesas.iter().map(|m| (m.branco.min(m.preto), m.branco.max(m.preto))).collect::<Vec<_>>(), [(0, 2), (1, 3)]);
+    assert_eq!(torneio.proxima_rodada(), Err(ErroTorneio::RodadaIncompleta));
+
+    let vitoria = Partida::from_fen("B:W22:B").unwrap();
+    assert_eq!(Desfecho::da_partida(&vitoria), Some(Desfecho::VitoriaBranco));
+    let mut desistencia = Partida::default();
+    desistencia.desistir(Cor::Branco).unwrap();
+    assert_eq!(Desfecho::da_partida(&desistencia), Some(Desfecho::VitoriaPreto));
+    assert_eq!(Desfecho::VitoriaPreto.to_string(), "0-2");
+    assert_eq!(torneio.registrar_partida(1, 1, &Partida::default()), Err(ErroTorneio::PartidaEmAndamento));
+    assert_eq!(torneio.registrar_partida(1, 1, &vitoria), Ok(()));
+    assert_eq!(torneio.jogar_bots(), Ok(1));
+
+    for _ in 1..3 {
+        torneio.proxima_rodada().unwrap();
+        torneio.jogar_bots().unwrap();
+    }
+    let folgas: HashSet<_> = torneio.rodadas.iter().filter_map(|rodada| rodada.folga).collect();
+    assert_eq!(folgas.len(), 3);
+    let total: f32 = (0..5).map(|i| torneio.pontos(i)).sum();
+    assert_eq!(total, 3.0 * 3.0);
+}